@@ -8,7 +8,6 @@ use apchat_models::{ModelColor, Message, Usage, ChatRequest, ChatResponse};
 use apchat_agents::{ToolDefinition, ChatMessage};
 use apchat_logging::{log_request, log_request_to_file, log_response, log_response_to_file, log_raw_response_to_file};
 use apchat_logging::safe_truncate;
-use apchat_toolcore::parse_xml_tool_calls;
 use crate::MAX_RETRIES;
 
 /// Non-streaming API call for Groq-style APIs
@@ -45,6 +44,8 @@ pub(crate) async fn call_api(
         }
     }
 
+    let (messages, tools) = super::messages_with_tools(chat, current_model, messages);
+
     // Retry logic with exponential backoff
     let mut retry_count = 0;
     loop {
@@ -55,7 +56,7 @@ pub(crate) async fn call_api(
                 chat.client_config.get_model_override(ModelColor::RedModel).as_deref().map(|x| x.as_str())
             ).to_string(),
             messages: messages.clone(),
-            tools: chat.get_tools(),
+            tools: tools.clone(),
             tool_choice: "auto".to_string(),
             stream: None,
         };
@@ -160,15 +161,8 @@ pub(crate) async fn call_api(
             .map(|c| c.message)
             .context("No response from API")?;
 
-        // If no structured tool calls were received, check for XML format in content
-        if message.tool_calls.is_none() {
-            if let Some(parsed_calls) = parse_xml_tool_calls(&message.content) {
-                eprintln!("{} Detected XML-format tool calls, parsing {} call(s)", "🔧".bright_yellow(), parsed_calls.len());
-                message.tool_calls = Some(parsed_calls);
-                // Clear the XML from content to avoid displaying it
-                message.content = String::new();
-            }
-        }
+        // If no structured tool calls were received, check for calls written into the content
+        super::apply_text_tool_calls(chat, current_model, &mut message);
 
         return Ok((message, chat_response.usage, current_model));
     }
@@ -229,7 +223,7 @@ pub(crate) async fn call_api_with_llm_client(
     let response = llm_client.chat(chat_messages, tools).await?;
 
     // Convert the response back to the old format
-    let message = Message {
+    let mut message = Message {
         role: response.message.role,
         content: response.message.content,
         tool_calls: response.message.tool_calls.map(|calls| {
@@ -246,6 +240,7 @@ pub(crate) async fn call_api_with_llm_client(
         name: response.message.name,
         reasoning: None,
     };
+    super::apply_text_tool_calls(chat, *model, &mut message);
 
     let usage = response.usage.map(|u| Usage {
         prompt_tokens: u.prompt_tokens as usize,
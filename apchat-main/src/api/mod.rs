@@ -3,3 +3,40 @@ mod client;
 
 pub(crate) use streaming::{call_api_streaming, call_api_streaming_with_llm_client};
 pub(crate) use client::{call_api, call_api_with_llm_client};
+
+use colored::Colorize;
+
+use crate::APChat;
+use apchat_models::{Message, ModelColor, PromptTools, Tool};
+use apchat_toolcore::parse_tool_calls;
+
+/// The messages and tool definitions to send to a model. When its backend has
+/// no native tool support the tools are described in the prompt and earlier
+/// calls and results are rewritten as text, so no definitions are sent.
+pub(crate) fn messages_with_tools(chat: &APChat, model: ModelColor, messages: Vec<Message>) -> (Vec<Message>, Vec<Tool>) {
+    let tools = chat.get_tools();
+    if chat.client_config.get_native_tools(model) {
+        return (messages, tools);
+    }
+    let prompt_tools = PromptTools::new(chat.client_config.get_tool_parser(model));
+    (prompt_tools.prepare_messages(&messages, &tools), Vec::new())
+}
+
+/// When a response has no structured tool calls, use the calls the model
+/// wrote into its text in the dialect configured for its slot, and keep only
+/// the text around them
+pub(crate) fn apply_text_tool_calls(chat: &APChat, model: ModelColor, message: &mut Message) {
+    if message.tool_calls.is_some() {
+        return;
+    }
+    if let Some(parsed) = parse_tool_calls(&message.content, chat.client_config.get_tool_parser(model)) {
+        eprintln!(
+            "{} Detected {}-format tool calls, parsing {} call(s)",
+            "🔧".bright_yellow(),
+            parsed.dialect.as_str(),
+            parsed.calls.len()
+        );
+        message.tool_calls = Some(parsed.calls);
+        message.content = parsed.text;
+    }
+}
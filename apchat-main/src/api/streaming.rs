@@ -6,7 +6,6 @@ use crate::APChat;
 use apchat_models::{ModelColor, Message, Usage, ChatRequest, StreamChunk};
 use apchat_agents::{ToolDefinition, ChatMessage};
 use apchat_logging::{log_request, log_request_to_file, log_response, log_response_to_file, log_raw_response_to_file, log_stream_chunk};
-use apchat_toolcore::parse_xml_tool_calls;
 use crate::{ToolCall, FunctionCall};
 
 /// Handle streaming API response for Groq-style APIs
@@ -25,6 +24,7 @@ pub(crate) async fn call_api_streaming(
         msg.reasoning = None; // Strip reasoning field to avoid compatibility issues
         msg
     }).collect();
+    let (messages, tools) = super::messages_with_tools(chat, current_model, messages);
 
     let request = ChatRequest {
         model: current_model.as_str(
@@ -33,7 +33,7 @@ pub(crate) async fn call_api_streaming(
             chat.client_config.get_model_override(ModelColor::RedModel).as_deref().map(|x| x.as_str())
         ).to_string(),
         messages,
-        tools: chat.get_tools(),
+        tools,
         tool_choice: "auto".to_string(),
         stream: Some(true),
     };
@@ -297,15 +297,8 @@ pub(crate) async fn call_api_streaming(
         reasoning: None,
     };
 
-    // If no structured tool calls were received, check for XML format in content
-    if message.tool_calls.is_none() {
-        if let Some(parsed_calls) = parse_xml_tool_calls(&accumulated_content) {
-            eprintln!("{} Detected XML-format tool calls, parsing {} call(s)", "🔧".bright_yellow(), parsed_calls.len());
-            message.tool_calls = Some(parsed_calls);
-            // Clear the XML from content to avoid displaying it
-            message.content = String::new();
-        }
-    }
+    // If no structured tool calls were received, check for calls written into the content
+    super::apply_text_tool_calls(chat, current_model, &mut message);
 
     Ok((message, usage, current_model))
 }
@@ -410,7 +403,7 @@ pub(crate) async fn call_api_streaming_with_llm_client(
     let response = llm_client.chat(chat_messages, tools).await?;
 
     // Convert the response back to the old format
-    let message = Message {
+    let mut message = Message {
         role: response.message.role,
         content: response.message.content,
         tool_calls: response.message.tool_calls.map(|calls| {
@@ -427,6 +420,7 @@ pub(crate) async fn call_api_streaming_with_llm_client(
         name: response.message.name,
         reasoning: None,
     };
+    super::apply_text_tool_calls(chat, *model, &mut message);
 
     let usage = response.usage.map(|u| Usage {
         prompt_tokens: u.prompt_tokens as usize,
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::cli::Cli;
+use crate::config::{self, initialize_tool_registry};
+use apchat_mcp::McpServer;
+use apchat_terminal::{TerminalManager, MAX_CONCURRENT_SESSIONS};
+use apchat_toolcore::{ArtifactStore, ToolContext};
+
+/// Serve the built-in and custom tools to an MCP client on stdin/stdout until
+/// the client disconnects. Calls are checked against the configured policy;
+/// nobody can be asked on the terminal, so actions the policy would ask about
+/// are refused.
+pub async fn run_mcp_server(cli: &Cli, work_dir: PathBuf) -> Result<()> {
+    let output = protocol_stdout()?;
+
+    let policy_manager = super::setup::policy_manager_from_cli(cli, &work_dir)?;
+
+    let mut registry = initialize_tool_registry();
+    config::register_custom_tools(&mut registry, &work_dir);
+    if let Some(ref profile) = cli.tool_profile {
+        registry.apply_profile(config::load_tool_profiles(&work_dir).get(profile)?);
+    }
+    let registry = Arc::new(registry);
+
+    let logs_dir = apchat_logging::get_logs_dir().unwrap_or_else(|_| PathBuf::from("logs"));
+    let terminal_manager = Arc::new(Mutex::new(TerminalManager::with_backend(
+        logs_dir.join("terminals"),
+        crate::resolve_terminal_backend(cli)?,
+        MAX_CONCURRENT_SESSIONS,
+    )));
+    let todo_manager = Arc::new(apchat_todo::TodoManager::new());
+    let artifacts = Arc::new(ArtifactStore::new(
+        logs_dir.join("artifacts").join(uuid::Uuid::new_v4().to_string()),
+    ));
+    let session_id = format!("mcp_{}", chrono::Utc::now().timestamp());
+
+    eprintln!(
+        "{} Serving {} tools over MCP from {}",
+        "🔌".cyan(),
+        registry.enabled_tool_names().len(),
+        work_dir.display()
+    );
+
+    let server = McpServer::new(Arc::clone(&registry), move || {
+        ToolContext::new(work_dir.clone(), session_id.clone(), policy_manager.clone())
+            .with_terminal_manager(terminal_manager.clone())
+            .with_todo_manager(todo_manager.clone())
+            .with_non_interactive(true)
+            .with_artifact_store(Arc::clone(&artifacts))
+    });
+    server.serve(tokio::io::stdin(), output).await
+}
+
+/// Stdout carries the protocol, but tools print progress there too: point
+/// file descriptor 1 at stderr and return a handle on the original stdout
+#[cfg(unix)]
+fn protocol_stdout() -> Result<tokio::fs::File> {
+    use std::os::unix::io::FromRawFd;
+
+    // SAFETY: plain descriptor calls; the duplicate is owned by the file
+    unsafe {
+        let protocol = libc::dup(libc::STDOUT_FILENO);
+        if protocol < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        if libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            libc::close(protocol);
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(tokio::fs::File::from_std(std::fs::File::from_raw_fd(protocol)))
+    }
+}
+
+#[cfg(not(unix))]
+fn protocol_stdout() -> Result<tokio::io::Stdout> {
+    Ok(tokio::io::stdout())
+}
@@ -3,9 +3,11 @@ pub mod task;
 pub mod subagent;
 pub mod repl;
 pub mod web_server;
+pub mod mcp_server;
 
 pub use setup::setup_from_cli;
 pub use task::run_task_mode;
 pub use subagent::run_subagent_mode;
 pub use repl::run_repl_mode;
 pub use web_server::run_web_server;
+pub use mcp_server::run_mcp_server;
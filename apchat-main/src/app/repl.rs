@@ -42,6 +42,8 @@ pub async fn run_repl_mode(
         backend_type,
         cli.early_superpowers,
     );
+    chat.allowed_roots = cli.allow_dirs.clone();
+    chat.configure_tools(cli)?;
 
     // Comprehensive model configuration display
     println!("{}", "═".repeat(80).bright_black());
@@ -126,13 +128,14 @@ pub async fn run_repl_mode(
     }
 
     // Initialize logger (async) – logs go into the workspace directory
-    chat.logger = match ConversationLogger::new(&chat.work_dir).await {
+    let logger = match ConversationLogger::new(&chat.work_dir).await {
         Ok(l) => Some(l),
         Err(e) => {
             eprintln!("Logging disabled: {}", e);
             None
         }
     };
+    chat.set_logger(logger);
 
     // If logger was created, log the initial system message that APChat::new added
     if let Some(logger) = &mut chat.logger {
@@ -357,6 +360,51 @@ pub async fn run_repl_mode(
                     continue;
                 }
 
+                // Handle /permissions commands
+                if line == "/permissions" || line == "/permissions list" {
+                    chat.policy_manager.prune_expired();
+                    let grants = chat.policy_manager.session_rules();
+                    if grants.is_empty() {
+                        println!("{} No session permissions granted", "ℹ️".bright_blue());
+                    } else {
+                        println!("{} Session permissions:", "🔐".bright_cyan());
+                        for rule in grants {
+                            let lifetime = match rule.remaining() {
+                                Some(remaining) => {
+                                    let secs = remaining.as_secs();
+                                    format!("expires in {}m {:02}s", secs / 60, secs % 60)
+                                }
+                                None => "until the session ends".to_string(),
+                            };
+                            println!("  {} {} -> {} ({})", rule.action, rule.pattern, rule.decision, lifetime);
+                        }
+                    }
+                    continue;
+                }
+
+                if line == "/permissions clear" {
+                    let removed = chat.policy_manager.clear_session_rules();
+                    println!("{} Revoked {} session permission(s)", "🔐".bright_green(), removed);
+                    continue;
+                }
+
+                if line == "/permissions help" {
+                    println!("{} Permission commands:", "🔐".bright_cyan());
+                    println!("  /permissions            - List permissions granted for this session");
+                    println!("  /permissions clear      - Revoke all session permissions");
+                    println!("  /permissions help       - Show this help");
+                    continue;
+                }
+
+                // Handle /tools commands
+                if line == "/tools" || line.starts_with("/tools ") {
+                    match tools_command(&mut chat, line["/tools".len()..].trim()) {
+                        Ok(output) => println!("{}", output),
+                        Err(e) => eprintln!("{} {}", "❌".bright_red(), e),
+                    }
+                    continue;
+                }
+
                 // Handle /session commands
                 if line == "/session" || line == "/session help" {
                     println!("{} Session commands:", "🖥️".bright_cyan());
@@ -435,6 +483,8 @@ pub async fn run_repl_mode(
                     println!("  /write-plan             - Use writing-plans skill to create detailed implementation plan");
                     println!("  /execute-plan           - Use executing-plans skill to execute plan with checkpoints");
                     println!("  /compact               - Force immediate conversation compaction to reduce session size");
+                    println!("  /permissions            - List or clear permissions granted for this session");
+                    println!("  /tools                  - List, enable or disable the tools offered to the model");
                     println!("  /skills help            - Show this help");
                     continue;
                 }
@@ -679,6 +729,73 @@ pub async fn run_repl_mode(
     Ok(())
 }
 
+/// `/tools [list | enable <tool|category>... | disable <tool|category>... | profile <name> | help]`
+fn tools_command(chat: &mut APChat, args: &str) -> Result<String> {
+    let mut words = args.split_whitespace();
+    match words.next().unwrap_or("list") {
+        "list" => {
+            let mut names = chat.tool_registry.get_tool_names();
+            names.sort();
+            let mut output = vec![format!(
+                "{} Tools ({} of {} enabled):",
+                "🧰".bright_cyan(),
+                chat.tool_registry.enabled_tool_names().len(),
+                names.len()
+            )];
+            for name in names {
+                let categories = chat.tool_registry.categories_of(&name).join(", ");
+                output.push(if chat.tool_registry.is_enabled(&name) {
+                    format!("  {} {} {}", "✓".bright_green(), name, categories.bright_black())
+                } else {
+                    format!("  {} {} {}", "✗".bright_red(), name.bright_black(), categories.bright_black())
+                });
+            }
+            Ok(output.join("\n"))
+        }
+        action @ ("enable" | "disable") => {
+            let selectors: Vec<&str> = words.collect();
+            if selectors.is_empty() {
+                anyhow::bail!("Usage: /tools {} <tool|category>...", action);
+            }
+            // Check every selector before changing anything
+            let mut names = Vec::new();
+            for selector in selectors {
+                let resolved = chat.tool_registry.resolve_tools(selector);
+                if resolved.is_empty() {
+                    anyhow::bail!("No tool or category named '{}'", selector);
+                }
+                names.extend(resolved);
+            }
+            let enable = action == "enable";
+            for name in &names {
+                chat.tool_registry.set_enabled(name, enable);
+            }
+            let verb = if enable { "Enabled" } else { "Disabled" };
+            Ok(format!("{} {}: {}", "🧰".bright_green(), verb, names.join(", ")))
+        }
+        "profile" => {
+            let name = words.next().ok_or_else(|| anyhow::anyhow!("Usage: /tools profile <name>"))?;
+            chat.apply_tool_profile(name)?;
+            Ok(format!(
+                "{} Applied tool profile '{}' ({} tools enabled)",
+                "🧰".bright_green(),
+                name,
+                chat.tool_registry.enabled_tool_names().len()
+            ))
+        }
+        "help" => Ok([
+            format!("{} Tool commands:", "🧰".bright_cyan()),
+            "  /tools                          - List tools and whether they are enabled".to_string(),
+            "  /tools enable <tool|category>   - Offer tools to the model again".to_string(),
+            "  /tools disable <tool|category>  - Stop offering tools to the model".to_string(),
+            "  /tools profile <name>           - Enable exactly the tools of a profile".to_string(),
+            "  /tools help                     - Show this help".to_string(),
+        ]
+        .join("\n")),
+        other => anyhow::bail!("Unknown /tools command '{}'; see /tools help", other),
+    }
+}
+
 #[cfg(test)]
 mod repl_compact_tests {
     use crate::APChat;
@@ -703,6 +820,8 @@ mod repl_compact_tests {
             current_model: ModelColor::GrnModel,
             total_tokens_used: 0,
             logger: None,
+            tool_log: Default::default(),
+            progress: Default::default(),
             tool_registry: ToolRegistry::new(),
             agent_coordinator: None,
             use_agents: false,
@@ -711,6 +830,11 @@ mod repl_compact_tests {
             terminal_manager: Arc::new(Mutex::new(TerminalManager::new(work_dir))),
             skill_registry: None,
             non_interactive: false,
+            confirmation_provider: None,
+            allowed_roots: Vec::new(),
+            tool_profile: None,
+            files_changed: std::collections::BTreeSet::new(),
+            artifacts: Arc::new(apchat_toolcore::ArtifactStore::new(temp_dir.path().join("artifacts"))),
             todo_manager: Arc::new(TodoManager::new()),
             stream_responses: false,
             verbose: false,
@@ -743,4 +867,32 @@ mod repl_compact_tests {
         // Should still have at least the system message
         assert!(chat.messages.len() >= 1, "Should have at least system message after compaction");
     }
+
+    #[tokio::test]
+    async fn test_tools_command() {
+        use apchat_tools::{ListFilesTool, ReadFileTool, RunCommandTool};
+
+        let mut chat = create_test_chat().await;
+        chat.tool_registry.register_with_categories(ReadFileTool, vec!["file_ops".to_string()]);
+        chat.tool_registry.register_with_categories(ListFilesTool, vec!["file_ops".to_string()]);
+        chat.tool_registry.register_with_categories(RunCommandTool, vec!["system".to_string()]);
+
+        super::tools_command(&mut chat, "disable file_ops").unwrap();
+        assert_eq!(chat.tool_registry.enabled_tool_names(), vec!["run_command"]);
+        assert_eq!(chat.get_tools().len(), 1);
+
+        // Nothing changes when one of the names is unknown
+        assert!(super::tools_command(&mut chat, "enable read_file nosuch").is_err());
+        assert_eq!(chat.tool_registry.enabled_tool_names(), vec!["run_command"]);
+
+        super::tools_command(&mut chat, "enable read_file").unwrap();
+        assert_eq!(chat.tool_registry.enabled_tool_names(), vec!["read_file", "run_command"]);
+
+        super::tools_command(&mut chat, "profile readonly").unwrap();
+        assert_eq!(chat.tool_registry.enabled_tool_names(), vec!["list_files", "read_file"]);
+        assert!(super::tools_command(&mut chat, "profile nosuch").unwrap_err().to_string().contains("readonly"));
+
+        let listing = super::tools_command(&mut chat, "").unwrap();
+        assert!(listing.contains("2 of 3 enabled"));
+    }
 }
@@ -1,13 +1,13 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::cli::Cli;
 use crate::config::{ClientConfig, BackendType};
-use apchat_models::{ModelColor, ModelProvider, ModelConfig};
+use apchat_models::{ModelColor, ModelProvider, ModelConfig, ToolCallDialect};
 use crate::config::helpers::get_model_config_from_env;
-use apchat_policy::PolicyManager;
+use apchat_policy::{AuditLog, PolicyLayer, PolicyManager};
 use apchat_llm_api::config::{parse_model_attings, GROQ_API_URL, ANTHROPIC_API_URL, OPENAI_API_URL, get_default_url_for_backend};
 
 /// Application configuration derived from CLI arguments and environment
@@ -19,6 +19,88 @@ pub struct AppConfig {
     pub early_superpowers: bool,
 }
 
+/// Project policy file of earlier versions, still read when the workspace
+/// has no `.apchat/policy.toml`
+const LEGACY_POLICY_FILE: &str = "policies.toml";
+
+/// Paths of the global and project policy files for the given CLI arguments
+pub fn policy_file_paths(cli: &Cli, work_dir: &Path) -> (Option<PathBuf>, PathBuf) {
+    let global_path = apchat_logging::get_okaychat_dir()
+        .ok()
+        .map(|dir| dir.join("policy.toml"));
+    let project_path = match cli.policy_file {
+        Some(ref policy_file) => work_dir.join(policy_file),
+        None => {
+            let project_path = PolicyLayer::project_policy_path(work_dir);
+            let legacy_path = work_dir.join(LEGACY_POLICY_FILE);
+            if !project_path.exists() && legacy_path.exists() {
+                eprintln!(
+                    "{} {} is deprecated, move it to {}",
+                    "⚠️".yellow(),
+                    legacy_path.display(),
+                    project_path.display()
+                );
+                legacy_path
+            } else {
+                project_path
+            }
+        }
+    };
+    (global_path, project_path)
+}
+
+/// The audit log policy decisions are recorded in (`policy_audit.jsonl` in the logs directory)
+pub fn policy_audit_log() -> Option<AuditLog> {
+    apchat_logging::get_logs_dir()
+        .ok()
+        .map(|dir| AuditLog::new(AuditLog::default_path(&dir)))
+}
+
+/// The policy manager for the given CLI arguments: allow everything with
+/// `--auto-confirm`, otherwise the layered policy files, recording decisions
+/// in the audit log
+pub fn policy_manager_from_cli(cli: &Cli, work_dir: &Path) -> Result<PolicyManager> {
+    let learn_layer = cli
+        .learn_layer
+        .as_deref()
+        .map(str::parse::<PolicyLayer>)
+        .transpose()
+        .context("Invalid --learn-layer")?;
+
+    let policy_manager = if cli.auto_confirm {
+        eprintln!("{} Auto-confirm mode enabled - all actions will be approved automatically", "🚀".green());
+        PolicyManager::allow_all()
+    } else {
+        let (global_path, project_path) = policy_file_paths(cli, work_dir);
+        match PolicyManager::layered(global_path, Some(project_path), cli.learn_policies) {
+            Ok(mut pm) => {
+                if let Some(layer) = learn_layer {
+                    pm = pm.with_learn_layer(layer);
+                }
+                for layer in [PolicyLayer::Global, PolicyLayer::Project] {
+                    if let Some(path) = pm.layer_file(layer).filter(|path| path.exists()) {
+                        eprintln!("{} Loaded {} policy file: {}", "📋".cyan(), layer, path.display());
+                    }
+                }
+                if cli.learn_policies {
+                    eprintln!("{} Policy learning enabled - user decisions will be saved to the {} policy", "📚".cyan(), pm.learn_layer());
+                }
+                pm
+            }
+            Err(e) => {
+                eprintln!("{} Failed to load policy file: {}", "⚠️".yellow(), e);
+                eprintln!("{} Using default policy (ask for confirmation)", "📋".cyan());
+                PolicyManager::new()
+            }
+        }
+    };
+
+    Ok(match policy_audit_log() {
+        Some(audit) => policy_manager.with_audit_log(audit),
+        None => policy_manager,
+    })
+}
+
 /// Resolve API key based on the final backend and precedence rules
 /// This ensures that when a backend is overridden, we pick up the correct API key
 fn resolve_api_key_for_backend(
@@ -280,11 +362,31 @@ pub fn setup_from_cli(cli: &Cli) -> Result<AppConfig> {
         panic!("Failed to create model providers array")
     });
 
-    let client_config = ClientConfig {
+    let mut client_config = ClientConfig {
         api_key: api_key.clone(),
         model_providers,
     };
 
+    // Tool call dialect per model: CLI > APCHAT_<COLOR>_TOOL_PARSER > auto
+    for (i, color) in ModelColor::iter().enumerate() {
+        let tool_parser = model_configs[i].tool_parser.clone().or_else(|| {
+            env::var(format!("APCHAT_{}_TOOL_PARSER", color.as_str_lowercase().to_uppercase())).ok()
+        });
+        if let Some(name) = tool_parser {
+            match name.parse::<ToolCallDialect>() {
+                Ok(dialect) => client_config.set_tool_parser(color, dialect),
+                Err(e) => eprintln!("{} {}_model: {}, using auto", "⚠️".yellow(), color.as_str_lowercase(), e),
+            }
+        }
+
+        // Native tool support: CLI > APCHAT_<COLOR>_NATIVE_TOOLS > on
+        let native_tools = !model_configs[i].no_native_tools
+            && env::var(format!("APCHAT_{}_NATIVE_TOOLS", color.as_str_lowercase().to_uppercase()))
+                .map(|value| !matches!(value.to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+                .unwrap_or(true);
+        client_config.set_native_tools(color, native_tools);
+    }
+
     // Inform user about auto-detected Anthropic configuration
     for (i, color) in ModelColor::iter().enumerate() {
         let is_anthropic = backends[i].as_ref() == Some(&BackendType::Anthropic)
@@ -295,30 +397,7 @@ pub fn setup_from_cli(cli: &Cli) -> Result<AppConfig> {
         }
     }
 
-    // Create policy manager based on CLI arguments
-    let policy_manager = if cli.auto_confirm {
-        eprintln!("{} Auto-confirm mode enabled - all actions will be approved automatically", "🚀".green());
-        PolicyManager::allow_all()
-    } else if cli.policy_file.is_some() || cli.learn_policies {
-        let policy_file = cli.policy_file.clone().unwrap_or_else(|| "policies.toml".to_string());
-        let policy_path = work_dir.join(&policy_file);
-        match PolicyManager::from_file(&policy_path, cli.learn_policies) {
-            Ok(pm) => {
-                eprintln!("{} Loaded policy file: {}", "📋".cyan(), policy_path.display());
-                if cli.learn_policies {
-                    eprintln!("{} Policy learning enabled - user decisions will be saved to policy file", "📚".cyan());
-                }
-                pm
-            }
-            Err(e) => {
-                eprintln!("{} Failed to load policy file: {}", "⚠️".yellow(), e);
-                eprintln!("{} Using default policy (ask for confirmation)", "📋".cyan());
-                PolicyManager::new()
-            }
-        }
-    } else {
-        PolicyManager::new()
-    };
+    let policy_manager = policy_manager_from_cli(cli, &work_dir)?;
 
     Ok(AppConfig {
         client_config,
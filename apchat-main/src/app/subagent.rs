@@ -6,6 +6,7 @@ use crate::APChat;
 use crate::cli::Cli;
 use crate::config::ClientConfig;
 use apchat_policy::PolicyManager;
+use apchat_toolcore::StdioConfirmation;
 
 /// Subagent task summary structure
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -57,9 +58,16 @@ pub async fn run_subagent_mode(
 
     // Mark as non-interactive to prevent prompts
     subagent.non_interactive = true;
+    subagent.allowed_roots = cli.allow_dirs.clone();
+    subagent.configure_tools(cli)?;
+
+    // Let the launching session answer policy questions instead of denying them
+    if cli.escalate_confirmations {
+        subagent.confirmation_provider = Some(std::sync::Arc::new(StdioConfirmation));
+    }
 
     // Disable logging for subagent mode to avoid clutter
-    subagent.logger = None;
+    subagent.set_logger(None);
 
     // Track initial state to detect changes
     let initial_file_count = count_files(&work_dir)?;
@@ -90,7 +98,7 @@ pub async fn run_subagent_mode(
     };
 
     // Analyze what changed during execution
-    analyze_changes(&subagent, &work_dir, initial_file_count, &mut tools_used, &mut files_modified);
+    analyze_changes(&subagent, initial_file_count, &mut tools_used, &mut files_modified);
 
     // Create summary
     let summary_obj = SubagentSummary {
@@ -263,7 +271,6 @@ fn extract_summary_from_response(response: &str) -> String {
 /// Analyze what changed during subagent execution
 fn analyze_changes(
     subagent: &APChat,
-    work_dir: &PathBuf,
     _initial_file_count: usize,
     tools_used: &mut Vec<String>,
     files_modified: &mut Vec<String>,
@@ -277,27 +284,13 @@ fn analyze_changes(
                 if !tools_used.contains(&function.name) {
                     tools_used.push(function.name.clone());
                 }
-                
-                // Extract file information from specific tools
-                match function.name.as_str() {
-                    "write_file" | "edit_file" | "apply_edit_plan" => {
-                        if let Ok(args) = serde_json::from_str::<serde_json::Value>(&function.arguments) {
-                            if let Some(file_path) = args.get("file_path").and_then(|v| v.as_str()) {
-                                let full_path = work_dir.join(file_path);
-                                if let Some(path_str) = full_path.to_str() {
-                                    if !files_modified.contains(&path_str.to_string()) {
-                                        files_modified.push(path_str.to_string());
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                }
             }
         }
     }
-    
+
+    // Files as reported by the tools that changed them
+    files_modified.extend(subagent.files_changed.iter().map(|path| path.display().to_string()));
+
     // Sort for consistent output
     tools_used.sort();
     files_modified.sort();
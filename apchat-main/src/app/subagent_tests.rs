@@ -36,6 +36,8 @@ mod tests {
             auto_confirm: false,
             policy_file: None,
             learn_policies: false,
+            learn_layer: None,
+            escalate_confirmations: false,
             stream: false,
             verbose: false,
             terminal_backend: None,
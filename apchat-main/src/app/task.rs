@@ -39,15 +39,18 @@ pub async fn run_task_mode(
         backend_type,
         cli.early_superpowers,
     );
+    chat.allowed_roots = cli.allow_dirs.clone();
+    chat.configure_tools(cli)?;
 
     // Initialize logger for task mode
-    chat.logger = match ConversationLogger::new_task_mode(&chat.work_dir).await {
+    let logger = match ConversationLogger::new_task_mode(&chat.work_dir).await {
         Ok(l) => Some(l),
         Err(e) => {
             eprintln!("Task logging disabled: {}", e);
             None
         }
     };
+    chat.set_logger(logger);
 
     let response = if chat.use_agents && chat.agent_coordinator.is_some() {
         // Use agent system
@@ -0,0 +1,123 @@
+// Tool middleware owned by a chat session: progress tracking and the
+// conversation log. Both sit at the start of the chain, so they see each
+// result after spilling and redaction.
+use apchat_agents::progress_evaluator::{ToolCallInfo, ToolCallSummary};
+use apchat_logging::{safe_truncate, ConversationLogger};
+use apchat_toolcore::{ToolContext, ToolInvocation, ToolMiddleware, ToolResult};
+use async_trait::async_trait;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Tool calls made for the current request, for progress evaluation
+#[derive(Clone, Default)]
+pub(crate) struct ProgressTracker {
+    progress: Arc<Mutex<Progress>>,
+}
+
+#[derive(Default)]
+struct Progress {
+    calls: Vec<ToolCallInfo>,
+    errors: Vec<String>,
+    files_changed: BTreeSet<String>,
+}
+
+impl ProgressTracker {
+    /// Forget the calls of the previous request
+    pub(crate) fn reset(&self) {
+        *self.progress.lock().unwrap() = Progress::default();
+    }
+
+    /// Summarize the calls so far for the progress evaluator
+    pub(crate) fn summary(&self, total_calls: u32, original_request: &str, elapsed_seconds: u64) -> ToolCallSummary {
+        let progress = self.progress.lock().unwrap();
+        let mut tool_usage = HashMap::new();
+        for call in &progress.calls {
+            *tool_usage.entry(call.tool_name.clone()).or_insert(0) += 1;
+        }
+
+        ToolCallSummary {
+            total_calls,
+            tool_usage,
+            recent_calls: progress.calls.iter().rev().take(10).cloned().collect(),
+            current_task: "Executing user request with tools".to_string(),
+            original_request: original_request.to_string(),
+            elapsed_seconds,
+            errors: progress.errors.clone(),
+            files_changed: progress.files_changed.iter().cloned().collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for ProgressTracker {
+    fn name(&self) -> &str {
+        "progress"
+    }
+
+    async fn after(&self, call: &ToolInvocation, _context: &ToolContext, result: &mut ToolResult) {
+        let output = match result.error {
+            Some(ref error) if !result.success => error.as_str(),
+            _ => result.content.as_str(),
+        };
+        let result_summary = if output.chars().count() > 200 {
+            format!("{} (truncated)", safe_truncate(output, 200))
+        } else {
+            output.to_string()
+        };
+
+        let mut progress = self.progress.lock().unwrap();
+        if !result.success {
+            progress.errors.push(format!("{}: {}", call.tool, output));
+        }
+        progress
+            .files_changed
+            .extend(result.metadata.files_changed().map(|path| path.display().to_string()));
+        progress.calls.push(ToolCallInfo {
+            tool_name: call.tool.clone(),
+            parameters: serde_json::to_string(&call.params.data).unwrap_or_default(),
+            success: result.success,
+            duration_ms: result.metadata.duration_ms.unwrap_or_else(|| call.elapsed().as_millis() as u64),
+            result_summary: Some(result_summary),
+        });
+    }
+}
+
+/// Writes tool results to the session's conversation log, once one is set
+#[derive(Clone, Default)]
+pub(crate) struct ToolLog {
+    logger: Arc<RwLock<Option<ConversationLogger>>>,
+}
+
+impl ToolLog {
+    pub(crate) fn set_logger(&self, logger: Option<ConversationLogger>) {
+        *self.logger.write().unwrap() = logger;
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for ToolLog {
+    fn name(&self) -> &str {
+        "conversation_log"
+    }
+
+    async fn after(&self, call: &ToolInvocation, _context: &ToolContext, result: &mut ToolResult) {
+        let Some(logger) = self.logger.read().unwrap().clone() else {
+            return;
+        };
+        let content = match result.error {
+            Some(ref error) if !result.success => format!("Error: {}", error),
+            _ => result.content.clone(),
+        };
+        if std::env::var("DEBUG_LOG").is_ok() {
+            eprintln!("[DEBUG] Logging tool result for {}", call.tool);
+        }
+        logger
+            .log_tool_result(
+                &content,
+                call.id.as_deref().unwrap_or_default(),
+                &call.tool,
+                serde_json::to_value(&result.metadata).ok(),
+            )
+            .await;
+    }
+}
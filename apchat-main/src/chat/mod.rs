@@ -2,6 +2,7 @@
 pub mod state;
 pub mod history;
 pub mod session;
+pub mod middleware;
 
 // Re-export commonly used items
 pub use state::{save_state, load_state};
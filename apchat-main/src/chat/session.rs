@@ -3,7 +3,8 @@ use colored::Colorize;
 
 use crate::APChat;
 use apchat_models::{ModelColor, Message};
-use apchat_logging::safe_truncate;
+use apchat_toolcore::{SideEffects, ToolResult};
+use std::collections::VecDeque;
 
 /// Main chat loop - handles user messages, tool calls, and model interactions
 pub(crate) async fn chat(
@@ -46,11 +47,9 @@ pub(crate) async fn chat(
             PROGRESS_EVAL_INTERVAL,
         ));
 
-        // Track tool calls for progress evaluation
-        let mut tool_call_history: Vec<apchat_agents::progress_evaluator::ToolCallInfo> = Vec::new();
-        let mut files_changed: std::collections::HashSet<String> = std::collections::HashSet::new();
+        // The progress middleware tracks this request's tool calls
+        chat.progress.reset();
         let start_time = std::time::Instant::now();
-        let mut errors_encountered: Vec<String> = Vec::new();
 
         loop {
             // Check for cancellation at the start of each iteration
@@ -202,11 +201,7 @@ pub(crate) async fn chat(
 
                 // Detect if tool is read-only (less likely to be problematic loop)
                 let is_read_only = tool_calls.iter().all(|tc|
-                    tc.function.name == "open_file" ||
-                    tc.function.name == "read_file" ||
-                    tc.function.name == "list_files" ||
-                    tc.function.name == "search_files" ||
-                    tc.function.name == "grep_search"
+                    chat.tool_registry.side_effects(&tc.function.name) == SideEffects::ReadOnly
                 );
 
                 // More strict threshold for consecutive repeats
@@ -260,22 +255,11 @@ pub(crate) async fn chat(
                         println!("{}", format!("🧠 Evaluating progress after {} tool calls...", tool_call_iterations).bright_blue());
                         eprintln!("[DEBUG] Progress evaluation triggered at iteration {}", tool_call_iterations);
 
-                        // Create tool call summary
-                        let mut tool_usage = std::collections::HashMap::new();
-                        for call in &tool_call_history {
-                            *tool_usage.entry(call.tool_name.clone()).or_insert(0) += 1;
-                        }
-
-                        let summary = apchat_agents::progress_evaluator::ToolCallSummary {
-                            total_calls: tool_call_iterations as u32,
-                            tool_usage,
-                            recent_calls: tool_call_history.iter().rev().take(10).cloned().collect(),
-                            current_task: "Executing user request with tools".to_string(),
-                            original_request: user_message.to_string(),
-                            elapsed_seconds: start_time.elapsed().as_secs(),
-                            errors: errors_encountered.clone(),
-                            files_changed: files_changed.iter().cloned().collect(),
-                        };
+                        let summary = chat.progress.summary(
+                            tool_call_iterations as u32,
+                            user_message,
+                            start_time.elapsed().as_secs(),
+                        );
 
                         match evaluator.evaluate_progress(&summary).await {
                             Ok(evaluation) => {
@@ -389,7 +373,23 @@ pub(crate) async fn chat(
                     ).await;
                 }
 
-                for tool_call in tool_calls {
+                // Outcomes of read-only calls that already ran together
+                let mut ready: VecDeque<Result<ToolResult>> = VecDeque::new();
+                for (index, tool_call) in tool_calls.iter().enumerate() {
+                    if ready.is_empty() {
+                        let run = chat.tool_registry.read_only_run(
+                            tool_calls[index..].iter().map(|tc| tc.function.name.as_str())
+                        );
+                        if run > 1 {
+                            println!("{} Running {} read-only tool calls concurrently", "⚡".yellow(), run);
+                            let calls: Vec<(&str, &str, &str)> = tool_calls[index..index + run]
+                                .iter()
+                                .map(|tc| (tc.id.as_str(), tc.function.name.as_str(), tc.function.arguments.as_str()))
+                                .collect();
+                            ready = chat.execute_tools_concurrently(&calls, cancellation_token.as_ref()).await.into();
+                        }
+                    }
+
                     println!(
                         "{} {} with args: {} (iteration {}/{})",
                         "🔧 Calling tool:".yellow(),
@@ -399,19 +399,25 @@ pub(crate) async fn chat(
                         MAX_TOOL_ITERATIONS
                     );
 
-                    let tool_start_time = std::time::Instant::now();
-                    let result = match chat.execute_tool(
-                        &tool_call.function.name,
-                        &tool_call.function.arguments,
-                    ).await {
-                        Ok(r) => r,
+                    let outcome = match ready.pop_front() {
+                        Some(outcome) => outcome,
+                        None => chat.execute_tool(
+                            &tool_call.id,
+                            &tool_call.function.name,
+                            &tool_call.function.arguments,
+                            cancellation_token.as_ref(),
+                        ).await,
+                    };
+                    let (result, display_result) = match outcome {
+                        Ok(tool_result) => {
+                            let display = tool_result.display_content().to_string();
+                            (tool_result.content, display)
+                        }
                         Err(e) => {
                             let error_msg = e.to_string();
 
-                            // Track error for progress evaluation
-                            errors_encountered.push(format!("{}: {}", tool_call.function.name, error_msg));
                             // Make cancellation errors very explicit to the model
-                            if error_msg.contains("cancelled by user") ||
+                            let message = if error_msg.contains("cancelled by user") ||
                                error_msg.contains("Edit cancelled") ||
                                error_msg.contains("Command cancelled") {
                                 // Extract user's comment if present
@@ -440,65 +446,13 @@ pub(crate) async fn chat(
                                 )
                             } else {
                                 format!("Error: {}", error_msg)
-                            }
+                            };
+                            (message.clone(), message)
                         }
                     };
 
-                    // Display result to user (truncate for file reading tools)
-                    let display_result = if tool_call.function.name == "open_file" || tool_call.function.name == "read_file" {
-                        let lines: Vec<&str> = result.lines().collect();
-                        if lines.len() > 10 {
-                            let first_10 = lines[..10].join("\n");
-                            let remaining = lines.len() - 10;
-                            format!("{}\n\n...and {} more lines", first_10, remaining)
-                        } else {
-                            result.clone()
-                        }
-                    } else {
-                        result.clone()
-                    };
-
                     println!("{} {}", "📋 Result:".green(), display_result.bright_black());
 
-                    // Log tool result
-                    if let Some(logger) = &mut chat.logger {
-                        if std::env::var("DEBUG_LOG").is_ok() {
-                            eprintln!("[DEBUG] Logging tool result for {}", tool_call.function.name);
-                        }
-                        logger.log_tool_result(
-                            &result,
-                            &tool_call.id,
-                            &tool_call.function.name,
-                        ).await;
-                    }
-
-                    // Track tool call for progress evaluation
-                    let duration = tool_start_time.elapsed();
-                    let result_summary = if result.chars().count() > 200 {
-                        format!("{} (truncated)", safe_truncate(&result, 200))
-                    } else {
-                        result.clone()
-                    };
-
-                    // Track files that were changed
-                    if tool_call.function.name.contains("write_file") ||
-                       tool_call.function.name.contains("edit_file") {
-                        if let Ok(args) = serde_json::from_str::<serde_json::Value>(&tool_call.function.arguments) {
-                            if let Some(file_path) = args.get("file_path").and_then(|v| v.as_str()) {
-                                files_changed.insert(file_path.to_string());
-                            }
-                        }
-                    }
-
-                    let call_info = apchat_agents::progress_evaluator::ToolCallInfo {
-                        tool_name: tool_call.function.name.clone(),
-                        parameters: tool_call.function.arguments.clone(),
-                        success: !result.contains("failed") && !result.contains("cancelled"),
-                        duration_ms: duration.as_millis() as u64,
-                        result_summary: Some(result_summary),
-                    };
-                    tool_call_history.push(call_info);
-
                     chat.messages.push(Message {
                         role: "tool".to_string(),
                         content: result,
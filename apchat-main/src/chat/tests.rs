@@ -24,6 +24,8 @@ mod tests {
             current_model: ModelColor::GrnModel,
             total_tokens_used: 0,
             logger: None,
+            tool_log: Default::default(),
+            progress: Default::default(),
             tool_registry: ToolRegistry::new(),
             agent_coordinator: None,
             use_agents: false,
@@ -32,6 +34,11 @@ mod tests {
             terminal_manager: Arc::new(Mutex::new(TerminalManager::new(work_dir))),
             skill_registry: None,
             non_interactive: false,
+            confirmation_provider: None,
+            allowed_roots: Vec::new(),
+            tool_profile: None,
+            files_changed: std::collections::BTreeSet::new(),
+            artifacts: Arc::new(apchat_toolcore::ArtifactStore::new(temp_dir.path().join("artifacts"))),
             todo_manager: Arc::new(TodoManager::new()),
             stream_responses: false,
             verbose: false,
@@ -319,4 +326,69 @@ mod tests {
         
         assert_eq!(not_truncated, short_text);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_tool_profile_is_passed_to_tool_context() {
+        let mut apchat = create_test_apchat();
+        assert!(apchat.tool_context(None).tool_profile.is_none());
+
+        apchat.apply_tool_profile("edit").unwrap();
+        assert_eq!(apchat.tool_context(None).tool_profile.as_deref(), Some("edit"));
+
+        // An unknown profile leaves the current one in place
+        assert!(apchat.apply_tool_profile("nope").is_err());
+        assert_eq!(apchat.tool_profile.as_deref(), Some("edit"));
+    }
+
+    #[tokio::test]
+    async fn test_switch_model_goes_through_policy_and_registry() {
+        use apchat_policy::{ActionType, Decision, PolicyRule};
+
+        let arguments = r#"{"model": "red_model", "reason": "cheaper"}"#;
+        let mut apchat = create_test_apchat();
+        apchat.tool_registry.register(apchat_tools::SwitchModelTool::new());
+        apchat.policy_manager = PolicyManager::allow_all();
+        apchat
+            .policy_manager
+            .add_session_rule(PolicyRule::new(ActionType::ModelSwitch, "red_model".to_string(), Decision::Deny));
+
+        let error = apchat.execute_tool("call-1", "switch_model", arguments, None).await.unwrap_err();
+        assert!(error.to_string().contains("Denied by policy"), "{}", error);
+        assert_eq!(apchat.current_model, ModelColor::GrnModel);
+
+        apchat.policy_manager = PolicyManager::allow_all();
+        apchat.tool_registry.set_enabled("switch_model", false);
+        assert!(apchat.execute_tool("call-1", "switch_model", arguments, None).await.is_err());
+        assert_eq!(apchat.current_model, ModelColor::GrnModel);
+
+        apchat.tool_registry.set_enabled("switch_model", true);
+        apchat.execute_tool("call-1", "switch_model", arguments, None).await.unwrap();
+        assert_eq!(apchat.current_model, ModelColor::RedModel);
+    }
+
+    #[tokio::test]
+    async fn test_progress_middleware_tracks_tool_calls_of_the_request() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut apchat = create_test_apchat();
+        apchat.work_dir = temp_dir.path().to_path_buf();
+        apchat.policy_manager = PolicyManager::allow_all();
+        apchat.tool_registry.register(apchat_tools::WriteFileTool);
+        apchat.tool_registry.prepend_middleware(apchat.progress.clone());
+
+        let written = r#"{"file_path": "notes.md", "content": "hello"}"#;
+        apchat.execute_tool("call-1", "write_file", written, None).await.unwrap();
+        assert!(apchat.execute_tool("call-2", "write_file", r#"{"file_path": "../outside.md", "content": "x"}"#, None).await.is_err());
+
+        let summary = apchat.progress.summary(2, "write notes", 1);
+        assert_eq!(summary.tool_usage.get("write_file"), Some(&2));
+        assert_eq!(summary.recent_calls.len(), 2);
+        assert!(summary.recent_calls[1].success);
+        assert_eq!(summary.errors.len(), 1);
+        assert!(summary.errors[0].starts_with("write_file: "), "{:?}", summary.errors);
+        assert_eq!(summary.files_changed.len(), 1);
+        assert!(summary.files_changed[0].ends_with("notes.md"));
+
+        apchat.progress.reset();
+        assert!(apchat.progress.summary(0, "next", 0).recent_calls.is_empty());
+    }
+}
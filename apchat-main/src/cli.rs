@@ -3,10 +3,11 @@ use clap::{Parser, Subcommand};
 use clap_complete::Shell;
 use std::env;
 use std::future::Future;
+use std::path::PathBuf;
 use std::pin::Pin;
 
 use apchat_toolcore::{Tool, ToolParameters, ToolContext};
-use apchat_policy::PolicyManager;
+use apchat_policy::{expanded_commands, parse_since, ActionType, AuditLog, PolicyLayer, PolicyManager};
 use apchat_tools::{
     OpenFileTool, ReadFileTool, WriteFileTool, EditFileTool, ListFilesTool,
     RunCommandTool, SearchFilesTool,
@@ -47,6 +48,11 @@ pub struct Cli {
     #[arg(long)]
     pub pretty: bool,
 
+    /// Send confirmation requests to the parent process instead of denying them
+    /// (only useful with --task; used when launched as a subagent)
+    #[arg(long)]
+    pub escalate_confirmations: bool,
+
     /// Use llama.cpp server for all models (e.g., http://localhost:8080)
     /// This is a convenience flag that sets --api-url-blu-model, --api-url-grn-model, and --api-url-red-model
     #[arg(long, value_name = "URL")]
@@ -110,6 +116,30 @@ pub struct Cli {
     #[arg(long, value_name = "KEY")]
     pub red_key: Option<String>,
 
+    /// Dialect of tool calls blu_model writes into its text (auto, glm, hermes, llama3, mistral, json, none)
+    #[arg(long, value_name = "DIALECT")]
+    pub blu_tool_parser: Option<String>,
+
+    /// Dialect of tool calls grn_model writes into its text (auto, glm, hermes, llama3, mistral, json, none)
+    #[arg(long, value_name = "DIALECT")]
+    pub grn_tool_parser: Option<String>,
+
+    /// Dialect of tool calls red_model writes into its text (auto, glm, hermes, llama3, mistral, json, none)
+    #[arg(long, value_name = "DIALECT")]
+    pub red_tool_parser: Option<String>,
+
+    /// blu_model's backend has no native tool support: describe tools in the prompt
+    #[arg(long)]
+    pub blu_no_native_tools: bool,
+
+    /// grn_model's backend has no native tool support: describe tools in the prompt
+    #[arg(long)]
+    pub grn_no_native_tools: bool,
+
+    /// red_model's backend has no native tool support: describe tools in the prompt
+    #[arg(long)]
+    pub red_no_native_tools: bool,
+
     /// Auto-confirm all actions without asking (auto-pilot mode)
     #[arg(long)]
     pub auto_confirm: bool,
@@ -119,14 +149,38 @@ pub struct Cli {
     #[arg(long)]
     pub early_superpowers: bool,
 
-    /// Path to policy file (default: policies.toml in project root)
+    /// Path to the project policy file (default: .apchat/policy.toml in project root)
+    /// Rules in ~/.okaychat/policy.toml take precedence over the project policy
     #[arg(long, value_name = "PATH")]
     pub policy_file: Option<String>,
 
+    /// Let file tools also access this directory (repeatable); everything else
+    /// outside the working directory is refused
+    #[arg(long = "allow-dir", value_name = "DIR")]
+    pub allow_dirs: Vec<PathBuf>,
+
+    /// Offer the model only the tools of this profile (full, readonly, edit,
+    /// no-terminal, or one from tool_profiles.toml)
+    #[arg(long, value_name = "NAME", env = "APCHAT_TOOL_PROFILE")]
+    pub tool_profile: Option<String>,
+
+    /// Send only a core set of tools plus search_tools, and let the model
+    /// find the others when it needs them
+    #[arg(long, env = "APCHAT_TOOL_SEARCH")]
+    pub tool_search: bool,
+
+    /// Tools sent with every request when --tool-search is on (comma-separated)
+    #[arg(long, value_name = "TOOLS", value_delimiter = ',', env = "APCHAT_CORE_TOOLS")]
+    pub core_tools: Vec<String>,
+
     /// Learn from user decisions and save them to policy file
     #[arg(long)]
     pub learn_policies: bool,
 
+    /// Policy layer that learned decisions are saved to (project, global or session)
+    #[arg(long, value_name = "LAYER")]
+    pub learn_layer: Option<String>,
+
     /// Enable streaming mode - show AI responses as they're generated
     #[arg(long)]
     pub stream: bool,
@@ -237,6 +291,41 @@ pub enum Commands {
         #[command(subcommand)]
         command: TerminalCommands,
     },
+    /// Inspect the security policy and its audit trail
+    Policy {
+        #[command(subcommand)]
+        command: PolicyCommands,
+    },
+    /// Serve APChat's tools to an MCP client over stdio
+    McpServe,
+}
+
+#[derive(Subcommand)]
+pub enum PolicyCommands {
+    /// Show how the policy decides an action and which rule decides it
+    Explain {
+        /// Action type (e.g. command_execution, file_write, terminal_input)
+        action: String,
+        /// Target of the action (a file path or a command line)
+        target: String,
+    },
+    /// Show recorded policy decisions and user responses
+    Audit {
+        /// Only show entries since this time (e.g. 30m, 2h, 7d, 2024-05-01)
+        #[arg(long)]
+        since: Option<String>,
+    },
+    /// Report unreachable and shadowed policy rules
+    Lint,
+    /// Find learned rules made redundant by broader rules in the same file
+    Consolidate {
+        /// Policy layer to consolidate (global or project)
+        #[arg(long, default_value = "project")]
+        layer: String,
+        /// Remove the redundant rules instead of only listing them
+        #[arg(long)]
+        apply: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -286,18 +375,24 @@ impl Cli {
                 api_url: self.api_url_blu_model.clone(),
                 api_key: self.blu_key.clone(),
                 model: self.model_blu_model.clone(),
+                tool_parser: self.blu_tool_parser.clone(),
+                no_native_tools: self.blu_no_native_tools,
             },
             ModelConfig {
                 backend: self.grn_backend.clone(),
                 api_url: self.api_url_grn_model.clone(),
                 api_key: self.grn_key.clone(),
                 model: self.model_grn_model.clone(),
+                tool_parser: self.grn_tool_parser.clone(),
+                no_native_tools: self.grn_no_native_tools,
             },
             ModelConfig {
                 backend: self.red_backend.clone(),
                 api_url: self.api_url_red_model.clone(),
                 api_key: self.red_key.clone(),
                 model: self.model_red_model.clone(),
+                tool_parser: self.red_tool_parser.clone(),
+                no_native_tools: self.red_no_native_tools,
             },
         ]
     }
@@ -492,6 +587,18 @@ impl Commands {
                     Err(anyhow::anyhow!("Terminal commands require special handling"))
                 })
             }
+            Commands::Policy { .. } => {
+                // Policy commands need the configured policy files, handled in main.rs
+                Box::pin(async move {
+                    Err(anyhow::anyhow!("Policy commands require special handling"))
+                })
+            }
+            Commands::McpServe => {
+                // The server runs until the client disconnects, handled in main.rs
+                Box::pin(async move {
+                    Err(anyhow::anyhow!("MCP serve requires special handling"))
+                })
+            }
         }
     }
 }
@@ -549,6 +656,85 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_mcp_serve_command() -> Result<(), Box<dyn std::error::Error>> {
+        let cli = Cli::try_parse_from(["apchat", "mcp-serve"])?;
+        assert!(matches!(cli.command, Some(Commands::McpServe)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tool_profile_and_search_flags() -> Result<(), Box<dyn std::error::Error>> {
+        let cli = Cli::try_parse_from(["apchat", "--tool-profile", "readonly"])?;
+        assert_eq!(cli.tool_profile, Some("readonly".to_string()));
+        assert!(!cli.tool_search);
+
+        let cli = Cli::try_parse_from(["apchat", "--tool-search", "--core-tools", "read_file,run_command"])?;
+        assert!(cli.tool_search);
+        assert_eq!(cli.core_tools, vec!["read_file", "run_command"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tool_parser_and_native_tools_flags() -> Result<(), Box<dyn std::error::Error>> {
+        let cli = Cli::try_parse_from(["apchat", "--grn-tool-parser", "llama3", "--grn-no-native-tools"])?;
+        let configs = cli.get_model_configs();
+
+        assert_eq!(configs[ModelColor::GrnModel as usize].tool_parser, Some("llama3".to_string()));
+        assert!(configs[ModelColor::GrnModel as usize].no_native_tools);
+        assert!(!configs[ModelColor::BluModel as usize].no_native_tools);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_learn_layer_flag() -> Result<(), Box<dyn std::error::Error>> {
+        let cli = Cli::try_parse_from(&["apchat", "--learn-policies", "--learn-layer", "global"])?;
+        
+        assert!(cli.learn_policies);
+        assert_eq!(cli.learn_layer, Some("global".to_string()));
+        
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_commands() -> Result<(), Box<dyn std::error::Error>> {
+        let cli = Cli::try_parse_from(&["apchat", "policy", "explain", "command_execution", "cargo test && ls"])?;
+        match cli.command {
+            Some(Commands::Policy { command: PolicyCommands::Explain { action, target } }) => {
+                assert_eq!(action, "command_execution");
+                assert_eq!(target, "cargo test && ls");
+            }
+            _ => panic!("Expected policy explain command"),
+        }
+
+        let cli = Cli::try_parse_from(&["apchat", "policy", "audit", "--since", "2h"])?;
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Policy { command: PolicyCommands::Audit { since: Some(ref since) } }) if since == "2h"
+        ));
+
+        let cli = Cli::try_parse_from(&["apchat", "policy", "lint"])?;
+        assert!(matches!(cli.command, Some(Commands::Policy { command: PolicyCommands::Lint })));
+
+        let cli = Cli::try_parse_from(["apchat", "policy", "consolidate", "--apply"])?;
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Policy { command: PolicyCommands::Consolidate { ref layer, apply: true } }) if layer == "project"
+        ));
+
+        let explained = PolicyCommands::Explain {
+            action: "command_execution".to_string(),
+            target: "sudo rm -rf /".to_string(),
+        }
+        .execute(&PolicyManager::new(), None)?;
+        assert!(explained.starts_with("command_execution 'sudo rm -rf /' -> deny (builtin rule"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_task_argument() -> Result<(), Box<dyn std::error::Error>> {
         let task_text = "help me debug this issue";
@@ -697,6 +883,79 @@ impl TerminalCommands {
     }
 }
 
+impl PolicyCommands {
+    pub fn execute(&self, policy_manager: &PolicyManager, audit_log: Option<&AuditLog>) -> Result<String> {
+        match self {
+            PolicyCommands::Explain { action, target } => {
+                let action: ActionType = action.parse()?;
+                let decision = policy_manager.decide(&action, target);
+                let mut output = format!("{} '{}' -> {}", action, target, decision);
+
+                // Show how each part of a compound command line was judged
+                if action.is_shell_command() {
+                    let commands = expanded_commands(target);
+                    if commands.len() > 1 {
+                        for command in commands {
+                            output.push_str(&format!("\n  {} -> {}", command, policy_manager.decide(&action, &command)));
+                        }
+                    }
+                }
+
+                for layer in [PolicyLayer::Global, PolicyLayer::Project] {
+                    if let Some(path) = policy_manager.layer_file(layer) {
+                        let status = if path.exists() { "" } else { " (not found)" };
+                        output.push_str(&format!("\n{} policy: {}{}", layer, path.display(), status));
+                    }
+                }
+                Ok(output)
+            }
+            PolicyCommands::Audit { since } => {
+                let Some(audit_log) = audit_log else {
+                    return Err(anyhow::anyhow!("No audit log available"));
+                };
+                let since = since
+                    .as_deref()
+                    .map(|since| parse_since(since, chrono::Utc::now()))
+                    .transpose()?;
+
+                let entries = audit_log.read(since)?;
+                if entries.is_empty() {
+                    return Ok(format!("No audit entries in {}", audit_log.path().display()));
+                }
+                Ok(entries.iter().map(|entry| entry.to_string()).collect::<Vec<_>>().join("\n"))
+            }
+            PolicyCommands::Lint => {
+                let findings = policy_manager.lint();
+                if findings.is_empty() {
+                    return Ok("No problems found".to_string());
+                }
+                let unreachable = findings.iter().filter(|f| f.is_unreachable()).count();
+                let mut output: Vec<String> = findings.iter().map(|f| f.to_string()).collect();
+                output.push(format!(
+                    "{} unreachable, {} shadowed",
+                    unreachable,
+                    findings.len() - unreachable
+                ));
+                Ok(output.join("\n"))
+            }
+            PolicyCommands::Consolidate { layer, apply } => {
+                let layer: PolicyLayer = layer.parse()?;
+                let consolidations = policy_manager.consolidate(layer, *apply)?;
+                if consolidations.is_empty() {
+                    return Ok(format!("No redundant learned rules in the {} policy", layer));
+                }
+                let mut output: Vec<String> = consolidations.iter().map(|c| c.to_string()).collect();
+                output.push(if *apply {
+                    format!("Removed {} redundant rule(s)", consolidations.len())
+                } else {
+                    format!("{} redundant rule(s); run with --apply to remove them", consolidations.len())
+                });
+                Ok(output.join("\n"))
+            }
+        }
+    }
+}
+
     #[test]
     fn test_task_argument() -> Result<(), Box<dyn std::error::Error>> {
         let task_text = "help me debug this issue";
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use crate::config::{ClientConfig, normalize_api_url};
 use apchat_models::ModelColor;
 use apchat_llm_api::{
-    LlmClient, BackendType, GROQ_API_URL,
+    LlmClient, BackendType, GROQ_API_URL, PromptToolsClient,
     client::{anthropic::AnthropicLlmClient, groq::GroqLlmClient, llama_cpp::LlamaCppClient},
 };
 use colored::Colorize;
@@ -126,7 +126,7 @@ pub fn create_client_for_model_color(
     client_config: &ClientConfig,
     default_api_key: &str,
 ) -> Arc<dyn LlmClient> {
-    match model {
+    let client = match model {
         ModelColor::BluModel => {
             create_model_client(
                 "blu",
@@ -157,6 +157,13 @@ pub fn create_client_for_model_color(
                 default_api_key,
             )
         }
+    };
+
+    // Backends without native tool support get the tools through the prompt
+    if client_config.get_native_tools(*model) {
+        client
+    } else {
+        Arc::new(PromptToolsClient::new(client, client_config.get_tool_parser(*model)))
     }
 }
 
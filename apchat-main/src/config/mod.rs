@@ -5,10 +5,11 @@ use std::sync::Arc;
 use apchat_agents::{
     PlanningCoordinator, AgentFactory,
 };
-use apchat_toolcore::ToolRegistry;
-use apchat_policy::PolicyManager;
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{RedactionMiddleware, SpillMiddleware, TimingMiddleware, ToolProfiles, ToolRegistry};
+use apchat_policy::{ActionType, PolicyManager};
 use apchat_tools::*;
-use apchat_models::{ModelColor, ModelProvider};
+use apchat_models::{ModelColor, ModelProvider, ToolCallDialect};
 
 pub mod helpers;
 pub use helpers::{get_system_prompt, get_api_url, get_api_key, create_model_client, create_client_for_model_color};
@@ -95,6 +96,26 @@ impl ClientConfig {
         self.get_provider_mut(color).model_name = model;
     }
     
+    /// Get the tool call dialect for a specific model color
+    pub fn get_tool_parser(&self, color: ModelColor) -> ToolCallDialect {
+        self.get_provider(color).tool_parser
+    }
+
+    /// Set the tool call dialect for a specific model color
+    pub fn set_tool_parser(&mut self, color: ModelColor, dialect: ToolCallDialect) {
+        self.get_provider_mut(color).tool_parser = dialect;
+    }
+
+    /// Whether the backend of a specific model color accepts tool definitions
+    pub fn get_native_tools(&self, color: ModelColor) -> bool {
+        self.get_provider(color).native_tools
+    }
+
+    /// Set whether the backend of a specific model color accepts tool definitions
+    pub fn set_native_tools(&mut self, color: ModelColor, native_tools: bool) {
+        self.get_provider_mut(color).native_tools = native_tools;
+    }
+
     /// Legacy method: Get model override for a specific model color
     pub fn get_model_override(&self, color: ModelColor) -> Option<&String> {
         Some(&self.get_provider(color).model_name)
@@ -111,10 +132,35 @@ impl ClientConfig {
 #[cfg(test)]
 mod api_key_tests;
 
+/// Tool output above this many bytes is spilled to the artifact store
+const DEFAULT_TOOL_OUTPUT_LIMIT: usize = 32_000;
+
+/// Tools sent with every request when tool search is on
+pub const DEFAULT_CORE_TOOLS: &[&str] = &[
+    "open_file",
+    "read_file",
+    "write_file",
+    "edit_file",
+    "list_files",
+    "search_files",
+    "run_command",
+    "todo_write",
+    "todo_list",
+];
+
 /// Initialize the tool registry with all available tools
 pub fn initialize_tool_registry() -> ToolRegistry {
     let mut registry = ToolRegistry::new();
 
+    // Output larger than this many bytes is saved to the session's artifact store
+    let spill_limit = match std::env::var("APCHAT_TOOL_OUTPUT_LIMIT") {
+        Ok(limit) => limit.trim().parse::<usize>().unwrap_or_else(|_| {
+            eprintln!("{} Ignoring APCHAT_TOOL_OUTPUT_LIMIT: '{}' is not a number", "⚠️".yellow(), limit);
+            DEFAULT_TOOL_OUTPUT_LIMIT
+        }),
+        Err(_) => DEFAULT_TOOL_OUTPUT_LIMIT,
+    };
+
     // Register file operation tools
     registry.register_with_categories(OpenFileTool, vec!["file_ops".to_string()]);
     registry.register_with_categories(ReadFileTool, vec!["file_ops".to_string()]);
@@ -162,9 +208,137 @@ pub fn initialize_tool_registry() -> ToolRegistry {
     registry.register_with_categories(PtyKillTool, vec!["terminal".to_string()]);
     registry.register_with_categories(PtyRequestUserInputTool, vec!["terminal".to_string()]);
 
+    // Register the pager for output too large to show in full; its pages fit
+    // within the spill limit
+    registry.register_with_categories(ReadToolOutputTool::new(spill_limit), vec!["file_ops".to_string()]);
+
+    // Per-tool timeouts, e.g. APCHAT_TOOL_TIMEOUTS="run_command=600,default=120"
+    if let Ok(spec) = std::env::var("APCHAT_TOOL_TIMEOUTS") {
+        if let Err(e) = registry.configure_timeouts(&spec) {
+            eprintln!("{} Ignoring APCHAT_TOOL_TIMEOUTS: {}", "⚠️".yellow(), e);
+        }
+    }
+
+    // How many read-only tool calls from one response run at once; 1 disables concurrency
+    if let Ok(limit) = std::env::var("APCHAT_TOOL_PARALLELISM") {
+        match limit.trim().parse::<usize>() {
+            Ok(limit) => registry.set_parallelism(limit),
+            Err(_) => eprintln!("{} Ignoring APCHAT_TOOL_PARALLELISM: '{}' is not a number", "⚠️".yellow(), limit),
+        }
+    }
+
+    // Behavior shared by every tool call, whichever path makes it. Spilling is
+    // registered before redaction so the saved output is redacted too. File
+    // reads are not redacted: edits made from them would write the
+    // placeholders back into the files.
+    registry.add_middleware(TimingMiddleware::new(std::time::Duration::from_secs(30)));
+    registry.add_middleware(EditPlanConfirmation);
+    registry.add_middleware(SpillMiddleware::new(spill_limit));
+    registry.add_middleware(RedactionMiddleware::secrets().with_exempt_tools(["read_file", "open_file"]));
+
     registry
 }
 
+/// Register the project's custom tools from `.apchat/tools/*.toml`. Invalid
+/// manifests and tools that would shadow a builtin are reported and skipped.
+pub fn register_custom_tools(registry: &mut ToolRegistry, work_dir: &std::path::Path) {
+    for tool in load_custom_tools(&custom_tools_dir(work_dir)) {
+        match tool {
+            Ok(tool) if registry.has_tool(&tool.manifest().name) => {
+                eprintln!("{} Ignoring custom tool '{}': a tool with that name already exists", "⚠️".yellow(), tool.manifest().name);
+            }
+            Ok(tool) => registry.register_with_categories(tool, vec!["custom".to_string()]),
+            Err(e) => eprintln!("{} {:#}", "⚠️".yellow(), e),
+        }
+    }
+}
+
+/// Send only the `core` tools (the defaults when empty) plus `search_tools`,
+/// through which the model finds the rest. Unknown core tools are reported.
+pub fn enable_tool_search(registry: &mut ToolRegistry, core: &[String]) {
+    let core: Vec<String> = if core.is_empty() {
+        DEFAULT_CORE_TOOLS.iter().map(|name| name.to_string()).collect()
+    } else {
+        core.to_vec()
+    };
+    for name in core.iter().filter(|name| !registry.has_tool(name)) {
+        eprintln!("{} Unknown core tool '{}'", "⚠️".yellow(), name);
+    }
+    registry.register(SearchToolsTool);
+    registry.enable_tool_search(core);
+}
+
+/// Built-in tool profiles plus those in `~/.okaychat/tool_profiles.toml` and
+/// `.apchat/tool_profiles.toml`. An invalid file is reported and ignored.
+pub fn load_tool_profiles(work_dir: &std::path::Path) -> ToolProfiles {
+    let global = apchat_logging::get_okaychat_dir().ok().map(|dir| dir.join("tool_profiles.toml"));
+    ToolProfiles::layered(global.as_deref(), &ToolProfiles::project_config_path(work_dir)).unwrap_or_else(|e| {
+        eprintln!("{} {:#}", "⚠️".yellow(), e);
+        ToolProfiles::builtin()
+    })
+}
+
+/// Start the MCP servers configured in `~/.okaychat/mcp.toml` and
+/// `.apchat/mcp.toml` and register their tools. A project server's command
+/// is checked like any other command first, since the file comes with the
+/// repository. Servers that fail to start are reported and skipped.
+pub fn register_mcp_servers(registry: &mut ToolRegistry, work_dir: &std::path::Path, policy_manager: &PolicyManager) {
+    let global = apchat_logging::get_okaychat_dir().ok().map(|dir| dir.join("mcp.toml"));
+    let mut config = match apchat_mcp::McpConfig::layered(global.as_deref(), &apchat_mcp::McpConfig::project_config_path(work_dir)) {
+        Ok(config) if config.servers.values().any(|server| server.enabled) => config,
+        Ok(_) => return,
+        Err(e) => {
+            eprintln!("{} {:#}", "⚠️".yellow(), e);
+            return;
+        }
+    };
+
+    // Servers are started on the running runtime, which the session is created from synchronously
+    let handle = match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => handle,
+        _ => {
+            eprintln!("{} MCP servers need the multi-threaded runtime; skipping them", "⚠️".yellow());
+            return;
+        }
+    };
+    tokio::task::block_in_place(|| {
+        handle.block_on(async {
+            let context = ToolContext::new(work_dir.to_path_buf(), "mcp_startup".to_string(), policy_manager.clone());
+            for (name, server) in config.servers.iter_mut().filter(|(_, server)| server.enabled && server.project) {
+                let command_line = server.command_line();
+                let prompt = format!("Start MCP server '{}' from the project config? (y/N):", name);
+                match context.check_permission(ActionType::CommandExecution, &command_line, &prompt).await {
+                    Ok((true, _)) => {}
+                    Ok((false, reason)) => {
+                        let reason = reason.unwrap_or_else(|| "not approved".to_string());
+                        eprintln!("{} Not starting MCP server '{}' ({}): {}", "⚠️".yellow(), name, command_line, reason);
+                        server.enabled = false;
+                    }
+                    Err(e) => {
+                        eprintln!("{} Not starting MCP server '{}': {:#}", "⚠️".yellow(), name, e);
+                        server.enabled = false;
+                    }
+                }
+            }
+
+            for client in apchat_mcp::connect_servers(&config, work_dir).await {
+                let client = match client {
+                    Ok(client) => client,
+                    Err(e) => {
+                        eprintln!("{} {:#}", "⚠️".yellow(), e);
+                        continue;
+                    }
+                };
+                let name = client.name().to_string();
+                match apchat_mcp::register_server(registry, client).await {
+                    Ok(count) => println!("{} MCP server '{}': {} tool(s)", "🔌".blue(), name, count),
+                    Err(e) => eprintln!("{} MCP server '{}': {:#}", "⚠️".yellow(), name, e),
+                }
+            }
+        })
+    });
+}
+
 /// Initialize the agent system with configuration files
 pub fn initialize_agent_system(client_config: &ClientConfig, tool_registry: &ToolRegistry, policy_manager: &PolicyManager) -> Result<PlanningCoordinator> {
     println!("{} Initializing agent system...", "🤖".blue());
@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 use std::env;
 use std::fs;
+use std::collections::BTreeSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -26,10 +27,11 @@ use apchat_agents::{
 use apchat_logging::ConversationLogger;
 use apchat_policy::PolicyManager;
 use apchat_terminal::{TerminalManager, TerminalBackendType, MAX_CONCURRENT_SESSIONS};
-use apchat_toolcore::{ToolRegistry, ToolParameters, ToolContext};
+use apchat_toolcore::{ArtifactStore, ToolRegistry, ToolParameters, ToolContext, ToolInvocation, ToolResult, ConfirmationProvider};
 use cli::{Cli, Commands};
 use config::{ClientConfig, GROQ_API_URL, initialize_tool_registry, initialize_agent_system};
 use chat::{save_state, load_state};
+use chat::middleware::{ProgressTracker, ToolLog};
 use app::{setup_from_cli, run_task_mode, run_subagent_mode, run_repl_mode};
 use apchat_models::{
     ModelColor, Message, ToolCall, FunctionCall, ModelProvider,
@@ -50,6 +52,10 @@ pub(crate) struct APChat {
     pub(crate) current_model: ModelColor,
     pub(crate) total_tokens_used: usize,
     pub(crate) logger: Option<ConversationLogger>,
+    // Writes tool results to `logger`; set both with `set_logger`
+    pub(crate) tool_log: ToolLog,
+    // Tool calls made for the current request, for progress evaluation
+    pub(crate) progress: ProgressTracker,
     pub(crate) tool_registry: ToolRegistry,
     // Agent system
     pub(crate) agent_coordinator: Option<PlanningCoordinator>,
@@ -64,6 +70,16 @@ pub(crate) struct APChat {
     pub(crate) skill_registry: Option<Arc<apchat_skills::SkillRegistry>>,
     // Non-interactive mode (web/API)
     pub(crate) non_interactive: bool,
+    // Answers `Ask` policy decisions (terminal, web socket, parent process)
+    pub(crate) confirmation_provider: Option<Arc<dyn ConfirmationProvider>>,
+    // Extra directories file tools may access besides the work directory
+    pub(crate) allowed_roots: Vec<PathBuf>,
+    // Tool profile in use, passed on to subagents
+    pub(crate) tool_profile: Option<String>,
+    // Files written or deleted by tool calls, as reported by the tools
+    pub(crate) files_changed: BTreeSet<PathBuf>,
+    // Tool output too large for the conversation, readable with read_tool_output
+    pub(crate) artifacts: Arc<ArtifactStore>,
     // Todo manager for task tracking
     pub(crate) todo_manager: Arc<apchat_todo::TodoManager>,
     // Streaming mode
@@ -144,7 +160,15 @@ impl APChat {
         backend_type: TerminalBackendType,
         early_superpowers: bool,
     ) -> Self {
-        let tool_registry = initialize_tool_registry();
+        let mut tool_registry = initialize_tool_registry();
+        config::register_custom_tools(&mut tool_registry, &work_dir);
+        config::register_mcp_servers(&mut tool_registry, &work_dir, &policy_manager);
+
+        // Session hooks go first, so they see results after spilling and redaction
+        let tool_log = ToolLog::default();
+        let progress = ProgressTracker::default();
+        tool_registry.prepend_middleware(tool_log.clone());
+        tool_registry.prepend_middleware(progress.clone());
 
         // Initialize skill registry
         let skills_dir = work_dir.join("skills");
@@ -181,6 +205,14 @@ impl APChat {
         // Initialize todo manager
         let todo_manager = Arc::new(apchat_todo::TodoManager::new());
 
+        // Each session spills oversized tool output to its own directory
+        let artifacts = Arc::new(ArtifactStore::new(
+            apchat_logging::get_logs_dir()
+                .unwrap_or_else(|_| PathBuf::from("logs"))
+                .join("artifacts")
+                .join(uuid::Uuid::new_v4().to_string()),
+        ));
+
         // Determine initial model based on overrides or defaults
         // Default to GPT-OSS for cost efficiency - it's significantly cheaper than Kimi
         // while still providing good performance for most tasks
@@ -201,6 +233,8 @@ impl APChat {
             current_model: initial_model,
             total_tokens_used: 0,
             logger: None,
+            tool_log,
+            progress,
             tool_registry,
             agent_coordinator,
             use_agents,
@@ -213,6 +247,11 @@ impl APChat {
             verbose,
             debug_level: 0, // Default debug level is 0 (off)
             non_interactive: false, // Default to interactive mode
+            confirmation_provider: None,
+            allowed_roots: Vec::new(),
+            tool_profile: None,
+            files_changed: BTreeSet::new(),
+            artifacts,
         };
 
         chat.messages.push(Message {
@@ -303,6 +342,10 @@ impl APChat {
                 skill_registry: self.skill_registry.clone(),
                 todo_manager: Some(self.todo_manager.clone()),
                 cancellation_token,
+                confirmation_provider: self.confirmation_provider.clone(),
+                allowed_roots: self.allowed_roots.clone(),
+                artifacts: Some(Arc::clone(&self.artifacts)),
+                tool_profile: self.tool_profile.clone(),
             };
 
             // Debug: Log current model
@@ -349,14 +392,7 @@ impl APChat {
     }
 
     fn switch_model(&mut self, model_str: &str, reason: &str) -> Result<String> {
-        let new_model = match model_str.to_lowercase().as_str() {
-            "blu_model" | "blu-model" | "blumodel" => ModelColor::BluModel,
-            "grn_model" | "grn-model" | "grnmodel" => ModelColor::GrnModel,
-            "red_model" | "red-model" | "redmodel" => ModelColor::RedModel,
-            // For backward compatibility, map Anthropic references to BluModel
-            "anthropic" | "claude" | "anthropic_model" | "anthropic-model" => ModelColor::BluModel,
-            _ => anyhow::bail!("Unknown model: {}. Available: 'blu_model', 'grn_model', 'red_model'", model_str),
-        };
+        let new_model = parse_model_name(model_str)?;
 
         if new_model == self.current_model {
             return Ok(format!(
@@ -386,6 +422,25 @@ impl APChat {
         ))
     }
 
+    /// Apply the tool profile and tool search options given on the command line
+    pub(crate) fn configure_tools(&mut self, cli: &Cli) -> Result<()> {
+        if cli.tool_search {
+            config::enable_tool_search(&mut self.tool_registry, &cli.core_tools);
+        }
+        if let Some(ref profile) = cli.tool_profile {
+            self.apply_tool_profile(profile)?;
+        }
+        Ok(())
+    }
+
+    /// Offer the model only the tools of the named profile
+    pub(crate) fn apply_tool_profile(&mut self, name: &str) -> Result<()> {
+        let profiles = config::load_tool_profiles(&self.work_dir);
+        self.tool_registry.apply_profile(profiles.get(name)?);
+        self.tool_profile = Some(name.to_string());
+        Ok(())
+    }
+
     /// Format the current model as a string in format "modname@backend(url)"
     fn format_current_model_string(&self) -> String {
         let provider = self.client_config.get_provider(self.current_model);
@@ -428,52 +483,145 @@ impl APChat {
         ))
     }
 
-    async fn execute_tool(&mut self, name: &str, arguments: &str) -> Result<String> {
+    /// Log the conversation and its tool results to `logger`, or stop logging
+    pub(crate) fn set_logger(&mut self, logger: Option<ConversationLogger>) {
+        self.tool_log.set_logger(logger.clone());
+        self.logger = logger;
+    }
+
+    /// Run the tool call `id`; cancelling `cancellation` stops the tool and
+    /// kills any processes it started
+    async fn execute_tool(
+        &mut self,
+        id: &str,
+        name: &str,
+        arguments: &str,
+        cancellation: Option<&tokio_util::sync::CancellationToken>,
+    ) -> Result<ToolResult> {
         // For backward compatibility, handle special tools that need main application state
         match name {
             "switch_model" => {
+                // The registry applies the policy, profiles and middleware;
+                // the switch itself needs this session's state
                 let args: SwitchModelArgs = serde_json::from_str(arguments)?;
-                self.switch_model(&args.model, &args.reason)
+                parse_model_name(&args.model)?;
+                let call = ToolInvocation::new(name, parse_tool_arguments(name, arguments)?).with_id(id);
+                let context = self.tool_context(cancellation);
+                let result = self.tool_registry.execute(call, &context).await;
+                self.finish_tool_call(name, result)?;
+                self.switch_model(&args.model, &args.reason).map(ToolResult::success)
             }
             _ => {
                 // Use the tool registry for all tools (including plan_edits and apply_edit_plan)
-                let params = ToolParameters::from_json(arguments)
-                    .with_context(|| format!("Failed to parse tool arguments for '{}': {}", name, arguments))?;
-
-                // Format current model string for subagent tools
-                let current_model_string = self.format_current_model_string();
+                let call = ToolInvocation::new(name, parse_tool_arguments(name, arguments)?).with_id(id);
+                let context = self.tool_context(cancellation);
+                let result = self.tool_registry.execute(call, &context).await;
+                self.finish_tool_call(name, result)
+            }
+        }
+    }
 
-                let mut context = ToolContext::new(
-                    self.work_dir.clone(),
-                    format!("session_{}", chrono::Utc::now().timestamp()),
-                    self.policy_manager.clone()
-                )
-                .with_terminal_manager(self.terminal_manager.clone())
-                .with_todo_manager(self.todo_manager.clone())
-                .with_non_interactive(self.non_interactive)
-                .with_current_model_string(current_model_string);
-
-                // Add skill registry if available
-                if let Some(ref registry) = self.skill_registry {
-                    context = context.with_skill_registry(Arc::clone(registry));
+    /// Run read-only tool calls (see `ToolRegistry::read_only_run`) at the
+    /// same time. Outcomes are in call order.
+    async fn execute_tools_concurrently(
+        &mut self,
+        calls: &[(&str, &str, &str)],
+        cancellation: Option<&tokio_util::sync::CancellationToken>,
+    ) -> Vec<Result<ToolResult>> {
+        let mut parse_errors = Vec::new();
+        let mut parsed = Vec::new();
+        for (id, name, arguments) in calls {
+            match parse_tool_arguments(name, arguments) {
+                Ok(params) => {
+                    parse_errors.push(None);
+                    parsed.push(ToolInvocation::new(name, params).with_id(id));
                 }
+                Err(e) => parse_errors.push(Some(e)),
+            }
+        }
+
+        let context = self.tool_context(cancellation);
+        let mut results = self.tool_registry.execute_concurrently(parsed, &context).await.into_iter();
+
+        let mut outcomes = Vec::new();
+        for ((_, name, _), parse_error) in calls.iter().zip(parse_errors) {
+            outcomes.push(match (parse_error, results.next()) {
+                (Some(e), _) => Err(e),
+                (None, Some(result)) => self.finish_tool_call(name, result),
+                (None, None) => Err(anyhow::anyhow!("Tool '{}' produced no result", name)),
+            });
+        }
+        outcomes
+    }
 
-                let context = context;
+    /// Context for tool calls made by this session
+    fn tool_context(&self, cancellation: Option<&tokio_util::sync::CancellationToken>) -> ToolContext {
+        // Format current model string for subagent tools
+        let current_model_string = self.format_current_model_string();
 
-                let result = self.tool_registry.execute_tool(name, params, &context).await;
+        let mut context = ToolContext::new(
+            self.work_dir.clone(),
+            format!("session_{}", chrono::Utc::now().timestamp()),
+            self.policy_manager.clone()
+        )
+        .with_terminal_manager(self.terminal_manager.clone())
+        .with_todo_manager(self.todo_manager.clone())
+        .with_non_interactive(self.non_interactive)
+        .with_current_model_string(current_model_string)
+        .with_allowed_roots(self.allowed_roots.clone())
+        .with_artifact_store(Arc::clone(&self.artifacts));
+
+        // Add skill registry if available
+        if let Some(ref registry) = self.skill_registry {
+            context = context.with_skill_registry(Arc::clone(registry));
+        }
 
-                if result.success {
-                    Ok(result.content)
-                } else {
-                    Err(anyhow::anyhow!("Tool '{}' failed: {}", name, result.error.unwrap_or_else(|| "Unknown error".to_string())))
-                }
-            }
+        if let Some(ref profile) = self.tool_profile {
+            context = context.with_tool_profile(profile.clone());
+        }
+
+        if let Some(ref provider) = self.confirmation_provider {
+            context = context.with_confirmation_provider(Arc::clone(provider));
+        }
+
+        if let Some(token) = cancellation {
+            context = context.with_cancellation(token.child_token());
         }
+
+        context
     }
 
+    /// Record what a call changed and turn a failed result into an error
+    fn finish_tool_call(&mut self, name: &str, result: ToolResult) -> Result<ToolResult> {
+        // Failed calls may still have changed files before failing
+        self.files_changed.extend(result.metadata.files_changed().cloned());
+
+        if result.success {
+            Ok(result)
+        } else {
+            Err(anyhow::anyhow!("Tool '{}' failed: {}", name, result.error.unwrap_or_else(|| "Unknown error".to_string())))
+        }
+    }
 
 }
 
+/// The model a `switch_model` call or `/switch` command names
+fn parse_model_name(model_str: &str) -> Result<ModelColor> {
+    match model_str.to_lowercase().as_str() {
+        "blu_model" | "blu-model" | "blumodel" => Ok(ModelColor::BluModel),
+        "grn_model" | "grn-model" | "grnmodel" => Ok(ModelColor::GrnModel),
+        "red_model" | "red-model" | "redmodel" => Ok(ModelColor::RedModel),
+        // For backward compatibility, map Anthropic references to BluModel
+        "anthropic" | "claude" | "anthropic_model" | "anthropic-model" => Ok(ModelColor::BluModel),
+        _ => anyhow::bail!("Unknown model: {}. Available: 'blu_model', 'grn_model', 'red_model'", model_str),
+    }
+}
+
+fn parse_tool_arguments(name: &str, arguments: &str) -> Result<ToolParameters> {
+    ToolParameters::from_json(arguments)
+        .with_context(|| format!("Failed to parse tool arguments for '{}': {}", name, arguments))
+}
+
 /// Resolve terminal backend type from CLI args and environment variable
 /// Priority: CLI arg > ENV var > default (PTY)
 pub(crate) fn resolve_terminal_backend(cli: &Cli) -> Result<TerminalBackendType> {
@@ -538,6 +686,12 @@ async fn main() -> Result<()> {
                 ));
                 terminal_cmd.execute(terminal_manager).await?
             }
+            Commands::Policy { command: policy_cmd } => {
+                let (global_path, project_path) = app::setup::policy_file_paths(&cli, &work_dir);
+                let policy_manager = PolicyManager::layered(global_path, Some(project_path), false)?;
+                policy_cmd.execute(&policy_manager, app::setup::policy_audit_log().as_ref())?
+            }
+            Commands::McpServe => return app::run_mcp_server(&cli, work_dir).await,
             _ => command.execute().await?
         };
         println!("{}", result);
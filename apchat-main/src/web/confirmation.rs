@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use uuid::Uuid;
+
+use apchat_toolcore::{ConfirmationProvider, ConfirmationRequest, ConfirmationResponse};
+use crate::web::protocol::ServerMessage;
+use crate::web::session_manager::Session;
+
+/// How long to wait for a web client to answer a confirmation request
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Routes `Ask` policy decisions to the web clients of a session
+///
+/// The request is broadcast as a `ToolCallRequest` that requires confirmation
+/// and answered through the session's `PendingConfirmation`/`ConfirmTool` flow.
+pub struct WebConfirmation {
+    session: Weak<Session>,
+}
+
+impl WebConfirmation {
+    /// Holds a weak reference, since the session owns the chat that owns this provider
+    pub fn new(session: &Arc<Session>) -> Self {
+        Self {
+            session: Arc::downgrade(session),
+        }
+    }
+}
+
+impl std::fmt::Debug for WebConfirmation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebConfirmation")
+            .field("session", &self.session.upgrade().map(|s| s.id))
+            .finish()
+    }
+}
+
+#[async_trait]
+impl ConfirmationProvider for WebConfirmation {
+    async fn confirm(&self, request: &ConfirmationRequest) -> anyhow::Result<ConfirmationResponse> {
+        let Some(session) = self.session.upgrade() else {
+            return Ok(ConfirmationResponse::deny(Some("Web session closed".to_string())));
+        };
+
+        let confirmation_id = format!("confirm-{}", Uuid::new_v4());
+        let rx = session
+            .register_confirmation(
+                confirmation_id.clone(),
+                request.action.to_string(),
+                request.target.clone(),
+            )
+            .await;
+
+        session
+            .broadcast(ServerMessage::ToolCallRequest {
+                tool_call_id: confirmation_id.clone(),
+                name: request.action.to_string(),
+                arguments: serde_json::json!({
+                    "target": request.target,
+                    "prompt": request.prompt,
+                    "origin": request.origin,
+                }),
+                requires_confirmation: true,
+                diff: request.preview.clone(),
+                iteration: None,
+                max_iterations: None,
+            })
+            .await;
+
+        let response = match tokio::time::timeout(CONFIRMATION_TIMEOUT, rx).await {
+            Ok(Ok(true)) => ConfirmationResponse::approve(),
+            Ok(Ok(false)) => ConfirmationResponse::deny(None),
+            Ok(Err(_)) => ConfirmationResponse::deny(Some("Confirmation channel closed".to_string())),
+            Err(_) => {
+                session.pending_confirmations.write().await.remove(&confirmation_id);
+                ConfirmationResponse::deny(Some("Confirmation timed out (5 minutes)".to_string()))
+            }
+        };
+
+        // Close the confirmation card in the UI
+        let outcome = if response.approved { "Approved" } else { "Denied" };
+        session
+            .broadcast(ServerMessage::ToolCallResult {
+                tool_call_id: confirmation_id,
+                result: outcome.to_string(),
+                success: response.approved,
+                formatted_result: Some(outcome.to_string()),
+                metadata: None,
+            })
+            .await;
+
+        Ok(response)
+    }
+}
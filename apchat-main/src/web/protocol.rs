@@ -3,6 +3,7 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use apchat_models::Message;
+use apchat_toolcore::ToolMetadata;
 
 /// Session ID type
 pub type SessionId = Uuid;
@@ -18,6 +19,9 @@ pub struct SessionConfig {
     pub stream_responses: bool,
     #[serde(default)]
     pub early_superpowers: bool,
+    /// Tool profile the session offers the model; all tools when unset
+    #[serde(default)]
+    pub tool_profile: Option<String>,
 }
 
 fn default_stream() -> bool {
@@ -104,6 +108,8 @@ pub enum ServerMessage {
         success: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         formatted_result: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        metadata: Option<ToolMetadata>,
     },
 
     // State updates
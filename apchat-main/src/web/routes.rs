@@ -37,6 +37,7 @@ pub fn create_router(state: AppState) -> Router {
             "/api/sessions/:id",
             get(get_session_details).delete(close_session),
         )
+        .route("/api/sessions/:id/artifacts/:handle", get(get_artifact))
         // WebSocket endpoint
         .route("/ws/:session_id", get(websocket_handler))
         // Static files (HTML pages)
@@ -92,6 +93,24 @@ async fn close_session(
     })))
 }
 
+/// GET /api/sessions/:id/artifacts/:handle - Full output of a tool call that
+/// was excerpted for the model
+async fn get_artifact(
+    State(state): State<AppState>,
+    Path((id, handle)): Path<(SessionId, String)>,
+) -> Result<String, AppError> {
+    let session = state
+        .session_manager
+        .get_session(&id)
+        .await
+        .ok_or_else(|| AppError::NotFound("Session not found".into()))?;
+
+    session
+        .artifacts
+        .read(&handle)
+        .map_err(|e| AppError::NotFound(e.to_string()))
+}
+
 /// GET /ws/:session_id - WebSocket endpoint
 async fn websocket_handler(
     ws: WebSocketUpgrade,
@@ -219,48 +238,6 @@ async fn handle_client_message(
     }
 }
 
-/// Check if tool requires confirmation and extract plan/diff
-async fn check_tool_confirmation(
-    tool_name: &str,
-    tool_args: &str,
-    work_dir: &std::path::Path,
-) -> (bool, Option<String>) {
-    match tool_name {
-        "apply_edit_plan" => {
-            // Try to load and format the edit plan
-            let plan_path = work_dir.join(".apchat_edit_plan.json");
-            if let Ok(content) = tokio::fs::read_to_string(&plan_path).await {
-                if let Ok(plan) = serde_json::from_str::<Vec<serde_json::Value>>(&content) {
-                    let mut diff_text = String::new();
-                    for (idx, edit) in plan.iter().enumerate() {
-                        diff_text.push_str(&format!(
-                            "Edit #{} {} - {}\n",
-                            idx + 1,
-                            edit.get("file_path").and_then(|v| v.as_str()).unwrap_or("?"),
-                            edit.get("description").and_then(|v| v.as_str()).unwrap_or("?")
-                        ));
-                        if let Some(old) = edit.get("old_content").and_then(|v| v.as_str()) {
-                            for line in old.lines() {
-                                diff_text.push_str(&format!("  -{}\n", line));
-                            }
-                        }
-                        if let Some(new) = edit.get("new_content").and_then(|v| v.as_str()) {
-                            for line in new.lines() {
-                                diff_text.push_str(&format!("  +{}\n", line));
-                            }
-                        }
-                        diff_text.push('\n');
-                    }
-                    return (true, Some(diff_text));
-                }
-            }
-            (true, None)
-        }
-        "write_file" | "edit_file" => (true, None), // These also need confirmation but no pre-extracted diff
-        _ => (false, None),
-    }
-}
-
 /// Chat loop with WebSocket broadcasts (single LLM mode)
 async fn handle_chat_with_broadcast(
     session: &Arc<crate::web::session_manager::Session>,
@@ -305,108 +282,38 @@ async fn handle_chat_with_broadcast(
             tool_call_iterations += 1;
 
             for tool_call in tool_calls {
-                // Check if tool requires confirmation
-                let work_dir = session.apchat.lock().await.work_dir.clone();
-                let (requires_confirmation, diff) = check_tool_confirmation(
-                    &tool_call.function.name,
-                    &tool_call.function.arguments,
-                    &work_dir,
-                )
-                .await;
-
                 // Broadcast tool call request
                 let tool_msg = ServerMessage::ToolCallRequest {
                     tool_call_id: tool_call.id.clone(),
                     name: tool_call.function.name.clone(),
                     arguments: serde_json::from_str(&tool_call.function.arguments)
                         .unwrap_or(serde_json::json!({})),
-                    requires_confirmation,
-                    diff,
+                    // Confirmations are asked separately, through the
+                    // session's confirmation provider
+                    requires_confirmation: false,
+                    diff: None,
                     iteration: Some(tool_call_iterations),
                     max_iterations: Some(MAX_TOOL_ITERATIONS),
                 };
                 session.broadcast(tool_msg).await;
 
-                // If requires confirmation, wait for user response
-                if requires_confirmation {
-                    eprintln!("⏳ Registering confirmation for tool_call_id: {}", tool_call.id);
-                    let confirmation_rx = session
-                        .register_confirmation(
-                            tool_call.id.clone(),
-                            tool_call.function.name.clone(),
-                            tool_call.function.arguments.clone(),
-                        )
-                        .await;
-
-                    eprintln!("⏳ Waiting for user confirmation...");
-                    // Wait for confirmation (with timeout)
-                    let confirmed = match tokio::time::timeout(
-                        std::time::Duration::from_secs(300), // 5 minute timeout
-                        confirmation_rx,
-                    )
-                    .await
-                    {
-                        Ok(Ok(confirmed)) => {
-                            eprintln!("✅ Received confirmation: {}", confirmed);
-                            confirmed
-                        }
-                        Ok(Err(_)) => {
-                            eprintln!("❌ Confirmation channel closed");
-                            false
-                        }
-                        Err(_) => {
-                            // Timeout
-                            eprintln!("⏱️  Confirmation timeout");
-                            let error_msg = ServerMessage::Error {
-                                message: "Tool confirmation timeout (5 minutes)".to_string(),
-                                recoverable: true,
-                            };
-                            session.broadcast(error_msg).await;
-                            false
-                        }
-                    };
-
-                    if !confirmed {
-                        eprintln!("🚫 Tool execution denied");
-
-                        // User denied, send error result
-                        let error_str = "Tool execution cancelled by user".to_string();
-                        let result_msg = ServerMessage::ToolCallResult {
-                            tool_call_id: tool_call.id.clone(),
-                            result: error_str.clone(),
-                            success: false,
-                            formatted_result: Some(error_str.clone()),
-                        };
-                        session.broadcast(result_msg).await;
-
-                        // Add cancellation to history
-                        session.apchat.lock().await.messages.push(ChatMessage {
-                            role: "tool".to_string(),
-                            content: error_str,
-                            tool_calls: None,
-                            tool_call_id: Some(tool_call.id.clone()),
-                            name: Some(tool_call.function.name.clone()),
-                            reasoning: None,
-                        });
-                        continue; // Skip to next tool call
-                    }
-                }
-
-                // Execute tool (either confirmed or doesn't need confirmation)
+                // Execute tool; its middleware asks for any confirmation
                 let mut apchat = session.apchat.lock().await;
                 let result = apchat
-                    .execute_tool(&tool_call.function.name, &tool_call.function.arguments)
+                    .execute_tool(&tool_call.id, &tool_call.function.name, &tool_call.function.arguments, None)
                     .await;
                 drop(apchat);
 
                 // Broadcast tool result
                 match result {
-                    Ok(result_str) => {
+                    Ok(tool_result) => {
+                        let result_str = tool_result.content.clone();
                         let result_msg = ServerMessage::ToolCallResult {
                             tool_call_id: tool_call.id.clone(),
                             result: result_str.clone(),
                             success: true,
-                            formatted_result: Some(result_str.clone()),
+                            formatted_result: Some(tool_result.display_content().to_string()),
+                            metadata: Some(tool_result.metadata),
                         };
                         session.broadcast(result_msg).await;
 
@@ -427,6 +334,7 @@ async fn handle_chat_with_broadcast(
                             result: error_str.clone(),
                             success: false,
                             formatted_result: Some(error_str.clone()),
+                            metadata: None,
                         };
                         session.broadcast(result_msg).await;
 
@@ -9,8 +9,10 @@ use uuid::Uuid;
 
 use crate::config::ClientConfig;
 use apchat_policy::PolicyManager;
+use apchat_toolcore::ArtifactStore;
 use crate::web::protocol::{ServerMessage, SessionConfig, SessionInfo};
 use crate::web::persistence::{SessionPersistence, PersistentSession};
+use crate::web::confirmation::WebConfirmation;
 use crate::chat::state::ChatState;
 use crate::APChat;
 
@@ -59,6 +61,9 @@ pub struct Session {
     pub created_at: DateTime<Utc>,
     pub last_activity: Arc<tokio::sync::Mutex<DateTime<Utc>>>,
     pub pending_confirmations: Arc<RwLock<HashMap<String, PendingConfirmation>>>,
+    /// Full tool output that was excerpted for the model; shared with `apchat`
+    /// so it can be served while a chat turn holds the session lock
+    pub artifacts: Arc<ArtifactStore>,
 }
 
 impl Session {
@@ -71,6 +76,7 @@ impl Session {
             id,
             session_type,
             title: Arc::new(RwLock::new(None)),
+            artifacts: Arc::clone(&apchat.artifacts),
             apchat: Arc::new(tokio::sync::Mutex::new(apchat)),
             clients: Arc::new(RwLock::new(Vec::new())),
             created_at: Utc::now(),
@@ -96,6 +102,12 @@ impl Session {
         rx
     }
 
+    /// Route this session's `Ask` policy decisions to its web clients
+    pub async fn enable_web_confirmation(self: &Arc<Self>) {
+        let provider = WebConfirmation::new(self);
+        self.apchat.lock().await.confirmation_provider = Some(Arc::new(provider));
+    }
+
     /// Respond to a pending confirmation
     pub async fn respond_to_confirmation(&self, tool_call_id: &str, confirmed: bool) -> bool {
         if let Some(pending) = self.pending_confirmations.write().await.remove(tool_call_id) {
@@ -259,6 +271,9 @@ impl SessionManager {
 
         apchat.current_model = model;
         apchat.non_interactive = true; // Web sessions should not prompt for input
+        if let Some(ref profile) = config.tool_profile {
+            apchat.apply_tool_profile(profile)?;
+        }
 
         // Create session
         let session = Arc::new(Session::new(
@@ -266,6 +281,7 @@ impl SessionManager {
             SessionType::Web,
             apchat,
         ));
+        session.enable_web_confirmation().await;
 
         // Store session
         self.sessions.write().await.insert(session_id, session.clone());
@@ -372,7 +388,9 @@ impl SessionManager {
                         *session.title.write().await = persistent_session.title;
 
                         // Store session
-                        self.sessions.write().await.insert(session_id, Arc::new(session));
+                        let session = Arc::new(session);
+                        session.enable_web_confirmation().await;
+                        self.sessions.write().await.insert(session_id, session);
                         loaded_count += 1;
                     }
                     Err(e) => {
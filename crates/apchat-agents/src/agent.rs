@@ -139,6 +139,14 @@ pub struct ExecutionContext {
     pub skill_registry: Option<std::sync::Arc<apchat_skills::SkillRegistry>>,
     pub todo_manager: Option<std::sync::Arc<apchat_todo::TodoManager>>,
     pub cancellation_token: Option<tokio_util::sync::CancellationToken>,
+    /// Parent session's confirmation provider; agents escalate `Ask` decisions to it
+    pub confirmation_provider: Option<std::sync::Arc<dyn apchat_toolcore::ConfirmationProvider>>,
+    /// Directories outside the workspace that file tools may also access
+    pub allowed_roots: Vec<std::path::PathBuf>,
+    /// Where oversized tool output is saved for `read_tool_output`
+    pub artifacts: Option<std::sync::Arc<apchat_toolcore::ArtifactStore>>,
+    /// Tool profile of the parent session, passed on to subagents
+    pub tool_profile: Option<String>,
 }
 
 
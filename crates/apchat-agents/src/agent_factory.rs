@@ -3,7 +3,7 @@ use crate::agent_config::AgentConfig;
 use apchat_logging::safe_truncate;
 use apchat_toolcore::tool_registry::ToolRegistry;
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use colored::Colorize;
 
@@ -83,6 +83,74 @@ impl ConfigurableAgent {
         })
     }
 
+    /// Context for the tool calls this agent makes
+    fn tool_context(&self, context: &ExecutionContext) -> apchat_toolcore::tool_context::ToolContext {
+        let mut tool_context = apchat_toolcore::tool_context::ToolContext::new(
+            context.workspace_dir.clone(),
+            context.session_id.clone(),
+            self.policy_manager.clone(),
+        )
+        .with_allowed_roots(context.allowed_roots.clone());
+        if let Some(ref tm) = context.terminal_manager {
+            tool_context = tool_context.with_terminal_manager(tm.clone());
+        }
+        if let Some(ref sr) = context.skill_registry {
+            tool_context = tool_context.with_skill_registry(sr.clone());
+        }
+        if let Some(ref todo_mgr) = context.todo_manager {
+            tool_context = tool_context.with_todo_manager(todo_mgr.clone());
+        }
+        if let Some(ref parent) = context.confirmation_provider {
+            tool_context = tool_context.with_confirmation_provider(Arc::new(
+                apchat_toolcore::EscalatingConfirmation::new(
+                    format!("agent '{}'", self.config.name),
+                    parent.clone(),
+                ),
+            ));
+        }
+        if let Some(ref token) = context.cancellation_token {
+            tool_context = tool_context.with_cancellation(token.child_token());
+        }
+        if let Some(ref artifacts) = context.artifacts {
+            tool_context = tool_context.with_artifact_store(artifacts.clone());
+        }
+        if let Some(ref profile) = context.tool_profile {
+            tool_context = tool_context.with_tool_profile(profile.clone());
+        }
+        tool_context
+    }
+
+    /// Run read-only tool calls at the same time; results are in call order
+    async fn execute_read_only_run(
+        &self,
+        tool_calls: &[crate::agent::ToolCall],
+        context: &ExecutionContext,
+    ) -> VecDeque<apchat_toolcore::ToolResult> {
+        let mut parsed = Vec::new();
+        let mut parse_errors = Vec::new();
+        for tool_call in tool_calls {
+            match apchat_toolcore::ToolParameters::from_json(&tool_call.function.arguments) {
+                Ok(params) => {
+                    parsed.push(apchat_toolcore::ToolInvocation::new(&tool_call.function.name, params).with_id(&tool_call.id));
+                    parse_errors.push(None);
+                }
+                Err(e) => parse_errors.push(Some(e)),
+            }
+        }
+
+        let tool_context = self.tool_context(context);
+        let mut results = self.tool_registry.execute_concurrently(parsed, &tool_context).await.into_iter();
+        parse_errors
+            .into_iter()
+            .map(|parse_error| match parse_error {
+                Some(e) => apchat_toolcore::ToolResult::error(format!("Failed to parse tool arguments: {}", e)),
+                None => results
+                    .next()
+                    .unwrap_or_else(|| apchat_toolcore::ToolResult::error("Tool produced no result".to_string())),
+            })
+            .collect()
+    }
+
     async fn execute_with_tools(
         &self,
         task: &crate::agent::Task,
@@ -280,8 +348,10 @@ impl ConfigurableAgent {
                         // Add assistant message with tool calls
                         messages.push(response.message.clone());
 
-                        // Execute each tool call
-                        for tool_call in tool_calls {
+                        // Execute each tool call; results of read-only calls that
+                        // already ran together wait in `ready`
+                        let mut ready = VecDeque::new();
+                        for (index, tool_call) in tool_calls.iter().enumerate() {
                             // Check for cancellation before each tool call
                             if let Some(ref token) = context.cancellation_token {
                                 if token.is_cancelled() {
@@ -320,25 +390,25 @@ impl ConfigurableAgent {
                                      tool_name,
                                      self.config.tools.contains(&tool_name.to_string()));
 
-                            let tool_result = if let Some(tool) = self.tool_registry.get_tool(tool_name) {
+                            if ready.is_empty() {
+                                let run = self.tool_registry.read_only_run(
+                                    tool_calls[index..].iter().map(|tc| tc.function.name.as_str())
+                                );
+                                if run > 1 {
+                                    println!("  {} Running {} read-only tool calls concurrently", "⚡".yellow(), run);
+                                    ready = self.execute_read_only_run(&tool_calls[index..index + run], context).await;
+                                }
+                            }
+
+                            let tool_result = if let Some(result) = ready.pop_front() {
+                                result
+                            } else if self.tool_registry.has_tool(tool_name) {
                                 // Parse arguments and execute
                                 match apchat_toolcore::ToolParameters::from_json(tool_args) {
                                     Ok(params) => {
-                                        let mut tool_context = apchat_toolcore::tool_context::ToolContext::new(
-                                            context.workspace_dir.clone(),
-                                            context.session_id.clone(),
-                                            self.policy_manager.clone(),
-                                        );
-                                        if let Some(ref tm) = context.terminal_manager {
-                                            tool_context = tool_context.with_terminal_manager(tm.clone());
-                                        }
-                                        if let Some(ref sr) = context.skill_registry {
-                                            tool_context = tool_context.with_skill_registry(sr.clone());
-                                        }
-                                        if let Some(ref todo_mgr) = context.todo_manager {
-                                            tool_context = tool_context.with_todo_manager(todo_mgr.clone());
-                                        }
-                                        tool.execute(params, &tool_context).await
+                                        let tool_context = self.tool_context(context);
+                                        let call = apchat_toolcore::ToolInvocation::new(tool_name, params).with_id(&tool_call.id);
+                                        self.tool_registry.execute(call, &tool_context).await
                                     }
                                     Err(e) => {
                                         apchat_toolcore::ToolResult::error(format!("Failed to parse tool arguments: {}", e))
@@ -348,14 +418,11 @@ impl ConfigurableAgent {
                                 apchat_toolcore::ToolResult::error(format!("Tool '{}' not found", tool_name))
                             };
 
-                            let result_preview = if tool_result.success {
-                                if tool_result.content.chars().count() > 200 {
-                                    format!("{}...", safe_truncate(&tool_result.content, 200))
-                                } else {
-                                    tool_result.content.clone()
-                                }
+                            let shown = tool_result.display_content();
+                            let result_preview = if shown.chars().count() > 200 {
+                                format!("{}...", safe_truncate(shown, 200))
                             } else {
-                                tool_result.error.clone().unwrap_or_else(|| "Unknown error".to_string())
+                                shown.to_string()
                             };
                             println!("  {} Tool result: {}", if tool_result.success { "✅" } else { "❌" }, result_preview);
 
@@ -545,6 +545,10 @@ impl PlanningCoordinator {
             skill_registry: context.skill_registry.clone(),
             todo_manager: context.todo_manager.clone(),
             cancellation_token: context.cancellation_token.clone(),
+            confirmation_provider: context.confirmation_provider.clone(),
+            allowed_roots: context.allowed_roots.clone(),
+            artifacts: context.artifacts.clone(),
+            tool_profile: context.tool_profile.clone(),
         };
 
         // Execute task
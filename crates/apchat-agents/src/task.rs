@@ -47,6 +47,10 @@ pub struct TaskContextBuilder {
     skill_registry: Option<std::sync::Arc<apchat_skills::SkillRegistry>>,
     todo_manager: Option<std::sync::Arc<apchat_todo::TodoManager>>,
     cancellation_token: Option<tokio_util::sync::CancellationToken>,
+    confirmation_provider: Option<std::sync::Arc<dyn apchat_toolcore::ConfirmationProvider>>,
+    allowed_roots: Vec<std::path::PathBuf>,
+    artifacts: Option<std::sync::Arc<apchat_toolcore::ArtifactStore>>,
+    tool_profile: Option<String>,
 }
 
 impl TaskContextBuilder {
@@ -61,6 +65,10 @@ impl TaskContextBuilder {
             skill_registry: None,
             todo_manager: None,
             cancellation_token: None,
+            confirmation_provider: None,
+            allowed_roots: Vec::new(),
+            artifacts: None,
+            tool_profile: None,
         }
     }
 
@@ -104,6 +112,26 @@ impl TaskContextBuilder {
         self
     }
 
+    pub fn with_confirmation_provider(mut self, provider: std::sync::Arc<dyn apchat_toolcore::ConfirmationProvider>) -> Self {
+        self.confirmation_provider = Some(provider);
+        self
+    }
+
+    pub fn with_allowed_roots(mut self, allowed_roots: Vec<std::path::PathBuf>) -> Self {
+        self.allowed_roots = allowed_roots;
+        self
+    }
+
+    pub fn with_artifact_store(mut self, artifacts: std::sync::Arc<apchat_toolcore::ArtifactStore>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+
+    pub fn with_tool_profile(mut self, profile: String) -> Self {
+        self.tool_profile = Some(profile);
+        self
+    }
+
     pub fn build(self) -> Result<crate::agent::ExecutionContext, String> {
         Ok(crate::agent::ExecutionContext {
             workspace_dir: self.workspace_dir.ok_or("workspace_dir is required")?,
@@ -115,6 +143,10 @@ impl TaskContextBuilder {
             skill_registry: self.skill_registry,
             todo_manager: self.todo_manager,
             cancellation_token: self.cancellation_token,
+            confirmation_provider: self.confirmation_provider,
+            allowed_roots: self.allowed_roots,
+            artifacts: self.artifacts,
+            tool_profile: self.tool_profile,
         })
     }
 }
@@ -96,10 +96,17 @@ impl AnthropicLlmClient {
 
     fn convert_tools_to_anthropic_format(&self, tools: Vec<ToolDefinition>) -> Vec<Value> {
         tools.into_iter().map(|tool| {
+            // The JSON Schema passes through unchanged, nested items, enums
+            // and bounds included; Anthropic only insists on an object schema
+            let input_schema = if tool.parameters.is_object() {
+                tool.parameters
+            } else {
+                serde_json::json!({"type": "object", "properties": {}})
+            };
             serde_json::json!({
                 "name": tool.name,
                 "description": tool.description,
-                "input_schema": tool.parameters
+                "input_schema": input_schema
             })
         }).collect()
     }
@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 pub mod anthropic;
 pub mod groq;
 pub mod llama_cpp;
+pub mod prompt_tools;
+
+pub use prompt_tools::PromptToolsClient;
 
 /// Chat message structure (OpenAI-compatible format)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,113 @@
+use crate::client::{ChatMessage, FunctionCall, LlmClient, LlmResponse, StreamingChunk, ToolCall, ToolDefinition};
+use anyhow::Result;
+use apchat_models::{Message, PromptTools, Tool, ToolCallDialect};
+use async_trait::async_trait;
+use futures::Stream;
+use std::sync::Arc;
+
+/// Gives tool calling to a backend without native tool support: tools are
+/// described in the prompt, earlier calls and results are sent as text turns
+/// and the calls the model writes are parsed out of its reply
+pub struct PromptToolsClient {
+    inner: Arc<dyn LlmClient>,
+    prompt_tools: PromptTools,
+}
+
+impl PromptToolsClient {
+    /// Wrap `inner`, writing tools in the format of `dialect`
+    pub fn new(inner: Arc<dyn LlmClient>, dialect: ToolCallDialect) -> Self {
+        Self {
+            inner,
+            prompt_tools: PromptTools::new(dialect),
+        }
+    }
+
+    fn prepare(&self, messages: Vec<ChatMessage>, tools: Vec<ToolDefinition>) -> Vec<ChatMessage> {
+        let messages: Vec<Message> = messages.into_iter().map(to_message).collect();
+        let tools: Vec<Tool> = tools
+            .into_iter()
+            .map(|tool| Tool {
+                tool_type: "function".to_string(),
+                function: apchat_models::FunctionDef {
+                    name: tool.name,
+                    description: tool.description,
+                    parameters: tool.parameters,
+                },
+            })
+            .collect();
+        self.prompt_tools
+            .prepare_messages(&messages, &tools)
+            .into_iter()
+            .map(from_message)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl LlmClient for PromptToolsClient {
+    async fn chat(&self, messages: Vec<ChatMessage>, tools: Vec<ToolDefinition>) -> Result<LlmResponse> {
+        let mut response = self.inner.chat(self.prepare(messages, tools), Vec::new()).await?;
+
+        let mut message = to_message(response.message);
+        self.prompt_tools.parse_response(&mut message);
+        response.message = from_message(message);
+        Ok(response)
+    }
+
+    async fn chat_completion(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.inner.chat_completion(&self.prepare(messages.to_vec(), Vec::new())).await
+    }
+
+    async fn chat_streaming(
+        &self,
+        messages: Vec<ChatMessage>,
+        tools: Vec<ToolDefinition>,
+    ) -> Result<Box<dyn Stream<Item = Result<StreamingChunk>> + Send + Unpin>> {
+        self.inner.chat_streaming(self.prepare(messages, tools), Vec::new()).await
+    }
+}
+
+fn to_message(message: ChatMessage) -> Message {
+    Message {
+        role: message.role,
+        content: message.content,
+        tool_calls: message.tool_calls.map(|calls| {
+            calls
+                .into_iter()
+                .map(|call| apchat_models::ToolCall {
+                    id: call.id,
+                    tool_type: "function".to_string(),
+                    function: apchat_models::FunctionCall {
+                        name: call.function.name,
+                        arguments: call.function.arguments,
+                    },
+                })
+                .collect()
+        }),
+        tool_call_id: message.tool_call_id,
+        name: message.name,
+        reasoning: message.reasoning,
+    }
+}
+
+fn from_message(message: Message) -> ChatMessage {
+    ChatMessage {
+        role: message.role,
+        content: message.content,
+        tool_calls: message.tool_calls.map(|calls| {
+            calls
+                .into_iter()
+                .map(|call| ToolCall {
+                    id: call.id,
+                    function: FunctionCall {
+                        name: call.function.name,
+                        arguments: call.function.arguments,
+                    },
+                })
+                .collect()
+        }),
+        tool_call_id: message.tool_call_id,
+        name: message.name,
+        reasoning: message.reasoning,
+    }
+}
@@ -66,6 +66,7 @@ pub use client::{
     TokenUsage,
     ToolDefinition,
     StreamingChunk,
+    PromptToolsClient,
 };
 
 pub use config::{
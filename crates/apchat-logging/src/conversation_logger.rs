@@ -3,8 +3,10 @@ use serde::Serialize;
 use serde_json;
 use anyhow::Result;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
 use crate::get_logs_dir;
 
 #[derive(Serialize)]
@@ -36,11 +38,17 @@ struct LogEntry {
     task_depth: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     agent_name: Option<String>,
+    /// Structured facts about a tool result (files touched, exit code, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<serde_json::Value>,
 }
 
+/// Appends conversation entries to a JSONL file. Clones write to the same
+/// file, so the session and its tool middleware can share one log.
+#[derive(Clone)]
 pub struct ConversationLogger {
     file_path: PathBuf,
-    file: Option<tokio::fs::File>,
+    file: Arc<Mutex<Option<tokio::fs::File>>>,
 }
 
 impl ConversationLogger {
@@ -60,7 +68,7 @@ impl ConversationLogger {
             .append(true)
             .open(&file_path)
             .await?;
-        Ok(Self { file_path, file: Some(file) })
+        Ok(Self { file_path, file: Arc::new(Mutex::new(Some(file))) })
     }
 
     /// Create a new logger for task mode; generates the file name with "-task" suffix.
@@ -79,17 +87,17 @@ impl ConversationLogger {
             .append(true)
             .open(&file_path)
             .await?;
-        Ok(Self { file_path, file: Some(file) })
+        Ok(Self { file_path, file: Arc::new(Mutex::new(Some(file))) })
     }
 
     /// Append a single log entry.
-    pub async fn log(&mut self, role: &str, content: &str, model: Option<&str>, is_binary: bool) {
+    pub async fn log(&self, role: &str, content: &str, model: Option<&str>, is_binary: bool) {
         self.log_with_task_context(role, content, model, is_binary, None, None, None, None).await;
     }
 
     /// Append a log entry with task context
     pub async fn log_with_task_context(
-        &mut self,
+        &self,
         role: &str,
         content: &str,
         model: Option<&str>,
@@ -112,8 +120,9 @@ impl ConversationLogger {
             parent_task_id: parent_task_id.map(|s| s.to_string()),
             task_depth,
             agent_name: agent_name.map(|s| s.to_string()),
+            metadata: None,
         };
-        if let Some(file) = &mut self.file {
+        if let Some(file) = self.file.lock().await.as_mut() {
             if let Ok(json) = serde_json::to_string(&entry) {
                 // Write the JSON line
                 if let Err(e) = file.write_all(json.as_bytes()).await {
@@ -127,7 +136,7 @@ impl ConversationLogger {
 
     /// Log an assistant message with tool calls
     pub async fn log_with_tool_calls(
-        &mut self,
+        &self,
         role: &str,
         content: &str,
         model: Option<&str>,
@@ -138,7 +147,7 @@ impl ConversationLogger {
 
     /// Log an assistant message with tool calls and task context
     pub async fn log_with_tool_calls_and_task(
-        &mut self,
+        &self,
         role: &str,
         content: &str,
         model: Option<&str>,
@@ -166,8 +175,9 @@ impl ConversationLogger {
             parent_task_id: parent_task_id.map(|s| s.to_string()),
             task_depth,
             agent_name: agent_name.map(|s| s.to_string()),
+            metadata: None,
         };
-        if let Some(file) = &mut self.file {
+        if let Some(file) = self.file.lock().await.as_mut() {
             if let Ok(json) = serde_json::to_string(&entry) {
                 if std::env::var("DEBUG_LOG").is_ok() {
                     eprintln!("[DEBUG] Writing tool_calls log entry: {}", &json[..json.len().min(100)]);
@@ -186,17 +196,18 @@ impl ConversationLogger {
 
     /// Log a tool result
     pub async fn log_tool_result(
-        &mut self,
+        &self,
         content: &str,
         tool_call_id: &str,
         tool_name: &str,
+        metadata: Option<serde_json::Value>,
     ) {
-        self.log_tool_result_with_task(content, tool_call_id, tool_name, None, None, None, None).await;
+        self.log_tool_result_with_task(content, tool_call_id, tool_name, None, None, None, None, metadata).await;
     }
 
     /// Log a tool result with task context
     pub async fn log_tool_result_with_task(
-        &mut self,
+        &self,
         content: &str,
         tool_call_id: &str,
         tool_name: &str,
@@ -204,6 +215,7 @@ impl ConversationLogger {
         parent_task_id: Option<&str>,
         task_depth: Option<usize>,
         agent_name: Option<&str>,
+        metadata: Option<serde_json::Value>,
     ) {
         let entry = LogEntry {
             timestamp: Local::now().to_rfc3339(),
@@ -218,8 +230,9 @@ impl ConversationLogger {
             parent_task_id: parent_task_id.map(|s| s.to_string()),
             task_depth,
             agent_name: agent_name.map(|s| s.to_string()),
+            metadata,
         };
-        if let Some(file) = &mut self.file {
+        if let Some(file) = self.file.lock().await.as_mut() {
             if let Ok(json) = serde_json::to_string(&entry) {
                 if std::env::var("DEBUG_LOG").is_ok() {
                     eprintln!("[DEBUG] Writing tool result for {}: {}", tool_name, &content[..content.len().min(50)]);
@@ -237,8 +250,8 @@ impl ConversationLogger {
     }
 
     /// Close the logger (explicit drop). Called on graceful shutdown.
-    pub async fn shutdown(&mut self) {
-        if let Some(file) = self.file.take() {
+    pub async fn shutdown(&self) {
+        if let Some(file) = self.file.lock().await.take() {
             // Ensure data is flushed
             let _ = file.sync_all().await;
         }
@@ -0,0 +1,380 @@
+//! Derive macros for APChat
+//!
+//! `#[derive(ToolArgs)]` turns a serde struct describing the arguments of a
+//! tool into the tool's parameter definitions and a typed parser, so the
+//! schema sent to the model and the code reading the arguments cannot drift
+//! apart.
+//!
+//! ```ignore
+//! #[derive(Debug, Deserialize, ToolArgs)]
+//! pub struct SearchFilesArgs {
+//!     /// Text or pattern to search for
+//!     pub query: String,
+//!     /// Maximum number of results to return
+//!     #[serde(default = "default_max_results")]
+//!     #[tool(min = 1)]
+//!     pub max_results: usize,
+//! }
+//! ```
+//!
+//! The doc comment of a field is its description. Fields are required
+//! unless they are an `Option` or have a serde default; a `default = "path"`
+//! function is also advertised as the schema default. `Vec`s become arrays,
+//! maps become objects, and any other struct type becomes a nested object
+//! described by its own `ToolArgs` implementation.
+//!
+//! `#[tool(...)]` adds constraints to a field:
+//! - `description = "..."` instead of the doc comment
+//! - `type = "string"` for types the macro cannot map, such as enums
+//! - `enum("a", "b")`, `min = 1`, `max = 10`, `min_items = 1`,
+//!   `max_items = 5`, `pattern = "^[a-z]+$"`
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, ExprPath, Fields, GenericArgument, Lit, LitStr,
+    PathArguments, Token, Type,
+};
+
+#[proc_macro_derive(ToolArgs, attributes(tool))]
+pub fn derive_tool_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// How a field without a value is filled in, from `#[serde(default)]`
+enum SerdeDefault {
+    Trait,
+    Path(ExprPath),
+}
+
+#[derive(Default)]
+struct SerdeOptions {
+    rename: Option<String>,
+    default: Option<SerdeDefault>,
+    skip: bool,
+}
+
+#[derive(Default)]
+struct ToolOptions {
+    description: Option<String>,
+    param_type: Option<LitStr>,
+    enum_values: Vec<Lit>,
+    minimum: Option<Expr>,
+    maximum: Option<Expr>,
+    min_items: Option<Expr>,
+    max_items: Option<Expr>,
+    pattern: Option<LitStr>,
+}
+
+/// What a Rust type looks like in the schema
+enum Kind<'a> {
+    Scalar { param_type: &'static str, unsigned: bool },
+    Array(&'a Type),
+    Map,
+    Nested,
+}
+
+impl Kind<'_> {
+    /// Arrays and objects may arrive encoded as JSON strings
+    fn is_json(&self) -> bool {
+        !matches!(self, Kind::Scalar { .. })
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                return Err(meta.error("ToolArgs does not support rename_all; rename fields one by one"));
+            }
+            skip_meta_value(&meta)
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(name, "ToolArgs needs a struct with named fields"));
+            }
+        },
+        _ => return Err(syn::Error::new_spanned(name, "ToolArgs can only be derived for structs")),
+    };
+
+    let mut definitions = Vec::new();
+    let mut parsers = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let serde = serde_options(&field.attrs)?;
+        let tool = tool_options(&field.attrs)?;
+
+        if serde.skip {
+            let value = match serde.default {
+                Some(SerdeDefault::Path(ref path)) => quote!(#path()),
+                _ => quote!(::std::default::Default::default()),
+            };
+            parsers.push(quote!(#ident: #value));
+            continue;
+        }
+
+        let param_name = serde.rename.clone().unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string());
+        let description = match tool.description.clone().or_else(|| doc_comment(&field.attrs)) {
+            Some(description) => description,
+            None => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "tool arguments need a doc comment or #[tool(description = \"...\")] to describe them to the model",
+                ))
+            }
+        };
+
+        let optional_inner = option_inner(&field.ty);
+        let value_type = optional_inner.unwrap_or(&field.ty);
+        let kind = kind_of(value_type);
+
+        let mut definition = match tool.param_type {
+            Some(ref param_type) => quote!(::apchat_models::ParameterDefinition::new(#param_type, #description)),
+            None => schema(value_type, &kind, quote!(#description)),
+        };
+
+        if !tool.enum_values.is_empty() {
+            let values = &tool.enum_values;
+            definition = quote!(#definition.with_enum([#(::apchat_models::__private::serde_json::Value::from(#values)),*]));
+        }
+        if tool.minimum.is_some() || tool.maximum.is_some() {
+            let unsigned = matches!(kind, Kind::Scalar { unsigned: true, .. });
+            let minimum = match tool.minimum {
+                Some(ref minimum) => quote!(::std::option::Option::Some((#minimum) as f64)),
+                None if unsigned => quote!(::std::option::Option::Some(0.0)),
+                None => quote!(::std::option::Option::None),
+            };
+            let maximum = match tool.maximum {
+                Some(ref maximum) => quote!(::std::option::Option::Some((#maximum) as f64)),
+                None => quote!(::std::option::Option::None),
+            };
+            definition = quote!(#definition.with_range(#minimum, #maximum));
+        }
+        if tool.min_items.is_some() || tool.max_items.is_some() {
+            let min_items = optional_tokens(&tool.min_items);
+            let max_items = optional_tokens(&tool.max_items);
+            definition = quote!(#definition.with_item_count(#min_items, #max_items));
+        }
+        if let Some(ref pattern) = tool.pattern {
+            definition = quote!(#definition.with_pattern(#pattern));
+        }
+        if let Some(SerdeDefault::Path(ref path)) = serde.default {
+            definition = quote!(#definition.with_default(
+                ::apchat_models::__private::serde_json::to_value(#path()).unwrap_or_default()
+            ));
+        }
+        if optional_inner.is_none() && serde.default.is_none() {
+            definition = quote!(#definition.required());
+        }
+        definitions.push(quote!((::std::string::String::from(#param_name), #definition)));
+
+        let is_json = match tool.param_type {
+            Some(ref param_type) => matches!(param_type.value().as_str(), "array" | "object"),
+            None => kind.is_json(),
+        };
+        let (get_required, get_optional) = if is_json {
+            (quote!(get_required_json), quote!(get_optional_json))
+        } else {
+            (quote!(get_required), quote!(get_optional))
+        };
+        let value = match (optional_inner, &serde.default) {
+            (Some(inner), _) => quote!(params.#get_optional::<#inner>(#param_name)?),
+            (None, Some(SerdeDefault::Trait)) => {
+                quote!(params.#get_optional::<#value_type>(#param_name)?.unwrap_or_default())
+            }
+            (None, Some(SerdeDefault::Path(path))) => {
+                quote!(params.#get_optional::<#value_type>(#param_name)?.unwrap_or_else(#path))
+            }
+            (None, None) => quote!(params.#get_required::<#value_type>(#param_name)?),
+        };
+        parsers.push(quote!(#ident: #value));
+    }
+
+    let construct = match &input.data {
+        Data::Struct(data) if matches!(data.fields, Fields::Unit) => quote!(Self),
+        _ => quote!(Self { #(#parsers),* }),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::apchat_models::ToolArgs for #name #ty_generics #where_clause {
+            fn parameters() -> ::std::collections::HashMap<::std::string::String, ::apchat_models::ParameterDefinition> {
+                ::std::collections::HashMap::from([#(#definitions),*])
+            }
+
+            fn parse(params: &::apchat_models::ToolParameters) -> ::apchat_models::__private::anyhow::Result<Self> {
+                ::std::result::Result::Ok(#construct)
+            }
+        }
+    })
+}
+
+/// Base definition of a value of type `ty`, before field constraints
+fn schema(ty: &Type, kind: &Kind, description: TokenStream2) -> TokenStream2 {
+    match kind {
+        Kind::Scalar { param_type, unsigned } => {
+            let definition = quote!(::apchat_models::ParameterDefinition::new(#param_type, #description));
+            if *unsigned {
+                quote!(#definition.with_range(::std::option::Option::Some(0.0), ::std::option::Option::None))
+            } else {
+                definition
+            }
+        }
+        Kind::Array(item) => {
+            let item = option_inner(item).unwrap_or(item);
+            let item_schema = schema(item, &kind_of(item), quote!(""));
+            quote!(::apchat_models::ParameterDefinition::array(#description, #item_schema))
+        }
+        Kind::Map => quote!(::apchat_models::ParameterDefinition::new("object", #description)),
+        Kind::Nested => quote!(::apchat_models::ParameterDefinition::object(
+            #description,
+            <#ty as ::apchat_models::ToolArgs>::parameters()
+        )),
+    }
+}
+
+fn kind_of(ty: &Type) -> Kind<'_> {
+    let ty = match ty {
+        Type::Reference(reference) => &reference.elem,
+        other => other,
+    };
+    let Some((ident, argument)) = last_segment(ty) else {
+        return Kind::Nested;
+    };
+
+    let scalar = |param_type, unsigned| Kind::Scalar { param_type, unsigned };
+    match ident.as_str() {
+        "String" | "str" | "PathBuf" | "char" => scalar("string", false),
+        "bool" => scalar("boolean", false),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => scalar("integer", false),
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => scalar("integer", true),
+        "f32" | "f64" => scalar("number", false),
+        "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => match argument {
+            Some(item) => Kind::Array(item),
+            None => Kind::Nested,
+        },
+        "HashMap" | "BTreeMap" | "Value" => Kind::Map,
+        _ => Kind::Nested,
+    }
+}
+
+/// Name of the last path segment of a type and its first type argument
+fn last_segment(ty: &Type) -> Option<(String, Option<&Type>)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let argument = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments.args.iter().find_map(|argument| match argument {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }),
+        _ => None,
+    };
+    Some((segment.ident.to_string(), argument))
+}
+
+fn option_inner(ty: &Type) -> Option<&Type> {
+    match last_segment(ty) {
+        Some((ident, argument)) if ident == "Option" => argument,
+        _ => None,
+    }
+}
+
+fn optional_tokens(value: &Option<Expr>) -> TokenStream2 {
+    match value {
+        Some(value) => quote!(::std::option::Option::Some(#value)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+/// Doc comment lines joined into one description
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta.require_name_value().ok()?.value {
+            Expr::Lit(ExprLit { lit: Lit::Str(line), .. }) => Some(line.value().trim().to_string()),
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
+
+fn serde_options(attrs: &[Attribute]) -> syn::Result<SerdeOptions> {
+    let mut options = SerdeOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") && meta.input.peek(Token![=]) {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("default") {
+                options.default = Some(if meta.input.peek(Token![=]) {
+                    SerdeDefault::Path(meta.value()?.parse::<LitStr>()?.parse()?)
+                } else {
+                    SerdeDefault::Trait
+                });
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                options.skip = true;
+            } else {
+                skip_meta_value(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+fn tool_options(attrs: &[Attribute]) -> syn::Result<ToolOptions> {
+    let mut options = ToolOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("tool")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("description") {
+                options.description = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("type") {
+                options.param_type = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("enum") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                options.enum_values = Punctuated::<Lit, Token![,]>::parse_terminated(&content)?
+                    .into_iter()
+                    .collect();
+            } else if meta.path.is_ident("min") {
+                options.minimum = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("max") {
+                options.maximum = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("min_items") {
+                options.min_items = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("max_items") {
+                options.max_items = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("pattern") {
+                options.pattern = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown tool attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// Consume the value of a serde attribute we do not interpret
+fn skip_meta_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.input.parse::<proc_macro2::Group>()?;
+    }
+    Ok(())
+}
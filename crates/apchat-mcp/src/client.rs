@@ -0,0 +1,342 @@
+//! JSON-RPC over stdio with one MCP server process.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::McpServerConfig;
+
+/// Protocol revision requested in the handshake
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// List requests stop after this many pages, in case a server keeps
+/// returning a cursor
+const MAX_PAGES: usize = 100;
+
+type Writer = Arc<tokio::sync::Mutex<ChildStdin>>;
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// A tool offered by a server (`tools/list`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "inputSchema", default)]
+    pub input_schema: Value,
+    #[serde(default)]
+    pub annotations: ToolAnnotations,
+}
+
+/// Hints a server gives about a tool; they are not trusted for policy
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ToolAnnotations {
+    #[serde(rename = "readOnlyHint", default)]
+    pub read_only_hint: bool,
+}
+
+/// A resource offered by a server (`resources/list`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteResource {
+    pub uri: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A prompt template offered by a server (`prompts/list`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemotePrompt {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// A running MCP server that has completed the initialize handshake. The
+/// server process is killed when the client is dropped.
+pub struct McpClient {
+    name: String,
+    writer: Writer,
+    pending: Pending,
+    closed: Arc<AtomicBool>,
+    next_id: AtomicU64,
+    timeout: Duration,
+    capabilities: Value,
+    instructions: Option<String>,
+    _child: Mutex<Child>,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient").field("name", &self.name).finish_non_exhaustive()
+    }
+}
+
+impl McpClient {
+    /// Start the server and perform the initialize handshake
+    pub async fn connect(name: &str, config: &McpServerConfig, work_dir: &Path) -> Result<Arc<Self>> {
+        let mut command = Command::new(&config.command);
+        command
+            .args(&config.args)
+            .envs(&config.env)
+            .current_dir(match config.cwd {
+                Some(ref cwd) => work_dir.join(cwd),
+                None => work_dir.to_path_buf(),
+            })
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true);
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("Failed to start MCP server '{}' ({})", name, config.command))?;
+
+        let stdin = child.stdin.take().context("MCP server has no stdin")?;
+        let stdout = child.stdout.take().context("MCP server has no stdout")?;
+        let writer: Writer = Arc::new(tokio::sync::Mutex::new(stdin));
+        let pending: Pending = Arc::default();
+        let closed = Arc::new(AtomicBool::new(false));
+        tokio::spawn(read_messages(stdout, Arc::clone(&writer), Arc::clone(&pending), Arc::clone(&closed)));
+
+        let mut client = Self {
+            name: name.to_string(),
+            writer,
+            pending,
+            closed,
+            next_id: AtomicU64::new(1),
+            timeout: config.timeout(),
+            capabilities: Value::Null,
+            instructions: None,
+            _child: Mutex::new(child),
+        };
+
+        let initialized = client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "apchat", "version": env!("CARGO_PKG_VERSION")},
+                }),
+            )
+            .await
+            .with_context(|| format!("MCP server '{}' failed to initialize", name))?;
+        client.capabilities = initialized.get("capabilities").cloned().unwrap_or(Value::Null);
+        client.instructions = initialized.get("instructions").and_then(Value::as_str).map(str::to_string);
+        client.notify("notifications/initialized", json!({})).await?;
+
+        Ok(Arc::new(client))
+    }
+
+    /// Name of the server in the config
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Usage notes the server sent in the handshake
+    pub fn instructions(&self) -> Option<&str> {
+        self.instructions.as_deref()
+    }
+
+    /// Whether the server declared a capability, e.g. `resources`
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.get(capability).is_some_and(|value| !value.is_null())
+    }
+
+    pub async fn list_tools(&self) -> Result<Vec<RemoteTool>> {
+        self.list("tools/list", "tools").await
+    }
+
+    pub async fn list_resources(&self) -> Result<Vec<RemoteResource>> {
+        self.list("resources/list", "resources").await
+    }
+
+    pub async fn list_prompts(&self) -> Result<Vec<RemotePrompt>> {
+        self.list("prompts/list", "prompts").await
+    }
+
+    /// Call a tool; the call is withdrawn if `cancellation` fires first
+    pub async fn call_tool(&self, name: &str, arguments: Value, cancellation: &CancellationToken) -> Result<Value> {
+        self.send("tools/call", json!({"name": name, "arguments": arguments}), Some(cancellation))
+            .await
+    }
+
+    pub async fn read_resource(&self, uri: &str, cancellation: &CancellationToken) -> Result<Value> {
+        self.send("resources/read", json!({"uri": uri}), Some(cancellation)).await
+    }
+
+    pub async fn get_prompt(&self, name: &str, arguments: Value, cancellation: &CancellationToken) -> Result<Value> {
+        self.send("prompts/get", json!({"name": name, "arguments": arguments}), Some(cancellation))
+            .await
+    }
+
+    /// Send a request and wait at most the server's timeout for the result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        self.send(method, params, None).await
+    }
+
+    /// Send a request and wait for its result. With a cancellation token the
+    /// caller's deadline applies instead of the server's timeout, and a
+    /// cancelled request is withdrawn with `notifications/cancelled`.
+    async fn send(&self, method: &str, params: Value, cancellation: Option<&CancellationToken>) -> Result<Value> {
+        if self.closed.load(Ordering::SeqCst) {
+            anyhow::bail!("MCP server '{}' has exited", self.name);
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        if let Err(e) = write_message(&self.writer, &message).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e).with_context(|| format!("Failed to send {} to MCP server '{}'", method, self.name));
+        }
+
+        let never = CancellationToken::new();
+        let (cancelled, limit) = match cancellation {
+            Some(token) => (token, None),
+            None => (&never, Some(self.timeout)),
+        };
+        let timed_out = async {
+            match limit {
+                Some(limit) => tokio::time::sleep(limit).await,
+                None => std::future::pending().await,
+            }
+        };
+        let response = tokio::select! {
+            response = receiver => response,
+            _ = cancelled.cancelled() => {
+                self.withdraw(id, "cancelled").await;
+                anyhow::bail!("{} on MCP server '{}' was cancelled", method, self.name);
+            }
+            _ = timed_out => {
+                self.withdraw(id, "timed out").await;
+                anyhow::bail!("{} on MCP server '{}' timed out after {:?}", method, self.name, self.timeout);
+            }
+        };
+
+        match response {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(message)) => anyhow::bail!("MCP server '{}' returned an error for {}: {}", self.name, method, message),
+            Err(_) => anyhow::bail!("MCP server '{}' exited before answering {}", self.name, method),
+        }
+    }
+
+    /// Stop waiting for a request and tell the server to stop working on it
+    async fn withdraw(&self, id: u64, reason: &str) {
+        self.pending.lock().unwrap().remove(&id);
+        let _ = self
+            .notify("notifications/cancelled", json!({"requestId": id, "reason": reason}))
+            .await;
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        write_message(&self.writer, &json!({"jsonrpc": "2.0", "method": method, "params": params})).await
+    }
+
+    /// Collect every page of a list request
+    async fn list<T: DeserializeOwned>(&self, method: &str, key: &str) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_PAGES {
+            let params = match cursor {
+                Some(ref cursor) => json!({"cursor": cursor}),
+                None => json!({}),
+            };
+            let result = self.request(method, params).await?;
+            let page: Vec<T> = serde_json::from_value(result.get(key).cloned().unwrap_or_else(|| json!([])))
+                .with_context(|| format!("Unexpected {} result from MCP server '{}'", method, self.name))?;
+            items.extend(page);
+
+            match result.get("nextCursor").and_then(Value::as_str) {
+                Some(next) => cursor = Some(next.to_string()),
+                None => break,
+            }
+        }
+        Ok(items)
+    }
+}
+
+async fn write_message(writer: &Writer, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    let mut writer = writer.lock().await;
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Route responses to their waiting requests until the server exits
+async fn read_messages(stdout: ChildStdout, writer: Writer, pending: Pending, closed: Arc<AtomicBool>) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        // Anything that is not a JSON-RPC message, e.g. stray logging, is skipped
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+
+        match (message.get("id"), message.get("method").and_then(Value::as_str)) {
+            (Some(id), None) => {
+                let Some(sender) = id.as_u64().and_then(|id| pending.lock().unwrap().remove(&id)) else {
+                    continue;
+                };
+                let response = match message.get("error") {
+                    Some(error) => Err(error
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown error")
+                        .to_string()),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(response);
+            }
+            (Some(id), Some(method)) => {
+                // Requests from the server: answer pings, decline the rest
+                let reply = if method == "ping" {
+                    json!({"jsonrpc": "2.0", "id": id, "result": {}})
+                } else {
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32601, "message": format!("Method not supported: {}", method)},
+                    })
+                };
+                let _ = write_message(&writer, &reply).await;
+            }
+            // Notifications need no answer
+            _ => {}
+        }
+    }
+
+    // The server is gone; dropping the senders fails every waiting request
+    closed.store(true, Ordering::SeqCst);
+    pending.lock().unwrap().clear();
+}
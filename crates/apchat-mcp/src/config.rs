@@ -0,0 +1,144 @@
+//! Which MCP servers to start, read from `mcp.toml`:
+//!
+//! ```toml
+//! [servers.tracker]
+//! command = "tracker-mcp"
+//! args = ["--stdio"]
+//! env = { TRACKER_URL = "https://tracker.internal" }
+//! timeout_secs = 60
+//! ```
+//!
+//! The user-global file (`~/.okaychat/mcp.toml`) and the project file
+//! (`.apchat/mcp.toml`) are merged. A project server cannot replace a global
+//! server of the same name, which would inherit the grants given to it, and
+//! project servers only start when the policy allows their command line.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Timeout of a request to a server that does not set `timeout_secs`
+pub const DEFAULT_MCP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Servers to start, by name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct McpConfig {
+    #[serde(default)]
+    pub servers: BTreeMap<String, McpServerConfig>,
+}
+
+/// How to start one stdio MCP server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct McpServerConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Directory to start in, relative to the workspace
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Read from the project file rather than the user's own
+    #[serde(skip)]
+    pub project: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl McpServerConfig {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: None,
+            timeout_secs: None,
+            enabled: true,
+            project: false,
+        }
+    }
+
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_secs = Some(timeout.as_secs());
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout_secs.map(Duration::from_secs).unwrap_or(DEFAULT_MCP_TIMEOUT)
+    }
+
+    /// The command and its arguments as one shell-quoted line, the target
+    /// the policy is asked about before starting the server
+    pub fn command_line(&self) -> String {
+        let quote = |word: &str| {
+            let plain = !word.is_empty()
+                && word.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:@,+%".contains(c));
+            match plain {
+                true => word.to_string(),
+                false => format!("'{}'", word.replace('\'', r"'\''")),
+            }
+        };
+        std::iter::once(self.command.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .map(quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl McpConfig {
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Read a config file; a missing file configures no servers
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid MCP config {}", path.display()))
+    }
+
+    /// Global config plus the project servers, marked as such. Project
+    /// servers named like a global server are reported and left out.
+    pub fn layered(global: Option<&Path>, project: &Path) -> Result<Self> {
+        let mut config = match global {
+            Some(path) => Self::load(path)?,
+            None => Self::default(),
+        };
+        for (name, mut server) in Self::load(project)?.servers {
+            if config.servers.contains_key(&name) {
+                eprintln!(
+                    "⚠️  Ignoring MCP server '{}' in {}: a global server has that name",
+                    name,
+                    project.display()
+                );
+                continue;
+            }
+            server.project = true;
+            config.servers.insert(name, server);
+        }
+        Ok(config)
+    }
+
+    /// Project config file (`.apchat/mcp.toml`)
+    pub fn project_config_path(work_dir: &Path) -> PathBuf {
+        work_dir.join(".apchat").join("mcp.toml")
+    }
+}
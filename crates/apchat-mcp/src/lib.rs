@@ -0,0 +1,67 @@
+//! Model Context Protocol client for APChat
+//!
+//! Spawns the MCP servers configured in `mcp.toml`, talks JSON-RPC to them
+//! over stdio and mounts their tools in a [`ToolRegistry`] under namespaced
+//! names (`mcp__<server>__<tool>`). Resources and prompts are exposed as one
+//! extra tool each per server. Every call is gated by the policy action
+//! `mcp_tool_call` on the target `<server>/<tool>`.
+//!
+//! [`McpServer`] works the other way round and publishes a registry's tools
+//! to an MCP client (`apchat mcp-serve`).
+
+pub mod client;
+pub mod config;
+pub mod schema;
+pub mod server;
+pub mod tools;
+
+pub use client::*;
+pub use config::*;
+pub use schema::*;
+pub use server::*;
+pub use tools::*;
+
+use apchat_toolcore::{Tool, ToolRegistry};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Category the tools of every MCP server are registered under
+pub const MCP_CATEGORY: &str = "mcp";
+
+/// Start every enabled server in `config`. Servers that fail to start are
+/// returned as errors so the others can still be used.
+pub async fn connect_servers(config: &McpConfig, work_dir: &Path) -> Vec<anyhow::Result<Arc<McpClient>>> {
+    let mut clients = Vec::new();
+    for (name, server) in config.servers.iter().filter(|(_, server)| server.enabled) {
+        clients.push(McpClient::connect(name, server, work_dir).await);
+    }
+    clients
+}
+
+/// Register the tools, resources and prompts of a connected server and
+/// return how many tools were added. Tools whose names are taken are skipped.
+pub async fn register_server(registry: &mut ToolRegistry, client: Arc<McpClient>) -> anyhow::Result<usize> {
+    let mut added = 0;
+    for remote in client.list_tools().await? {
+        added += add_tool(registry, McpTool::new(Arc::clone(&client), remote));
+    }
+    if client.supports("resources") {
+        let resources = client.list_resources().await.unwrap_or_default();
+        added += add_tool(registry, McpReadResourceTool::new(Arc::clone(&client), resources));
+    }
+    if client.supports("prompts") {
+        let prompts = client.list_prompts().await.unwrap_or_default();
+        if !prompts.is_empty() {
+            added += add_tool(registry, McpGetPromptTool::new(Arc::clone(&client), prompts));
+        }
+    }
+    Ok(added)
+}
+
+fn add_tool<T: Tool + 'static>(registry: &mut ToolRegistry, tool: T) -> usize {
+    if registry.has_tool(tool.name()) {
+        return 0;
+    }
+    registry.register_with_categories(tool, vec![MCP_CATEGORY.to_string()]);
+    1
+}
@@ -0,0 +1,87 @@
+//! Conversion of MCP tool input schemas (JSON Schema) into the parameter
+//! definitions tools declare, so remote tools are validated like local ones.
+
+use apchat_toolcore::ParameterDefinition;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Parameters described by an object schema's `properties` and `required`
+pub fn parameters_from_schema(schema: &Value) -> HashMap<String, ParameterDefinition> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(name, property)| {
+                    let mut definition = definition_from_schema(property);
+                    definition.required = required.contains(&name.as_str());
+                    (name.clone(), definition)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn definition_from_schema(schema: &Value) -> ParameterDefinition {
+    // `anyOf: [{..}, {type: null}]` is how optional values are usually written
+    let variant = ["anyOf", "oneOf"]
+        .iter()
+        .filter_map(|key| schema.get(*key).and_then(Value::as_array))
+        .flatten()
+        .find(|variant| variant.get("type").and_then(Value::as_str) != Some("null"));
+    let typed = variant.unwrap_or(schema);
+
+    let text = |key: &str| schema.get(key).or_else(|| typed.get(key)).and_then(Value::as_str).map(str::to_string);
+    let number = |key: &str| typed.get(key).and_then(Value::as_f64);
+    let count = |key: &str| typed.get(key).and_then(Value::as_u64).map(|n| n as usize);
+
+    let param_type = schema_type(typed);
+    ParameterDefinition {
+        description: text("description").unwrap_or_default(),
+        required: false,
+        default: schema.get("default").or_else(|| typed.get("default")).cloned(),
+        enum_values: typed.get("enum").and_then(Value::as_array).cloned(),
+        items: typed.get("items").map(|items| Box::new(definition_from_schema(items))),
+        properties: if param_type == "object" { parameters_from_schema(typed) } else { HashMap::new() },
+        minimum: number("minimum"),
+        maximum: number("maximum"),
+        min_items: count("minItems"),
+        max_items: count("maxItems"),
+        pattern: text("pattern"),
+        param_type,
+    }
+}
+
+/// The JSON type of a schema, inferred from its other keywords when `type`
+/// is missing
+fn schema_type(schema: &Value) -> String {
+    match schema.get("type") {
+        Some(Value::String(name)) => return name.clone(),
+        Some(Value::Array(names)) => {
+            if let Some(name) = names.iter().filter_map(Value::as_str).find(|name| *name != "null") {
+                return name.to_string();
+            }
+        }
+        _ => {}
+    }
+    if schema.get("properties").is_some() {
+        "object"
+    } else if schema.get("items").is_some() {
+        "array"
+    } else {
+        match schema.get("enum").and_then(Value::as_array).and_then(|values| values.first()) {
+            Some(Value::Number(n)) if n.is_i64() || n.is_u64() => "integer",
+            Some(Value::Number(_)) => "number",
+            Some(Value::Bool(_)) => "boolean",
+            _ => "string",
+        }
+    }
+    .to_string()
+}
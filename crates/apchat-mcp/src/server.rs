@@ -0,0 +1,222 @@
+//! MCP server that publishes the tools of a [`ToolRegistry`] over a JSON-RPC
+//! stream, one message per line. Calls go through
+//! [`ToolRegistry::execute_tool`], so middleware, argument validation and the
+//! policy checks of the tools apply as in a chat session.
+
+use anyhow::Result;
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{SideEffects, ToolParameters, ToolRegistry, ToolResult};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+use crate::client::PROTOCOL_VERSION;
+
+/// Protocol revisions a client may ask for; others get [`PROTOCOL_VERSION`]
+const SUPPORTED_VERSIONS: [&str; 3] = [PROTOCOL_VERSION, "2025-03-26", "2024-11-05"];
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+type Output<W> = Arc<tokio::sync::Mutex<W>>;
+type ContextFactory = Arc<dyn Fn() -> ToolContext + Send + Sync>;
+type Running = Arc<Mutex<HashMap<String, CancellationToken>>>;
+
+/// A JSON-RPC error code and message
+type RpcError = (i64, String);
+
+/// Serves the tools of a registry to an MCP client
+pub struct McpServer {
+    registry: Arc<ToolRegistry>,
+    context: ContextFactory,
+    instructions: Option<String>,
+}
+
+impl McpServer {
+    /// `context` builds the [`ToolContext`] each call runs with; the server
+    /// gives it the call's cancellation token
+    pub fn new(registry: Arc<ToolRegistry>, context: impl Fn() -> ToolContext + Send + Sync + 'static) -> Self {
+        Self {
+            registry,
+            context: Arc::new(context),
+            instructions: None,
+        }
+    }
+
+    /// Usage notes sent to the client in the handshake
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Answer messages from `input` on `output` until the input ends, then
+    /// wait for the calls still running
+    pub async fn serve<R, W>(&self, input: R, output: W) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let output: Output<W> = Arc::new(tokio::sync::Mutex::new(output));
+        let running: Running = Arc::default();
+        let mut calls = JoinSet::new();
+
+        let mut lines = BufReader::new(input).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => message,
+                Err(e) => {
+                    write_message(&output, &error_response(Value::Null, (PARSE_ERROR, e.to_string()))).await?;
+                    continue;
+                }
+            };
+
+            let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+            match (message.get("id").cloned(), message.get("method").and_then(Value::as_str)) {
+                (Some(id), Some("tools/call")) => {
+                    // Calls run concurrently so a slow tool neither blocks
+                    // other requests nor its own cancellation
+                    let token = CancellationToken::new();
+                    running.lock().unwrap().insert(id.to_string(), token.clone());
+                    let registry = Arc::clone(&self.registry);
+                    let context = (self.context)().with_cancellation(token.clone());
+                    let (output, running) = (Arc::clone(&output), Arc::clone(&running));
+                    calls.spawn(async move {
+                        let result = call_tool(&registry, params, &context).await;
+                        running.lock().unwrap().remove(&id.to_string());
+                        // A cancelled request gets no response
+                        if !token.is_cancelled() {
+                            let _ = write_message(&output, &response(id, result)).await;
+                        }
+                    });
+                }
+                (Some(id), Some(method)) => {
+                    let result = self.handle(method, &params);
+                    write_message(&output, &response(id, result)).await?;
+                }
+                (None, Some("notifications/cancelled")) => {
+                    if let Some(id) = params.get("requestId") {
+                        if let Some(token) = running.lock().unwrap().get(&id.to_string()) {
+                            token.cancel();
+                        }
+                    }
+                }
+                // Other notifications and responses need nothing from us
+                _ => {}
+            }
+        }
+
+        while calls.join_next().await.is_some() {}
+        Ok(())
+    }
+
+    /// Answer a request other than `tools/call`
+    fn handle(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => {
+                let requested = params.get("protocolVersion").and_then(Value::as_str);
+                let version = requested
+                    .filter(|version| SUPPORTED_VERSIONS.contains(version))
+                    .unwrap_or(PROTOCOL_VERSION);
+                let mut result = json!({
+                    "protocolVersion": version,
+                    "capabilities": {"tools": {"listChanged": false}},
+                    "serverInfo": {"name": "apchat", "version": env!("CARGO_PKG_VERSION")},
+                });
+                if let Some(ref instructions) = self.instructions {
+                    result["instructions"] = json!(instructions);
+                }
+                Ok(result)
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({"tools": self.tool_list()})),
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+
+    /// Every enabled tool with its input schema, sorted by name
+    fn tool_list(&self) -> Vec<Value> {
+        self.registry
+            .get_openai_tool_definitions()
+            .into_iter()
+            .map(|definition| {
+                let function = &definition["function"];
+                let name = function["name"].as_str().unwrap_or_default();
+                let side_effects = self.registry.side_effects(name);
+                json!({
+                    "name": name,
+                    "description": function["description"],
+                    "inputSchema": function["parameters"],
+                    "annotations": {
+                        "readOnlyHint": side_effects == SideEffects::ReadOnly,
+                        "openWorldHint": side_effects == SideEffects::External,
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+/// Run a `tools/call` request. Unknown tools and malformed requests are
+/// protocol errors; a tool that fails reports an error result.
+async fn call_tool(registry: &ToolRegistry, params: Value, context: &ToolContext) -> Result<Value, RpcError> {
+    let Some(name) = params.get("name").and_then(Value::as_str) else {
+        return Err((INVALID_PARAMS, "Missing tool name".to_string()));
+    };
+    if !registry.is_enabled(name) {
+        return Err((INVALID_PARAMS, format!("Unknown tool: {}", name)));
+    }
+    let arguments = match params.get("arguments") {
+        None | Some(Value::Null) => Default::default(),
+        Some(Value::Object(arguments)) => arguments.clone(),
+        Some(_) => return Err((INVALID_PARAMS, "Tool arguments must be an object".to_string())),
+    };
+
+    let params = ToolParameters {
+        data: arguments.into_iter().collect(),
+    };
+    Ok(call_result(registry.execute_tool(name, params, context).await))
+}
+
+/// The MCP form of a tool result; failures carry their error as the text
+fn call_result(result: ToolResult) -> Value {
+    let text = if result.success {
+        result.content
+    } else {
+        match result.error {
+            Some(error) if !result.content.is_empty() => format!("{}\n{}", error, result.content),
+            Some(error) => error,
+            None => result.content,
+        }
+    };
+    json!({
+        "content": [{"type": "text", "text": text}],
+        "isError": !result.success,
+    })
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => error_response(id, error),
+    }
+}
+
+fn error_response(id: Value, (code, message): RpcError) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(output: &Output<W>, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    let mut output = output.lock().await;
+    output.write_all(line.as_bytes()).await?;
+    output.flush().await?;
+    Ok(())
+}
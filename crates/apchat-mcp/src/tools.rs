@@ -0,0 +1,334 @@
+//! [`Tool`] implementations that proxy calls to an MCP server.
+
+use apchat_policy::ActionType;
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{ParameterDefinition, SideEffects, Tool, ToolParameters, ToolResult};
+use async_trait::async_trait;
+use colored::Colorize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::client::{McpClient, RemotePrompt, RemoteResource, RemoteTool};
+use crate::schema::parameters_from_schema;
+
+/// Resources listed in the description of a server's `read_resource` tool
+const MAX_LISTED_RESOURCES: usize = 50;
+
+/// Name a server's tool is registered under, e.g. `mcp__tracker__create_issue`
+pub fn namespaced_name(server: &str, tool: &str) -> String {
+    let clean = |name: &str| -> String {
+        name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+            .collect()
+    };
+    format!("mcp__{}__{}", clean(server), clean(tool))
+}
+
+/// Ask the policy whether a call to `server/operation` may go ahead; returns
+/// the result to report when it may not
+async fn check_call(context: &ToolContext, client: &McpClient, operation: &str) -> Option<ToolResult> {
+    let target = format!("{}/{}", client.name(), operation);
+    print!("{} {} ", "MCP call:".yellow(), target.cyan());
+    std::io::stdout().flush().ok();
+
+    match context.check_permission(ActionType::McpToolCall, &target, "Allow? (y/N):").await {
+        Ok((true, _)) => {
+            println!("{} {}", "Calling:".green(), target.cyan());
+            None
+        }
+        Ok((false, Some(reason))) => Some(ToolResult::error(format!("MCP call cancelled by user: {}", reason))),
+        Ok((false, None)) => Some(ToolResult::error("MCP call cancelled by user or policy".to_string())),
+        Err(e) => Some(ToolResult::error(format!("Permission check failed: {}", e))),
+    }
+}
+
+/// Text of an MCP content list; non-text content is described
+fn content_text(content: Option<&Value>) -> String {
+    let items = content.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    let field = |item: &Value, key: &str| item.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+    items
+        .iter()
+        .map(|item| match item.get("type").and_then(Value::as_str) {
+            Some("text") => field(item, "text"),
+            Some("resource") => match item.get("resource") {
+                Some(resource) if resource.get("text").is_some() => field(resource, "text"),
+                Some(resource) => format!("[resource {}]", field(resource, "uri")),
+                None => "[resource]".to_string(),
+            },
+            Some("resource_link") => format!("[resource {}]", field(item, "uri")),
+            Some(kind @ ("image" | "audio")) => format!("[{} {}]", kind, field(item, "mimeType")),
+            _ => item.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A tool of an MCP server
+pub struct McpTool {
+    client: Arc<McpClient>,
+    remote: RemoteTool,
+    name: String,
+    parameters: HashMap<String, ParameterDefinition>,
+}
+
+impl McpTool {
+    pub fn new(client: Arc<McpClient>, remote: RemoteTool) -> Self {
+        Self {
+            name: namespaced_name(client.name(), &remote.name),
+            parameters: parameters_from_schema(&remote.input_schema),
+            client,
+            remote,
+        }
+    }
+
+    /// Name of the tool on its server
+    pub fn remote_name(&self) -> &str {
+        &self.remote.name
+    }
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        self.remote.description.as_deref().unwrap_or_default()
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        self.parameters.clone()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        // Only decides whether calls may overlap; every call is still checked
+        if self.remote.annotations.read_only_hint {
+            SideEffects::ReadOnly
+        } else {
+            SideEffects::External
+        }
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(self.client.timeout())
+    }
+
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        if let Some(rejected) = check_call(context, &self.client, &self.remote.name).await {
+            return rejected;
+        }
+
+        let arguments = Value::Object(params.data.into_iter().collect());
+        let result = match self.client.call_tool(&self.remote.name, arguments, &context.cancellation).await {
+            Ok(result) => result,
+            Err(e) => return ToolResult::error(format!("{:#}", e)),
+        };
+
+        let mut text = content_text(result.get("content"));
+        if text.is_empty() {
+            if let Some(structured) = result.get("structuredContent") {
+                text = serde_json::to_string_pretty(structured).unwrap_or_default();
+            }
+        }
+        if result.get("isError").and_then(Value::as_bool) == Some(true) {
+            ToolResult::error(text)
+        } else {
+            ToolResult::success(text)
+        }
+    }
+}
+
+/// Reads the resources of an MCP server by URI
+pub struct McpReadResourceTool {
+    client: Arc<McpClient>,
+    name: String,
+    description: String,
+}
+
+impl McpReadResourceTool {
+    /// `resources` are the ones the server listed, shown to the model
+    pub fn new(client: Arc<McpClient>, resources: Vec<RemoteResource>) -> Self {
+        let mut description = format!("Read a resource of the MCP server '{}' by URI.", client.name());
+        if !resources.is_empty() {
+            description.push_str(" Known resources:");
+            for resource in resources.iter().take(MAX_LISTED_RESOURCES) {
+                description.push_str(&format!("\n- {} ({})", resource.uri, resource.name));
+                if let Some(ref about) = resource.description {
+                    description.push_str(&format!(": {}", about));
+                }
+            }
+        }
+        Self {
+            name: namespaced_name(client.name(), "read_resource"),
+            client,
+            description,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for McpReadResourceTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        HashMap::from([(
+            "uri".to_string(),
+            ParameterDefinition::new("string", "URI of the resource").required(),
+        )])
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(self.client.timeout())
+    }
+
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let uri: String = match params.get_required("uri") {
+            Ok(uri) => uri,
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
+        if let Some(rejected) = check_call(context, &self.client, "read_resource").await {
+            return rejected;
+        }
+
+        match self.client.read_resource(&uri, &context.cancellation).await {
+            Ok(result) => {
+                let contents = result.get("contents").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+                let text: Vec<String> = contents
+                    .iter()
+                    .map(|content| match content.get("text").and_then(Value::as_str) {
+                        Some(text) => text.to_string(),
+                        None => format!(
+                            "[binary content {}]",
+                            content.get("mimeType").and_then(Value::as_str).unwrap_or_default()
+                        ),
+                    })
+                    .collect();
+                ToolResult::success(text.join("\n"))
+            }
+            Err(e) => ToolResult::error(format!("{:#}", e)),
+        }
+    }
+}
+
+/// Fills in a prompt template of an MCP server
+pub struct McpGetPromptTool {
+    client: Arc<McpClient>,
+    name: String,
+    description: String,
+    prompt_names: Vec<Value>,
+}
+
+impl McpGetPromptTool {
+    pub fn new(client: Arc<McpClient>, prompts: Vec<RemotePrompt>) -> Self {
+        let mut description = format!(
+            "Get a prompt template of the MCP server '{}', filled in with the given arguments. Prompts:",
+            client.name()
+        );
+        for prompt in &prompts {
+            description.push_str(&format!("\n- {}", prompt.name));
+            if let Some(ref about) = prompt.description {
+                description.push_str(&format!(": {}", about));
+            }
+            let arguments: Vec<String> = prompt
+                .arguments
+                .iter()
+                .map(|argument| {
+                    let mut text = argument.name.clone();
+                    if argument.required {
+                        text.push_str(" (required)");
+                    }
+                    if let Some(ref about) = argument.description {
+                        text.push_str(&format!(" - {}", about));
+                    }
+                    text
+                })
+                .collect();
+            if !arguments.is_empty() {
+                description.push_str(&format!(" [arguments: {}]", arguments.join("; ")));
+            }
+        }
+        Self {
+            name: namespaced_name(client.name(), "get_prompt"),
+            prompt_names: prompts.into_iter().map(|prompt| Value::String(prompt.name)).collect(),
+            client,
+            description,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for McpGetPromptTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        let name = ParameterDefinition {
+            enum_values: Some(self.prompt_names.clone()),
+            ..ParameterDefinition::new("string", "Name of the prompt").required()
+        };
+        HashMap::from([
+            ("name".to_string(), name),
+            (
+                "arguments".to_string(),
+                ParameterDefinition::new("object", "Prompt arguments by name"),
+            ),
+        ])
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(self.client.timeout())
+    }
+
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let name: String = match params.get_required("name") {
+            Ok(name) => name,
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
+        let arguments = match params.get_optional_json::<Value>("arguments") {
+            Ok(arguments) => arguments.unwrap_or_else(|| Value::Object(Default::default())),
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
+        if let Some(rejected) = check_call(context, &self.client, "get_prompt").await {
+            return rejected;
+        }
+
+        match self.client.get_prompt(&name, arguments, &context.cancellation).await {
+            Ok(result) => {
+                let messages = result.get("messages").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+                let text: Vec<String> = messages
+                    .iter()
+                    .map(|message| {
+                        let role = message.get("role").and_then(Value::as_str).unwrap_or("user");
+                        let content = message.get("content").cloned().map(|content| vec![content]);
+                        format!("{}: {}", role, content_text(content.map(Value::Array).as_ref()))
+                    })
+                    .collect();
+                ToolResult::success(text.join("\n\n"))
+            }
+            Err(e) => ToolResult::error(format!("{:#}", e)),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+#!/usr/bin/env python3
+"""A tiny stdio MCP server for the client tests.
+
+Tools: echo, add, fail, slow (sleeps until cancelled). tools/list is paged
+to exercise cursors. Also offers one resource and one prompt.
+"""
+import json
+import sys
+import threading
+import time
+
+lock = threading.Lock()
+cancelled = set()
+
+TOOLS = [
+    {
+        "name": "echo",
+        "description": "Echo the text back",
+        "inputSchema": {
+            "type": "object",
+            "properties": {"text": {"type": "string", "description": "Text to echo"}},
+            "required": ["text"],
+        },
+        "annotations": {"readOnlyHint": True},
+    },
+    {
+        "name": "add",
+        "description": "Add two integers",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "a": {"type": "integer"},
+                "b": {"anyOf": [{"type": "integer"}, {"type": "null"}], "default": 0},
+            },
+            "required": ["a"],
+        },
+    },
+    {
+        "name": "fail",
+        "description": "Always reports an error",
+        "inputSchema": {"type": "object", "properties": {}},
+    },
+    {
+        "name": "slow",
+        "description": "Runs until cancelled",
+        "inputSchema": {"type": "object", "properties": {}},
+    },
+]
+
+
+def send(message):
+    with lock:
+        sys.stdout.write(json.dumps(message) + "\n")
+        sys.stdout.flush()
+
+
+def text(value):
+    return {"content": [{"type": "text", "text": value}]}
+
+
+def call_tool(request_id, name, arguments):
+    if name == "echo":
+        return text(arguments["text"])
+    if name == "add":
+        return text(str(arguments["a"] + (arguments.get("b") or 0)))
+    if name == "fail":
+        return {"content": [{"type": "text", "text": "something broke"}], "isError": True}
+    if name == "slow":
+        while request_id not in cancelled:
+            time.sleep(0.01)
+        return None
+    raise KeyError(name)
+
+
+def handle(message):
+    request_id = message.get("id")
+    method = message.get("method")
+    params = message.get("params") or {}
+
+    if method == "initialize":
+        result = {
+            "protocolVersion": params["protocolVersion"],
+            "capabilities": {"tools": {}, "resources": {}, "prompts": {}},
+            "serverInfo": {"name": "fixture", "version": "1.0"},
+            "instructions": "Fixture server for tests",
+        }
+    elif method == "tools/list":
+        if params.get("cursor") == "page2":
+            result = {"tools": TOOLS[2:]}
+        else:
+            result = {"tools": TOOLS[:2], "nextCursor": "page2"}
+    elif method == "tools/call":
+        try:
+            result = call_tool(request_id, params["name"], params.get("arguments") or {})
+        except KeyError as e:
+            send({"jsonrpc": "2.0", "id": request_id, "error": {"code": -32602, "message": "Unknown tool %s" % e}})
+            return
+        if result is None:
+            return
+    elif method == "resources/list":
+        result = {"resources": [{"uri": "fixture://readme", "name": "README", "description": "About the fixture"}]}
+    elif method == "resources/read":
+        result = {"contents": [{"uri": params["uri"], "mimeType": "text/plain", "text": "Fixture readme"}]}
+    elif method == "prompts/list":
+        result = {
+            "prompts": [
+                {
+                    "name": "review",
+                    "description": "Review a file",
+                    "arguments": [{"name": "file", "required": True}],
+                }
+            ]
+        }
+    elif method == "prompts/get":
+        file = (params.get("arguments") or {}).get("file", "?")
+        result = {"messages": [{"role": "user", "content": {"type": "text", "text": "Please review %s" % file}}]}
+    else:
+        send({"jsonrpc": "2.0", "id": request_id, "error": {"code": -32601, "message": "Unknown method"}})
+        return
+    send({"jsonrpc": "2.0", "id": request_id, "result": result})
+
+
+for line in sys.stdin:
+    message = json.loads(line)
+    if message.get("method") == "notifications/cancelled":
+        cancelled.add(message["params"]["requestId"])
+    elif "id" in message:
+        threading.Thread(target=handle, args=(message,), daemon=True).start()
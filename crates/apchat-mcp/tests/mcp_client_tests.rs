@@ -0,0 +1,167 @@
+use apchat_mcp::{connect_servers, namespaced_name, register_server, McpClient, McpConfig, McpServerConfig};
+use apchat_policy::{ActionType, Decision, PolicyManager, PolicyRule};
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{SideEffects, ToolParameters, ToolRegistry};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn fixture_server() -> McpServerConfig {
+    let script = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mcp_server.py");
+    McpServerConfig::new("python3")
+        .with_args([script.display().to_string()])
+        .with_timeout(Duration::from_secs(10))
+}
+
+async fn connect() -> Arc<McpClient> {
+    McpClient::connect("fixture", &fixture_server(), &std::env::temp_dir()).await.unwrap()
+}
+
+fn context(work_dir: &Path, policy_manager: PolicyManager) -> ToolContext {
+    ToolContext::new(work_dir.to_path_buf(), "test_session".to_string(), policy_manager)
+}
+
+fn params(json: &str) -> ToolParameters {
+    ToolParameters::from_json(json).unwrap()
+}
+
+#[tokio::test]
+async fn test_handshake_and_paged_tool_list() {
+    let client = connect().await;
+
+    assert_eq!(client.instructions(), Some("Fixture server for tests"));
+    assert!(client.supports("resources"));
+    assert!(!client.supports("logging"));
+
+    let names: Vec<String> = client.list_tools().await.unwrap().into_iter().map(|tool| tool.name).collect();
+    assert_eq!(names, vec!["echo", "add", "fail", "slow"]);
+}
+
+#[tokio::test]
+async fn test_registered_tools_proxy_calls() {
+    let mut registry = ToolRegistry::new();
+    let added = register_server(&mut registry, connect().await).await.unwrap();
+    assert_eq!(added, 6);
+    assert!(registry.has_tool("mcp__fixture__read_resource"));
+    assert!(registry.has_tool("mcp__fixture__get_prompt"));
+    assert_eq!(registry.side_effects("mcp__fixture__echo"), SideEffects::ReadOnly);
+    assert_eq!(registry.side_effects("mcp__fixture__add"), SideEffects::External);
+
+    let work_dir = TempDir::new().unwrap();
+    let context = context(work_dir.path(), PolicyManager::allow_all());
+
+    let echoed = registry.execute_tool("mcp__fixture__echo", params(r#"{"text": "hello"}"#), &context).await;
+    assert!(echoed.success);
+    assert_eq!(echoed.content, "hello");
+
+    // Arguments are validated against the converted input schema
+    let sum = registry.execute_tool("mcp__fixture__add", params(r#"{"a": "2", "b": 3}"#), &context).await;
+    assert_eq!(sum.content, "5");
+    let invalid = registry.execute_tool("mcp__fixture__add", params(r#"{"b": 3}"#), &context).await;
+    assert!(invalid.error.unwrap().contains("missing required integer argument"));
+
+    let failed = registry.execute_tool("mcp__fixture__fail", ToolParameters::new(), &context).await;
+    assert!(!failed.success);
+    assert_eq!(failed.error.as_deref(), Some("something broke"));
+
+    let resource = registry
+        .execute_tool("mcp__fixture__read_resource", params(r#"{"uri": "fixture://readme"}"#), &context)
+        .await;
+    assert_eq!(resource.content, "Fixture readme");
+
+    let prompt = registry
+        .execute_tool(
+            "mcp__fixture__get_prompt",
+            params(r#"{"name": "review", "arguments": {"file": "main.rs"}}"#),
+            &context,
+        )
+        .await;
+    assert_eq!(prompt.content, "user: Please review main.rs");
+}
+
+#[tokio::test]
+async fn test_calls_are_gated_by_policy() {
+    let mut registry = ToolRegistry::new();
+    register_server(&mut registry, connect().await).await.unwrap();
+
+    let policy_manager = PolicyManager::allow_all();
+    policy_manager.add_session_rule(PolicyRule::new(
+        ActionType::McpToolCall,
+        "fixture/echo".to_string(),
+        Decision::Deny,
+    ));
+    let work_dir = TempDir::new().unwrap();
+    let context = context(work_dir.path(), policy_manager);
+
+    let denied = registry.execute_tool("mcp__fixture__echo", params(r#"{"text": "hi"}"#), &context).await;
+    assert!(!denied.success);
+    assert!(denied.error.unwrap().contains("Denied by policy"));
+
+    let allowed = registry.execute_tool("mcp__fixture__add", params(r#"{"a": 1}"#), &context).await;
+    assert_eq!(allowed.content, "1");
+}
+
+#[tokio::test]
+async fn test_cancelled_call_is_withdrawn() {
+    let client = connect().await;
+    let mut registry = ToolRegistry::new();
+    register_server(&mut registry, Arc::clone(&client)).await.unwrap();
+    registry.set_timeout("mcp__fixture__slow", Some(Duration::from_millis(200)));
+
+    let work_dir = TempDir::new().unwrap();
+    let context = context(work_dir.path(), PolicyManager::allow_all());
+    let result = registry.execute_tool("mcp__fixture__slow", ToolParameters::new(), &context).await;
+    assert!(!result.success);
+
+    // The server is still usable afterwards
+    let echoed = registry.execute_tool("mcp__fixture__echo", params(r#"{"text": "still here"}"#), &context).await;
+    assert_eq!(echoed.content, "still here");
+}
+
+#[tokio::test]
+async fn test_connect_servers_reports_failures() {
+    let mut config = McpConfig::default();
+    config.servers.insert("fixture".to_string(), fixture_server());
+    config.servers.insert("missing".to_string(), McpServerConfig::new("/nonexistent/mcp-server"));
+    let mut disabled = fixture_server();
+    disabled.enabled = false;
+    config.servers.insert("off".to_string(), disabled);
+
+    let clients = connect_servers(&config, &std::env::temp_dir()).await;
+    assert_eq!(clients.len(), 2);
+    assert_eq!(clients[0].as_ref().unwrap().name(), "fixture");
+    assert!(format!("{:#}", clients[1].as_ref().unwrap_err()).contains("Failed to start MCP server 'missing'"));
+}
+
+#[test]
+fn test_layered_config_and_names() {
+    let dir = TempDir::new().unwrap();
+    let global = dir.path().join("global.toml");
+    let project = McpConfig::project_config_path(dir.path());
+    std::fs::write(
+        &global,
+        "[servers.tracker]\ncommand = \"tracker-mcp\"\n\n[servers.docs]\ncommand = \"docs-mcp\"\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(project.parent().unwrap()).unwrap();
+    std::fs::write(
+        &project,
+        "[servers.tracker]\ncommand = \"tracker-dev\"\n\n[servers.lint]\ncommand = \"lint-mcp\"\nargs = [\"--stdio\", \"a b\"]\n",
+    )
+    .unwrap();
+
+    // A project server cannot take over a global server's name
+    let config = McpConfig::layered(Some(&global), &project).unwrap();
+    assert_eq!(config.servers["tracker"].command, "tracker-mcp");
+    assert!(!config.servers["tracker"].project);
+    assert_eq!(config.servers["docs"].command, "docs-mcp");
+    assert!(config.servers["lint"].project);
+    assert_eq!(config.servers["lint"].command_line(), "lint-mcp --stdio 'a b'");
+
+    let missing: PathBuf = dir.path().join("nothing.toml");
+    assert!(McpConfig::load(&missing).unwrap().servers.is_empty());
+    assert!(McpConfig::parse("[servers.bad]\nargs = []\n").is_err());
+
+    assert_eq!(namespaced_name("my server", "create.issue"), "mcp__my_server__create_issue");
+}
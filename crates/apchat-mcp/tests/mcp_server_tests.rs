@@ -0,0 +1,227 @@
+use apchat_mcp::{McpServer, PROTOCOL_VERSION};
+use apchat_policy::{ActionType, Decision, PolicyManager, PolicyRule};
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{ParameterDefinition, SideEffects, Tool, ToolParameters, ToolRegistry, ToolResult};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines};
+
+struct EchoTool;
+
+#[async_trait]
+impl Tool for EchoTool {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn description(&self) -> &str {
+        "Echo the text back"
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        HashMap::from([("text".to_string(), ParameterDefinition::new("string", "Text to echo").required())])
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
+    async fn execute(&self, params: ToolParameters, _context: &ToolContext) -> ToolResult {
+        match params.get_required::<String>("text") {
+            Ok(text) => ToolResult::success(text),
+            Err(e) => ToolResult::error(e.to_string()),
+        }
+    }
+}
+
+/// Checks the policy before pretending to run a command
+struct RunTool;
+
+#[async_trait]
+impl Tool for RunTool {
+    fn name(&self) -> &str {
+        "run"
+    }
+
+    fn description(&self) -> &str {
+        "Run a command"
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        HashMap::from([("command".to_string(), ParameterDefinition::new("string", "Command").required())])
+    }
+
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let command: String = params.get_required("command").unwrap();
+        match context.check_permission(ActionType::CommandExecution, &command, "Run?").await {
+            Ok((true, _)) => ToolResult::success(format!("ran {}", command)),
+            Ok((false, reason)) => ToolResult::error(reason.unwrap_or_else(|| "Denied".to_string())),
+            Err(e) => ToolResult::error(e.to_string()),
+        }
+    }
+}
+
+struct SlowTool;
+
+#[async_trait]
+impl Tool for SlowTool {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    fn description(&self) -> &str {
+        "Runs until cancelled"
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        HashMap::new()
+    }
+
+    async fn execute(&self, _params: ToolParameters, context: &ToolContext) -> ToolResult {
+        context.cancellation.cancelled().await;
+        ToolResult::error("stopped".to_string())
+    }
+}
+
+/// One end of a server running on in-memory pipes
+struct Session {
+    input: DuplexStream,
+    output: Lines<BufReader<DuplexStream>>,
+}
+
+impl Session {
+    fn start(policy_manager: PolicyManager) -> Self {
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool);
+        registry.register(RunTool);
+        registry.register(SlowTool);
+        let server = McpServer::new(Arc::new(registry), move || {
+            ToolContext::new(PathBuf::from("."), "mcp_test".to_string(), policy_manager.clone())
+                .with_non_interactive(true)
+        })
+        .with_instructions("Test tools");
+
+        let (input, server_input) = tokio::io::duplex(64 * 1024);
+        let (server_output, output) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move { server.serve(server_input, server_output).await.unwrap() });
+        Self {
+            input,
+            output: BufReader::new(output).lines(),
+        }
+    }
+
+    async fn send(&mut self, message: Value) {
+        let line = format!("{}\n", message);
+        self.input.write_all(line.as_bytes()).await.unwrap();
+    }
+
+    async fn recv(&mut self) -> Value {
+        let line = tokio::time::timeout(Duration::from_secs(5), self.output.next_line())
+            .await
+            .expect("no response from server")
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    async fn request(&mut self, id: u64, method: &str, params: Value) -> Value {
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})).await;
+        let response = self.recv().await;
+        assert_eq!(response["id"], id);
+        response
+    }
+
+    async fn call(&mut self, id: u64, name: &str, arguments: Value) -> Value {
+        self.request(id, "tools/call", json!({"name": name, "arguments": arguments})).await["result"].clone()
+    }
+}
+
+#[tokio::test]
+async fn test_handshake_and_tool_list() {
+    let mut session = Session::start(PolicyManager::allow_all());
+
+    let initialized = session.request(1, "initialize", json!({"protocolVersion": "2024-11-05"})).await;
+    assert_eq!(initialized["result"]["protocolVersion"], "2024-11-05");
+    assert_eq!(initialized["result"]["serverInfo"]["name"], "apchat");
+    assert_eq!(initialized["result"]["instructions"], "Test tools");
+    assert!(initialized["result"]["capabilities"]["tools"].is_object());
+    session.send(json!({"jsonrpc": "2.0", "method": "notifications/initialized"})).await;
+
+    let unknown_version = session.request(2, "initialize", json!({"protocolVersion": "1999-01-01"})).await;
+    assert_eq!(unknown_version["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+    let tools = session.request(3, "tools/list", json!({})).await["result"]["tools"].clone();
+    let names: Vec<&str> = tools.as_array().unwrap().iter().map(|tool| tool["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["echo", "run", "slow"]);
+    assert_eq!(tools[0]["inputSchema"]["type"], "object");
+    assert_eq!(tools[0]["inputSchema"]["required"], json!(["text"]));
+    assert_eq!(tools[0]["annotations"]["readOnlyHint"], true);
+    assert_eq!(tools[1]["annotations"]["readOnlyHint"], false);
+
+    let unknown = session.request(4, "resources/list", json!({})).await;
+    assert_eq!(unknown["error"]["code"], -32601);
+    assert_eq!(session.request(5, "ping", json!({})).await["result"], json!({}));
+}
+
+#[tokio::test]
+async fn test_calls_and_errors() {
+    let mut session = Session::start(PolicyManager::allow_all());
+
+    let echoed = session.call(1, "echo", json!({"text": "hello"})).await;
+    assert_eq!(echoed["content"][0]["text"], "hello");
+    assert_eq!(echoed["isError"], false);
+
+    // Arguments are validated like in a chat session
+    let invalid = session.call(2, "echo", json!({})).await;
+    assert_eq!(invalid["isError"], true);
+    assert!(invalid["content"][0]["text"].as_str().unwrap().contains("text"));
+
+    let unknown = session.request(3, "tools/call", json!({"name": "missing"})).await;
+    assert_eq!(unknown["error"]["code"], -32602);
+    let malformed = session.request(4, "tools/call", json!({"name": "echo", "arguments": [1]})).await;
+    assert_eq!(malformed["error"]["code"], -32602);
+
+    session.input.write_all(b"not json\n").await.unwrap();
+    assert_eq!(session.recv().await["error"]["code"], -32700);
+}
+
+#[tokio::test]
+async fn test_calls_are_gated_by_policy() {
+    let policy_manager = PolicyManager::new();
+    policy_manager.add_session_rule(PolicyRule::new(ActionType::CommandExecution, "ls".to_string(), Decision::Allow));
+    policy_manager.add_session_rule(PolicyRule::new(ActionType::CommandExecution, "rm *".to_string(), Decision::Deny));
+    let mut session = Session::start(policy_manager);
+
+    let allowed = session.call(1, "run", json!({"command": "ls"})).await;
+    assert_eq!(allowed["content"][0]["text"], "ran ls");
+
+    let denied = session.call(2, "run", json!({"command": "rm -rf build"})).await;
+    assert_eq!(denied["isError"], true);
+    assert!(denied["content"][0]["text"].as_str().unwrap().contains("Denied by policy"));
+
+    // Nobody can be asked, so calls that need confirmation are refused
+    let unconfirmed = session.call(3, "run", json!({"command": "make"})).await;
+    assert_eq!(unconfirmed["isError"], true);
+}
+
+#[tokio::test]
+async fn test_cancelled_call_gets_no_response() {
+    let mut session = Session::start(PolicyManager::allow_all());
+
+    session
+        .send(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "slow"}}))
+        .await;
+    // Other requests are answered while the call runs
+    assert_eq!(session.call(2, "echo", json!({"text": "meanwhile"})).await["content"][0]["text"], "meanwhile");
+
+    session
+        .send(json!({"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 1}}))
+        .await;
+    let next = session.request(3, "ping", json!({})).await;
+    assert_eq!(next["result"], json!({}));
+    assert!(tokio::time::timeout(Duration::from_millis(300), session.recv()).await.is_err());
+}
@@ -1,12 +1,30 @@
 // Models module - data structures for API communication
+pub mod prompt_tools;
 pub mod requests;
 pub mod responses;
+pub mod tool_args;
+pub mod tool_parsing;
 pub mod types;
 
+// Lets code generated by the ToolArgs derive refer to this crate by name,
+// including from inside it
+extern crate self as apchat_models;
+
 #[cfg(test)]
 mod tests;
 
 // Re-export commonly used types
 pub use requests::{ChatRequest, FunctionDef, Tool};
 pub use responses::{ChatResponse, StreamChunk, Usage};
-pub use types::{FunctionCall, Message, ModelColor, ModelProvider, BackendType, SwitchModelArgs, ToolCall, ModelConfig};
+pub use types::{FunctionCall, Message, ModelColor, ModelProvider, BackendType, SwitchModelArgs, ToolCall, ToolCallDialect, ModelConfig};
+pub use tool_args::{ParameterDefinition, ToolArgs, ToolParameters};
+pub use tool_parsing::{parse_tool_calls, TextToolCalls, ToolCallParser, ToolCallParsers};
+pub use prompt_tools::PromptTools;
+pub use apchat_macros::ToolArgs;
+
+#[doc(hidden)]
+pub mod __private {
+    // Used by code generated by the ToolArgs derive
+    pub use anyhow;
+    pub use serde_json;
+}
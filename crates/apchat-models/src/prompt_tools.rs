@@ -0,0 +1,240 @@
+//! Tool calling for models without native function calling: the tool schemas
+//! are written into the system prompt, past calls and their results become
+//! plain text turns, and calls are parsed back out of the completion text.
+//! Each [`ToolCallDialect`] has its own template, matching what models trained
+//! on that format expect.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::requests::Tool;
+use crate::tool_parsing::parse_tool_calls;
+use crate::types::{Message, ToolCall, ToolCallDialect};
+
+/// Renders tools and tool turns as text in one dialect's format
+#[derive(Debug, Clone, Copy)]
+pub struct PromptTools {
+    dialect: ToolCallDialect,
+    /// The slot's own setting, which replies are parsed with
+    parser: ToolCallDialect,
+}
+
+impl PromptTools {
+    /// Use the template of `dialect`; `auto` and `none` use the Hermes
+    /// template, which most instruct models follow
+    pub fn new(dialect: ToolCallDialect) -> Self {
+        let parser = dialect;
+        let dialect = match dialect {
+            ToolCallDialect::Auto | ToolCallDialect::None => ToolCallDialect::Hermes,
+            dialect => dialect,
+        };
+        Self { dialect, parser }
+    }
+
+    pub fn dialect(&self) -> ToolCallDialect {
+        self.dialect
+    }
+
+    /// Instructions describing the tools and how to call them
+    pub fn system_prompt(&self, tools: &[Tool]) -> String {
+        let schemas: Vec<String> = tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.function.name,
+                    "description": tool.function.description,
+                    "parameters": tool.function.parameters,
+                })
+                .to_string()
+            })
+            .collect();
+
+        match self.dialect {
+            ToolCallDialect::Glm => format!(
+                "# Tools\n\nYou can call the following functions:\n\n{}\n\n\
+                 To call a function, reply with one block per call, one argument per key/value pair:\n\
+                 <tool_call>function-name\n<arg_key>argument-name</arg_key>\n<arg_value>argument-value</arg_value>\n</tool_call>",
+                schemas.join("\n")
+            ),
+            ToolCallDialect::Llama3 => format!(
+                "You have access to the following functions:\n\n{}\n\n\
+                 To call a function, reply with <|python_tag|> followed by a JSON object of the form \
+                 {{\"name\": function name, \"parameters\": object of argument names and values}}. \
+                 Separate several calls with \"; \". Do not add any other text to a function call.",
+                schemas.join("\n")
+            ),
+            ToolCallDialect::Mistral => format!(
+                "[AVAILABLE_TOOLS][{}][/AVAILABLE_TOOLS]\n\n\
+                 To call tools, reply with [TOOL_CALLS] followed by a JSON array of calls: \
+                 [TOOL_CALLS][{{\"name\": \"function-name\", \"arguments\": {{...}}}}]",
+                schemas.join(", ")
+            ),
+            ToolCallDialect::Json => format!(
+                "You can call the following functions:\n\n{}\n\n\
+                 To call a function, reply with a fenced block per call:\n\
+                 ```json\n{{\"name\": \"function-name\", \"arguments\": {{...}}}}\n```",
+                schemas.join("\n")
+            ),
+            _ => format!(
+                "# Tools\n\nYou may call one or more functions to assist with the user query.\n\n\
+                 You are provided with function signatures within <tools></tools> XML tags:\n<tools>\n{}\n</tools>\n\n\
+                 For each function call, return a json object with function name and arguments within \
+                 <tool_call></tool_call> XML tags:\n<tool_call>\n{{\"name\": <function-name>, \"arguments\": <args-json-object>}}\n</tool_call>",
+                schemas.join("\n")
+            ),
+        }
+    }
+
+    /// The text an assistant turn with these calls is written as
+    pub fn render_calls(&self, calls: &[ToolCall]) -> String {
+        let parsed: Vec<(&str, Value)> = calls
+            .iter()
+            .map(|call| {
+                let arguments = serde_json::from_str(&call.function.arguments).unwrap_or_else(|_| json!({}));
+                (call.function.name.as_str(), arguments)
+            })
+            .collect();
+
+        match self.dialect {
+            ToolCallDialect::Glm => parsed
+                .iter()
+                .map(|(name, arguments)| {
+                    let mut block = format!("<tool_call>{}\n", name);
+                    for (key, value) in arguments.as_object().into_iter().flatten() {
+                        let value = match value {
+                            Value::String(text) => text.clone(),
+                            other => other.to_string(),
+                        };
+                        block.push_str(&format!("<arg_key>{}</arg_key>\n<arg_value>{}</arg_value>\n", key, value));
+                    }
+                    block.push_str("</tool_call>");
+                    block
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ToolCallDialect::Llama3 => {
+                let calls: Vec<String> = parsed
+                    .iter()
+                    .map(|(name, arguments)| json!({"name": name, "parameters": arguments}).to_string())
+                    .collect();
+                format!("<|python_tag|>{}", calls.join("; "))
+            }
+            ToolCallDialect::Mistral => {
+                let calls: Vec<Value> = parsed
+                    .iter()
+                    .map(|(name, arguments)| json!({"name": name, "arguments": arguments}))
+                    .collect();
+                format!("[TOOL_CALLS]{}", Value::Array(calls))
+            }
+            ToolCallDialect::Json => parsed
+                .iter()
+                .map(|(name, arguments)| format!("```json\n{}\n```", json!({"name": name, "arguments": arguments})))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => parsed
+                .iter()
+                .map(|(name, arguments)| {
+                    format!("<tool_call>\n{}\n</tool_call>", json!({"name": name, "arguments": arguments}))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    /// The text a tool result is sent back as
+    pub fn render_result(&self, name: &str, content: &str) -> String {
+        match self.dialect {
+            ToolCallDialect::Mistral => format!("[TOOL_RESULTS]{}[/TOOL_RESULTS]", json!({"name": name, "content": content})),
+            ToolCallDialect::Llama3 => format!("Output of {}:\n{}", name, content),
+            ToolCallDialect::Json => format!("Result of {}:\n```\n{}\n```", name, content),
+            _ => format!("<tool_response>\n{}\n</tool_response>", content),
+        }
+    }
+
+    /// Rewrite a conversation for a model without native tools: the tool
+    /// prompt joins the system message, assistant calls become text and the
+    /// results of one round of calls become a single user turn
+    pub fn prepare_messages(&self, messages: &[Message], tools: &[Tool]) -> Vec<Message> {
+        let mut prepared: Vec<Message> = Vec::with_capacity(messages.len() + 1);
+        let mut call_names: HashMap<&str, &str> = HashMap::new();
+        let mut in_results = false;
+
+        for message in messages {
+            match message.role.as_str() {
+                "assistant" => {
+                    let mut content = message.content.clone();
+                    if let Some(ref calls) = message.tool_calls {
+                        for call in calls {
+                            call_names.insert(&call.id, &call.function.name);
+                        }
+                        if !calls.is_empty() {
+                            if !content.is_empty() {
+                                content.push('\n');
+                            }
+                            content.push_str(&self.render_calls(calls));
+                        }
+                    }
+                    prepared.push(text_message("assistant", content));
+                    in_results = false;
+                }
+                "tool" => {
+                    let name = message
+                        .name
+                        .as_deref()
+                        .or_else(|| message.tool_call_id.as_deref().and_then(|id| call_names.get(id).copied()))
+                        .unwrap_or("tool");
+                    let result = self.render_result(name, &message.content);
+                    match prepared.last_mut() {
+                        Some(last) if in_results => {
+                            last.content.push('\n');
+                            last.content.push_str(&result);
+                        }
+                        _ => prepared.push(text_message("user", result)),
+                    }
+                    in_results = true;
+                }
+                _ => {
+                    prepared.push(text_message(&message.role, message.content.clone()));
+                    in_results = false;
+                }
+            }
+        }
+
+        if !tools.is_empty() {
+            let prompt = self.system_prompt(tools);
+            match prepared.first_mut() {
+                Some(system) if system.role == "system" => {
+                    system.content.push_str("\n\n");
+                    system.content.push_str(&prompt);
+                }
+                _ => prepared.insert(0, text_message("system", prompt)),
+            }
+        }
+        prepared
+    }
+
+    /// Take the calls out of a completion's text, as
+    /// [`tool_calls`](Message::tool_calls) with the markup removed, in the
+    /// slot's dialect as for any other reply: `auto` detects it and `none`
+    /// parses nothing.
+    pub fn parse_response(&self, message: &mut Message) {
+        if message.tool_calls.is_some() {
+            return;
+        }
+        if let Some(parsed) = parse_tool_calls(&message.content, self.parser) {
+            message.tool_calls = Some(parsed.calls);
+            message.content = parsed.text;
+        }
+    }
+}
+
+fn text_message(role: &str, content: String) -> Message {
+    Message {
+        role: role.to_string(),
+        content,
+        tool_calls: None,
+        tool_call_id: None,
+        name: None,
+        reasoning: None,
+    }
+}
@@ -1,2 +1,4 @@
 pub mod model_resolution_tests;
 pub mod model_provider_tests;
+pub mod tool_args_tests;
+pub mod prompt_tools_tests;
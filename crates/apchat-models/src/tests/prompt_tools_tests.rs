@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use crate::{FunctionCall, FunctionDef, Message, PromptTools, Tool, ToolCall, ToolCallDialect};
+    use serde_json::json;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+            reasoning: None,
+        }
+    }
+
+    fn read_file_tool() -> Tool {
+        Tool {
+            tool_type: "function".to_string(),
+            function: FunctionDef {
+                name: "read_file".to_string(),
+                description: "Read a file".to_string(),
+                parameters: json!({"type": "object", "properties": {"path": {"type": "string"}}}),
+            },
+        }
+    }
+
+    fn conversation() -> Vec<Message> {
+        let mut assistant = message("assistant", "Reading both.");
+        assistant.tool_calls = Some(
+            ["a.rs", "b.rs"]
+                .iter()
+                .enumerate()
+                .map(|(i, path)| ToolCall {
+                    id: format!("call_{}", i),
+                    tool_type: "function".to_string(),
+                    function: FunctionCall {
+                        name: "read_file".to_string(),
+                        arguments: json!({"path": path}).to_string(),
+                    },
+                })
+                .collect(),
+        );
+        let mut first = message("tool", "fn a() {}");
+        first.tool_call_id = Some("call_0".to_string());
+        first.name = Some("read_file".to_string());
+        let mut second = message("tool", "fn b() {}");
+        second.tool_call_id = Some("call_1".to_string());
+
+        vec![message("system", "You are helpful."), message("user", "Show me a.rs and b.rs"), assistant, first, second]
+    }
+
+    #[test]
+    fn test_prepare_messages_rewrites_tool_turns() {
+        let prompt_tools = PromptTools::new(ToolCallDialect::Auto);
+        assert_eq!(prompt_tools.dialect(), ToolCallDialect::Hermes);
+
+        let prepared = prompt_tools.prepare_messages(&conversation(), &[read_file_tool()]);
+        let roles: Vec<&str> = prepared.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "user"]);
+        assert!(prepared.iter().all(|m| m.tool_calls.is_none() && m.tool_call_id.is_none()));
+
+        assert!(prepared[0].content.starts_with("You are helpful.\n\n"));
+        assert!(prepared[0].content.contains("<tools>\n{\"description\":\"Read a file\""));
+        assert!(prepared[2].content.starts_with("Reading both.\n<tool_call>\n"));
+        assert_eq!(prepared[3].content, "<tool_response>\nfn a() {}\n</tool_response>\n<tool_response>\nfn b() {}\n</tool_response>");
+
+        // Without a system message one is added
+        let prepared = prompt_tools.prepare_messages(&[message("user", "hi")], &[read_file_tool()]);
+        assert_eq!(prepared[0].role, "system");
+        assert_eq!(prepared.len(), 2);
+    }
+
+    #[test]
+    fn test_rendered_calls_parse_back() {
+        let calls = conversation()[2].tool_calls.clone().unwrap();
+        for dialect in [ToolCallDialect::Glm, ToolCallDialect::Hermes, ToolCallDialect::Llama3, ToolCallDialect::Mistral, ToolCallDialect::Json] {
+            let prompt_tools = PromptTools::new(dialect);
+            let mut reply = message("assistant", &prompt_tools.render_calls(&calls));
+            prompt_tools.parse_response(&mut reply);
+
+            let parsed = reply.tool_calls.unwrap_or_else(|| panic!("{} calls were not parsed", dialect.as_str()));
+            assert_eq!(parsed.len(), 2, "{}", dialect.as_str());
+            assert_eq!(parsed[1].function.name, "read_file");
+            let arguments: serde_json::Value = serde_json::from_str(&parsed[1].function.arguments).unwrap();
+            assert_eq!(arguments, json!({"path": "b.rs"}), "{}", dialect.as_str());
+            assert_eq!(reply.content, "", "{}", dialect.as_str());
+        }
+    }
+
+    #[test]
+    fn test_result_formats() {
+        let mistral = PromptTools::new(ToolCallDialect::Mistral);
+        let prepared = mistral.prepare_messages(&conversation(), &[]);
+        // No tools, no tool prompt
+        assert_eq!(prepared[0].content, "You are helpful.");
+        // Names of results without one come from the call they answer
+        assert!(prepared[3].content.ends_with("[TOOL_RESULTS]{\"content\":\"fn b() {}\",\"name\":\"read_file\"}[/TOOL_RESULTS]"));
+
+        let mut plain = message("assistant", "All done.");
+        mistral.parse_response(&mut plain);
+        assert!(plain.tool_calls.is_none());
+        assert_eq!(plain.content, "All done.");
+    }
+
+    #[test]
+    fn test_replies_are_parsed_in_the_slot_dialect() {
+        let calls = conversation()[2].tool_calls.clone().unwrap();
+        let hermes = PromptTools::new(ToolCallDialect::Hermes).render_calls(&calls);
+        let fenced = PromptTools::new(ToolCallDialect::Json).render_calls(&calls);
+
+        // A pinned dialect only parses its own markup
+        let mut reply = message("assistant", &hermes);
+        PromptTools::new(ToolCallDialect::Glm).parse_response(&mut reply);
+        assert!(reply.tool_calls.is_none());
+
+        // `auto` detects dialects with their own markup but not fenced JSON
+        let auto = PromptTools::new(ToolCallDialect::Auto);
+        let mut reply = message("assistant", &hermes);
+        auto.parse_response(&mut reply);
+        assert_eq!(reply.tool_calls.map(|calls| calls.len()), Some(2));
+        let mut reply = message("assistant", &fenced);
+        auto.parse_response(&mut reply);
+        assert!(reply.tool_calls.is_none());
+
+        // `none` renders the Hermes template but parses nothing
+        let none = PromptTools::new(ToolCallDialect::None);
+        assert_eq!(none.dialect(), ToolCallDialect::Hermes);
+        let mut reply = message("assistant", &hermes);
+        none.parse_response(&mut reply);
+        assert!(reply.tool_calls.is_none());
+        assert_eq!(reply.content, hermes);
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use crate::types::{OpenFileArgs, PtyLaunchArgs, SearchFilesArgs, TodoWriteArgs};
+    use crate::{ToolArgs, ToolParameters};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, ToolArgs)]
+    struct Step {
+        /// What to do
+        title: String,
+        /// Current state of the step
+        #[tool(type = "string", enum("pending", "done"))]
+        status: String,
+    }
+
+    #[derive(Debug, Deserialize, ToolArgs)]
+    struct PlanArgs {
+        /// Steps of the plan
+        #[tool(min_items = 1, max_items = 5)]
+        steps: Vec<Step>,
+        #[tool(description = "How hard the plan is", min = 1, max = 10)]
+        #[serde(default)]
+        effort: i32,
+        /// Short identifier of the plan
+        #[serde(rename = "id")]
+        #[tool(pattern = "^[a-z-]+$")]
+        plan_id: Option<String>,
+        #[serde(skip)]
+        cached: Option<u32>,
+    }
+
+    fn params(value: serde_json::Value) -> ToolParameters {
+        ToolParameters::from_json(&value.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_derived_parameters_follow_fields() {
+        let parameters = SearchFilesArgs::parameters();
+        assert_eq!(parameters.len(), 5);
+        assert!(parameters["query"].required);
+        assert_eq!(parameters["query"].description, "Text or pattern to search for");
+        assert!(!parameters["pattern"].required);
+        assert_eq!(parameters["pattern"].default, Some(json!("**/*.rs")));
+        assert_eq!(parameters["regex"].param_type, "boolean");
+        assert_eq!(parameters["max_results"].param_type, "integer");
+        assert_eq!(parameters["max_results"].minimum, Some(0.0));
+        assert_eq!(parameters["max_results"].default, Some(json!(50)));
+
+        let schema = OpenFileArgs::parameters()["start_line"].to_json_schema();
+        assert_eq!(
+            schema,
+            json!({"description": "Starting line number (1-based)", "minimum": 0, "type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_derived_parameters_with_constraints() {
+        let parameters = PlanArgs::parameters();
+        assert_eq!(parameters.len(), 3, "skipped fields are not parameters");
+
+        let steps = parameters["steps"].to_json_schema();
+        assert_eq!(
+            steps,
+            json!({
+                "description": "Steps of the plan",
+                "items": {
+                    "properties": {
+                        "status": {"description": "Current state of the step", "enum": ["pending", "done"], "type": "string"},
+                        "title": {"description": "What to do", "type": "string"}
+                    },
+                    "required": ["status", "title"],
+                    "type": "object"
+                },
+                "maxItems": 5,
+                "minItems": 1,
+                "type": "array"
+            })
+        );
+        assert_eq!(parameters["effort"].description, "How hard the plan is");
+        assert_eq!((parameters["effort"].minimum, parameters["effort"].maximum), (Some(1.0), Some(10.0)));
+        assert!(!parameters["effort"].required);
+        assert_eq!(parameters["id"].pattern.as_deref(), Some("^[a-z-]+$"));
+        assert!(!parameters["id"].required);
+    }
+
+    #[test]
+    fn test_derived_parse() {
+        let args = SearchFilesArgs::parse(&params(json!({"query": "fn main"}))).unwrap();
+        assert_eq!(args.query, "fn main");
+        assert_eq!(args.pattern, "**/*.rs");
+        assert!(!args.regex);
+        assert_eq!(args.max_results, 50);
+
+        let error = SearchFilesArgs::parse(&params(json!({"pattern": "*.md"}))).unwrap_err();
+        assert_eq!(error.to_string(), "Required parameter 'query' missing");
+
+        // Nested arrays may arrive encoded as a JSON string
+        let encoded = json!([{"title": "write", "status": "done"}]).to_string();
+        let args = PlanArgs::parse(&params(json!({"steps": encoded, "id": "plan-a"}))).unwrap();
+        assert_eq!(args.steps.len(), 1);
+        assert_eq!((args.steps[0].title.as_str(), args.steps[0].status.as_str()), ("write", "done"));
+        assert_eq!(args.effort, 0);
+        assert_eq!(args.plan_id.as_deref(), Some("plan-a"));
+        assert_eq!(args.cached, None);
+    }
+
+    #[test]
+    fn test_tool_argument_structs_keep_wire_names() {
+        let todos = TodoWriteArgs::parameters()["todos"].to_json_schema();
+        assert_eq!(todos["items"]["required"], json!(["activeForm", "content", "status"]));
+        assert_eq!(todos["items"]["properties"]["status"]["enum"], json!(["pending", "in_progress", "completed"]));
+
+        let todo = json!({"todos": [{"content": "Run tests", "status": "pending", "activeForm": "Running tests"}]});
+        let args = TodoWriteArgs::parse(&params(todo)).unwrap();
+        assert_eq!(args.todos[0].active_form, "Running tests");
+
+        let args = PtyLaunchArgs::parse(&params(json!({"command": "htop"}))).unwrap();
+        assert_eq!((args.cols, args.rows), (80, 24));
+        assert_eq!(PtyLaunchArgs::parameters()["cols"].default, Some(json!(80)));
+    }
+}
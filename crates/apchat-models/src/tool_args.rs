@@ -0,0 +1,260 @@
+//! Tool arguments: the parameters a tool declares and the values it is
+//! called with.
+//!
+//! Tools describe their arguments with a struct deriving [`ToolArgs`], which
+//! generates the [`ParameterDefinition`]s sent to the model and a typed
+//! parser for the [`ToolParameters`] of a call from the same fields.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Arguments of a tool, usually implemented with `#[derive(ToolArgs)]`
+pub trait ToolArgs: Sized {
+    /// Parameter definitions advertised to the model
+    fn parameters() -> HashMap<String, ParameterDefinition>;
+
+    /// Read the arguments of a call
+    fn parse(params: &ToolParameters) -> Result<Self>;
+}
+
+/// Tool parameters
+#[derive(Debug, Clone, Default)]
+pub struct ToolParameters {
+    pub data: HashMap<String, Value>,
+}
+
+impl ToolParameters {
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<Self> {
+        let data: HashMap<String, Value> = serde_json::from_str(json_str)?;
+        Ok(Self { data })
+    }
+
+    pub fn set<T: Serialize>(&mut self, key: &str, value: T) {
+        if let Ok(json_value) = serde_json::to_value(value) {
+            self.data.insert(key.to_string(), json_value);
+        }
+    }
+
+    pub fn get_required<T>(&self, key: &str) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let value = self.data.get(key)
+            .ok_or_else(|| anyhow::anyhow!("Required parameter '{}' missing", key))?;
+
+        serde_json::from_value(value.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to parse parameter '{}': {}", key, e))
+    }
+
+    /// Like [`get_required`](Self::get_required) for array and object
+    /// parameters, but also accepts the value encoded as a JSON string, as
+    /// older models and text-based tool calls send them
+    pub fn get_required_json<T>(&self, key: &str) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let value = self.data.get(key)
+            .ok_or_else(|| anyhow::anyhow!("Required parameter '{}' missing", key))?;
+
+        let value = match value {
+            Value::String(encoded) => serde_json::from_str(encoded)
+                .map_err(|e| anyhow::anyhow!("Failed to parse parameter '{}' as JSON: {}", key, e))?,
+            other => other.clone(),
+        };
+        serde_json::from_value(value)
+            .map_err(|e| anyhow::anyhow!("Failed to parse parameter '{}': {}", key, e))
+    }
+
+    pub fn get_optional<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        match self.data.get(key) {
+            Some(value) => {
+                let parsed: T = serde_json::from_value(value.clone())
+                    .map_err(|e| anyhow::anyhow!("Failed to parse parameter '{}': {}", key, e))?;
+                Ok(Some(parsed))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Optional counterpart of [`get_required_json`](Self::get_required_json)
+    pub fn get_optional_json<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        if self.data.contains_key(key) {
+            self.get_required_json(key).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Tool parameter definition
+///
+/// A JSON Schema fragment for one parameter. Besides its type it can list
+/// allowed values, bound numbers and arrays, constrain strings with a
+/// pattern, and describe array elements and the fields of nested objects.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParameterDefinition {
+    pub param_type: String,
+    pub description: String,
+    pub required: bool,
+    pub default: Option<Value>,
+    /// Allowed values (`enum`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<Value>>,
+    /// Schema of the elements of an array parameter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<ParameterDefinition>>,
+    /// Fields of an object parameter; their `required` flags mark required sub-fields
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, ParameterDefinition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+    /// Regular expression string values must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+impl ParameterDefinition {
+    pub fn new(param_type: &str, description: &str) -> Self {
+        Self {
+            param_type: param_type.to_string(),
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// An array whose elements follow `items`
+    pub fn array(description: &str, items: ParameterDefinition) -> Self {
+        Self {
+            items: Some(Box::new(items)),
+            ..Self::new("array", description)
+        }
+    }
+
+    /// An object with the given fields
+    pub fn object(description: &str, properties: impl IntoIterator<Item = (String, ParameterDefinition)>) -> Self {
+        Self {
+            properties: properties.into_iter().collect(),
+            ..Self::new("object", description)
+        }
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn with_default(mut self, default: impl Into<Value>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn with_enum<V: Into<Value>>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+        self.enum_values = Some(values.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_range(mut self, minimum: Option<f64>, maximum: Option<f64>) -> Self {
+        self.minimum = minimum;
+        self.maximum = maximum;
+        self
+    }
+
+    pub fn with_item_count(mut self, min_items: Option<usize>, max_items: Option<usize>) -> Self {
+        self.min_items = min_items;
+        self.max_items = max_items;
+        self
+    }
+
+    pub fn with_pattern(mut self, pattern: &str) -> Self {
+        self.pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// JSON Schema for this parameter, with keys in sorted order so the
+    /// definitions sent to the model are stable for prompt caching
+    pub fn to_json_schema(&self) -> Value {
+        let mut schema = std::collections::BTreeMap::new();
+
+        if let Some(ref default) = self.default {
+            schema.insert("default", default.clone());
+        }
+        if !self.description.is_empty() {
+            schema.insert("description", Value::String(self.description.clone()));
+        }
+        if let Some(ref values) = self.enum_values {
+            schema.insert("enum", Value::Array(values.clone()));
+        }
+        if let Some(ref items) = self.items {
+            schema.insert("items", items.to_json_schema());
+        }
+        if let Some(maximum) = self.maximum {
+            schema.insert("maximum", self.number(maximum));
+        }
+        if let Some(max_items) = self.max_items {
+            schema.insert("maxItems", Value::from(max_items));
+        }
+        if let Some(minimum) = self.minimum {
+            schema.insert("minimum", self.number(minimum));
+        }
+        if let Some(min_items) = self.min_items {
+            schema.insert("minItems", Value::from(min_items));
+        }
+        if let Some(ref pattern) = self.pattern {
+            schema.insert("pattern", Value::String(pattern.clone()));
+        }
+        if !self.properties.is_empty() {
+            let (properties, required) = schema_properties(&self.properties);
+            schema.insert("properties", properties);
+            schema.insert("required", required);
+        }
+        schema.insert("type", Value::String(self.param_type.clone()));
+
+        Value::Object(schema.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    /// Integer bounds are written as integers
+    fn number(&self, value: f64) -> Value {
+        if self.param_type == "integer" && value.fract() == 0.0 {
+            Value::from(value as i64)
+        } else {
+            Value::from(value)
+        }
+    }
+}
+
+/// JSON Schema `properties` and sorted `required` list for a set of parameters
+pub fn schema_properties(parameters: &HashMap<String, ParameterDefinition>) -> (Value, Value) {
+    let mut names: Vec<&String> = parameters.keys().collect();
+    names.sort();
+
+    let properties = names
+        .iter()
+        .map(|name| (name.to_string(), parameters[*name].to_json_schema()))
+        .collect();
+    let required = names
+        .iter()
+        .filter(|name| parameters[**name].required)
+        .map(|name| Value::String(name.to_string()))
+        .collect();
+    (Value::Object(properties), Value::Array(required))
+}
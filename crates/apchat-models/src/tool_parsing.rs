@@ -0,0 +1,422 @@
+//! Tool calls that models write into their message text instead of returning
+//! them as structured calls. Each dialect has a [`ToolCallParser`]; a
+//! [`ToolCallParsers`] set tries the ones that fit a model slot's
+//! [`ToolCallDialect`] and strips the markup from the visible text.
+
+use crate::types::{FunctionCall, ToolCall, ToolCallDialect};
+use serde_json::{Map, Value};
+use std::ops::Range;
+
+/// Tool calls found in an assistant message
+#[derive(Debug, Clone)]
+pub struct TextToolCalls {
+    /// Dialect the calls were written in
+    pub dialect: ToolCallDialect,
+    pub calls: Vec<ToolCall>,
+    /// The message text with the tool call markup removed
+    pub text: String,
+}
+
+/// Recognizes one dialect of tool calls in message text
+pub trait ToolCallParser: Send + Sync {
+    fn dialect(&self) -> ToolCallDialect;
+
+    /// Whether the text contains this dialect's markup
+    fn detect(&self, content: &str) -> bool;
+
+    /// The calls in the text and the text without their markup, or `None`
+    /// when no call could be parsed
+    fn parse(&self, content: &str) -> Option<(Vec<FunctionCall>, String)>;
+}
+
+/// The parsers tried on a message, in order
+pub struct ToolCallParsers {
+    parsers: Vec<Box<dyn ToolCallParser>>,
+}
+
+impl ToolCallParsers {
+    /// Every built-in dialect, most distinctive markup first
+    pub fn builtin() -> Self {
+        Self::detected().with_parser(JsonBlockParser)
+    }
+
+    /// The dialects `auto` detects: those with dedicated markup. Fenced JSON
+    /// is left out, since a model may show JSON in an answer without calling
+    /// anything; a slot opts in with `json`.
+    pub fn detected() -> Self {
+        Self {
+            parsers: vec![
+                Box::new(MistralParser),
+                Box::new(Llama3Parser),
+                Box::new(HermesParser),
+                Box::new(GlmParser),
+            ],
+        }
+    }
+
+    /// The parsers for a model slot's dialect setting
+    pub fn for_dialect(dialect: ToolCallDialect) -> Self {
+        let parsers: Vec<Box<dyn ToolCallParser>> = match dialect {
+            ToolCallDialect::Auto => return Self::detected(),
+            ToolCallDialect::None => Vec::new(),
+            ToolCallDialect::Glm => vec![Box::new(GlmParser)],
+            ToolCallDialect::Hermes => vec![Box::new(HermesParser)],
+            ToolCallDialect::Llama3 => vec![Box::new(Llama3Parser)],
+            ToolCallDialect::Mistral => vec![Box::new(MistralParser)],
+            ToolCallDialect::Json => vec![Box::new(JsonBlockParser)],
+        };
+        Self { parsers }
+    }
+
+    /// Also try `parser`, after the existing ones
+    pub fn with_parser(mut self, parser: impl ToolCallParser + 'static) -> Self {
+        self.parsers.push(Box::new(parser));
+        self
+    }
+
+    /// Parse with the first parser that detects its dialect and finds calls
+    pub fn parse(&self, content: &str) -> Option<TextToolCalls> {
+        self.parsers
+            .iter()
+            .filter(|parser| parser.detect(content))
+            .find_map(|parser| {
+                let (calls, text) = parser.parse(content).filter(|(calls, _)| !calls.is_empty())?;
+                Some(TextToolCalls {
+                    dialect: parser.dialect(),
+                    calls: calls
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, function)| ToolCall {
+                            id: format!("call_{}", i),
+                            tool_type: "function".to_string(),
+                            function,
+                        })
+                        .collect(),
+                    text,
+                })
+            })
+    }
+}
+
+/// Parse the tool calls in `content` written in `dialect`
+pub fn parse_tool_calls(content: &str, dialect: ToolCallDialect) -> Option<TextToolCalls> {
+    ToolCallParsers::for_dialect(dialect).parse(content)
+}
+
+/// Parse tool calls from XML-like format used by some models (e.g., glm-4.6)
+/// Format: <tool_call>TOOL_NAME\n<arg_key>KEY</arg_key>\n<arg_value>VALUE</arg_value>\n...</tool_call>
+pub fn parse_xml_tool_calls(content: &str) -> Option<Vec<ToolCall>> {
+    parse_tool_calls(content, ToolCallDialect::Glm).map(|parsed| parsed.calls)
+}
+
+const TOOL_CALL_OPEN: &str = "<tool_call>";
+const TOOL_CALL_CLOSE: &str = "</tool_call>";
+
+/// `<tool_call>NAME<arg_key>..</arg_key><arg_value>..</arg_value></tool_call>`
+pub struct GlmParser;
+
+impl ToolCallParser for GlmParser {
+    fn dialect(&self) -> ToolCallDialect {
+        ToolCallDialect::Glm
+    }
+
+    fn detect(&self, content: &str) -> bool {
+        tagged_blocks(content, TOOL_CALL_OPEN, TOOL_CALL_CLOSE)
+            .iter()
+            .any(|(_, body)| !body.trim_start().starts_with(['{', '[']))
+    }
+
+    fn parse(&self, content: &str) -> Option<(Vec<FunctionCall>, String)> {
+        let mut calls = Vec::new();
+        let mut consumed = Vec::new();
+        for (range, body) in tagged_blocks(content, TOOL_CALL_OPEN, TOOL_CALL_CLOSE) {
+            if body.trim_start().starts_with(['{', '[']) {
+                continue;
+            }
+            let name = body[..body.find('<').unwrap_or(body.len())].trim();
+            if name.is_empty() {
+                continue;
+            }
+
+            let mut arguments = Map::new();
+            let keys = tagged_blocks(body, "<arg_key>", "</arg_key>");
+            for (i, (key_range, key)) in keys.iter().enumerate() {
+                // A key's value is the first <arg_value> before the next key
+                let until = keys.get(i + 1).map(|(next, _)| next.start).unwrap_or(body.len());
+                let rest = &body[key_range.end..until];
+                if let Some((_, value)) = tagged_blocks(rest, "<arg_value>", "</arg_value>").first() {
+                    arguments.insert(key.trim().to_string(), text_value(value.trim()));
+                }
+            }
+
+            calls.push(function_call(name, Value::Object(arguments)));
+            consumed.push(range);
+        }
+        Some((calls, remove_ranges(content, &consumed)))
+    }
+}
+
+/// `<tool_call>{"name": .., "arguments": ..}</tool_call>`
+pub struct HermesParser;
+
+impl ToolCallParser for HermesParser {
+    fn dialect(&self) -> ToolCallDialect {
+        ToolCallDialect::Hermes
+    }
+
+    fn detect(&self, content: &str) -> bool {
+        tagged_blocks(content, TOOL_CALL_OPEN, TOOL_CALL_CLOSE)
+            .iter()
+            .any(|(_, body)| body.trim_start().starts_with(['{', '[']))
+    }
+
+    fn parse(&self, content: &str) -> Option<(Vec<FunctionCall>, String)> {
+        let mut calls = Vec::new();
+        let mut consumed = Vec::new();
+        for (range, body) in tagged_blocks(content, TOOL_CALL_OPEN, TOOL_CALL_CLOSE) {
+            let found = calls_from_json_sequence(body);
+            if !found.is_empty() {
+                calls.extend(found);
+                consumed.push(range);
+            }
+        }
+        Some((calls, remove_ranges(content, &consumed)))
+    }
+}
+
+const PYTHON_TAG: &str = "<|python_tag|>";
+const LLAMA_END_TOKENS: [&str; 3] = ["<|eom_id|>", "<|eot_id|>", "<|end_of_text|>"];
+
+/// `<|python_tag|>{"name": .., "parameters": ..}`, several calls separated by `;`
+pub struct Llama3Parser;
+
+impl ToolCallParser for Llama3Parser {
+    fn dialect(&self) -> ToolCallDialect {
+        ToolCallDialect::Llama3
+    }
+
+    fn detect(&self, content: &str) -> bool {
+        content.contains(PYTHON_TAG)
+    }
+
+    fn parse(&self, content: &str) -> Option<(Vec<FunctionCall>, String)> {
+        let start = content.find(PYTHON_TAG)?;
+        let body_start = start + PYTHON_TAG.len();
+        let body_end = LLAMA_END_TOKENS
+            .iter()
+            .filter_map(|token| content[body_start..].find(token))
+            .min()
+            .map(|end| body_start + end)
+            .unwrap_or(content.len());
+
+        let calls = calls_from_json_sequence(&content[body_start..body_end]);
+        let mut text = format!("{}{}", &content[..start], &content[body_end..]);
+        for token in LLAMA_END_TOKENS {
+            text = text.replace(token, "");
+        }
+        Some((calls, text.trim().to_string()))
+    }
+}
+
+const MISTRAL_TAG: &str = "[TOOL_CALLS]";
+const MISTRAL_ARGS: &str = "[ARGS]";
+
+/// `[TOOL_CALLS][{"name": .., "arguments": ..}]`, or `[TOOL_CALLS]NAME[ARGS]{..}`
+/// in newer templates
+pub struct MistralParser;
+
+impl ToolCallParser for MistralParser {
+    fn dialect(&self) -> ToolCallDialect {
+        ToolCallDialect::Mistral
+    }
+
+    fn detect(&self, content: &str) -> bool {
+        content.contains(MISTRAL_TAG)
+    }
+
+    fn parse(&self, content: &str) -> Option<(Vec<FunctionCall>, String)> {
+        let start = content.find(MISTRAL_TAG)?;
+        let mut calls = Vec::new();
+        let mut rest = &content[start..];
+        while let Some(after_tag) = rest.strip_prefix(MISTRAL_TAG) {
+            let trimmed = after_tag.trim_start();
+            if trimmed.starts_with(['[', '{']) {
+                let (value, used) = leading_json(trimmed)?;
+                calls.extend(calls_from_json(&value));
+                rest = trimmed[used..].trim_start();
+            } else {
+                let args_at = trimmed.find(MISTRAL_ARGS)?;
+                let name = trimmed[..args_at].trim();
+                let after_args = &trimmed[args_at + MISTRAL_ARGS.len()..];
+                let (arguments, used) = leading_json(after_args)?;
+                calls.push(function_call(name, arguments));
+                rest = after_args[used..].trim_start();
+            }
+        }
+
+        let trailing = rest.trim_start_matches("</s>").trim();
+        let text = format!("{}{}", content[..start].trim(), trailing);
+        Some((calls, text.trim().to_string()))
+    }
+}
+
+/// Info strings of fenced blocks that may hold tool calls
+const FENCE_LANGUAGES: [&str; 4] = ["json", "tool_call", "tool_calls", "tool"];
+
+/// Fenced ```json blocks holding a call object or an array of them. Blocks
+/// holding other JSON are left in the text.
+pub struct JsonBlockParser;
+
+impl ToolCallParser for JsonBlockParser {
+    fn dialect(&self) -> ToolCallDialect {
+        ToolCallDialect::Json
+    }
+
+    fn detect(&self, content: &str) -> bool {
+        !tool_fences(content).is_empty()
+    }
+
+    fn parse(&self, content: &str) -> Option<(Vec<FunctionCall>, String)> {
+        let mut calls = Vec::new();
+        let mut consumed = Vec::new();
+        for (range, found) in tool_fences(content) {
+            calls.extend(found);
+            consumed.push(range);
+        }
+        Some((calls, remove_ranges(content, &consumed)))
+    }
+}
+
+/// Fenced blocks whose whole body is one or more tool calls
+fn tool_fences(content: &str) -> Vec<(Range<usize>, Vec<FunctionCall>)> {
+    tagged_blocks(content, "```", "```")
+        .into_iter()
+        .filter_map(|(range, body)| {
+            let (language, json) = body.split_once('\n')?;
+            if !FENCE_LANGUAGES.contains(&language.trim().to_lowercase().as_str()) {
+                return None;
+            }
+            let (value, used) = leading_json(json)?;
+            if !json[used..].trim().is_empty() {
+                return None;
+            }
+            // Without the tags of other dialects, only objects that name
+            // their arguments count as calls; `{"name": ..}` alone is too common
+            let objects = match value {
+                Value::Array(ref items) => items.iter().collect(),
+                ref object => vec![object],
+            };
+            let named_arguments = |object: &&Value| {
+                let object = object.get("function").unwrap_or(object);
+                object.get("arguments").or_else(|| object.get("parameters")).is_some()
+            };
+            if objects.is_empty() || !objects.iter().all(named_arguments) {
+                return None;
+            }
+            let calls = calls_from_json(&value);
+            (calls.len() == objects.len()).then_some((range, calls))
+        })
+        .collect()
+}
+
+/// The blocks between `open` and `close` tags: each block's range including
+/// the tags, and its body. A block left open runs to the end of the text.
+fn tagged_blocks<'a>(content: &'a str, open: &str, close: &str) -> Vec<(Range<usize>, &'a str)> {
+    let mut blocks = Vec::new();
+    let mut idx = 0;
+    while let Some(found) = content[idx..].find(open) {
+        let start = idx + found;
+        let body_start = start + open.len();
+        let (body_end, end) = match content[body_start..].find(close) {
+            Some(found) => (body_start + found, body_start + found + close.len()),
+            None => (content.len(), content.len()),
+        };
+        blocks.push((start..end, &content[body_start..body_end]));
+        idx = end;
+    }
+    blocks
+}
+
+/// `content` without the given byte ranges, trimmed
+fn remove_ranges(content: &str, ranges: &[Range<usize>]) -> String {
+    let mut text = String::new();
+    let mut idx = 0;
+    for range in ranges {
+        text.push_str(&content[idx..range.start]);
+        idx = range.end;
+    }
+    text.push_str(&content[idx..]);
+    text.trim().to_string()
+}
+
+/// The JSON value at the start of `text` and how many bytes it took
+fn leading_json(text: &str) -> Option<(Value, usize)> {
+    let mut values = serde_json::Deserializer::from_str(text).into_iter::<Value>();
+    let value = values.next()?.ok()?;
+    Some((value, values.byte_offset()))
+}
+
+/// Calls in a run of JSON values separated by whitespace, `,` or `;`
+fn calls_from_json_sequence(text: &str) -> Vec<FunctionCall> {
+    let mut calls = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some((value, used)) = leading_json(rest) {
+        calls.extend(calls_from_json(&value));
+        rest = rest[used..].trim_start_matches(|c: char| c.is_whitespace() || c == ';' || c == ',');
+    }
+    calls
+}
+
+/// Calls in a call object or an array of them. Call objects have a `name`
+/// and `arguments` or `parameters`, possibly wrapped in `function`.
+fn calls_from_json(value: &Value) -> Vec<FunctionCall> {
+    match value {
+        Value::Array(items) => items.iter().flat_map(calls_from_json).collect(),
+        Value::Object(object) => {
+            if let Some(function) = object.get("function").filter(|function| function.is_object()) {
+                return calls_from_json(function);
+            }
+            let Some(name) = object.get("name").and_then(Value::as_str) else {
+                return Vec::new();
+            };
+            let arguments = match object.get("arguments").or_else(|| object.get("parameters")) {
+                // Some models send the arguments as a JSON string
+                Some(Value::String(text)) => match serde_json::from_str::<Value>(text) {
+                    Ok(arguments) if arguments.is_object() => arguments,
+                    _ => return Vec::new(),
+                },
+                Some(arguments @ Value::Object(_)) => arguments.clone(),
+                None | Some(Value::Null) => Value::Object(Map::new()),
+                Some(_) => return Vec::new(),
+            };
+            vec![function_call(name, arguments)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn function_call(name: &str, arguments: Value) -> FunctionCall {
+    FunctionCall {
+        name: name.to_string(),
+        arguments: serde_json::to_string(&arguments).unwrap_or_else(|_| "{}".to_string()),
+    }
+}
+
+/// The value of a text argument: a number or boolean when the text is exactly
+/// its JSON rendering, otherwise the text itself. Requiring an exact rendering
+/// keeps text like `007` or `1.50` unchanged. Arrays and objects stay text, as
+/// they are often file content; argument validation parses them when the
+/// tool's schema asks for one.
+fn text_value(text: &str) -> Value {
+    match serde_json::from_str::<Value>(text) {
+        Ok(value) if value.is_number() || value.is_boolean() => {
+            let rendered = serde_json::to_string(&value).unwrap_or_default();
+            if rendered == text {
+                value
+            } else {
+                Value::String(text.to_string())
+            }
+        }
+        _ => Value::String(text.to_string()),
+    }
+}
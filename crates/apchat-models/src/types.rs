@@ -1,4 +1,5 @@
 use serde::{Deserialize, Deserializer, Serialize};
+use crate::ToolArgs;
 
 /// Backend type for LLM models
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -32,6 +33,63 @@ impl BackendType {
     }
 }
 
+/// How tool calls written into message text are recognized, for models that
+/// do not return structured tool calls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolCallDialect {
+    /// Detect the dialect from the text; fenced JSON needs [`Json`](Self::Json)
+    #[default]
+    Auto,
+    /// `<tool_call>NAME<arg_key>..</arg_key><arg_value>..</arg_value></tool_call>` (GLM)
+    Glm,
+    /// `<tool_call>{"name": .., "arguments": ..}</tool_call>` (Hermes, Qwen)
+    Hermes,
+    /// `<|python_tag|>{"name": .., "parameters": ..}` (Llama 3)
+    Llama3,
+    /// `[TOOL_CALLS][{"name": .., "arguments": ..}]` (Mistral)
+    Mistral,
+    /// Fenced ```json blocks holding `{"name": .., "arguments": ..}`
+    Json,
+    /// Only structured tool calls are used
+    None,
+}
+
+impl std::str::FromStr for ToolCallDialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "glm" | "xml" => Ok(Self::Glm),
+            "hermes" | "qwen" => Ok(Self::Hermes),
+            "llama3" | "llama-3" | "llama" => Ok(Self::Llama3),
+            "mistral" => Ok(Self::Mistral),
+            "json" | "fenced" => Ok(Self::Json),
+            "none" | "off" => Ok(Self::None),
+            _ => Err(format!(
+                "Unknown tool parser '{}' (expected auto, glm, hermes, llama3, mistral, json or none)",
+                s
+            )),
+        }
+    }
+}
+
+impl ToolCallDialect {
+    /// Get string representation
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Auto => "auto",
+            Self::Glm => "glm",
+            Self::Hermes => "hermes",
+            Self::Llama3 => "llama3",
+            Self::Mistral => "mistral",
+            Self::Json => "json",
+            Self::None => "none",
+        }
+    }
+}
+
 /// Model colors supported by the system
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ModelColor {
@@ -51,6 +109,17 @@ pub struct ModelProvider {
     pub api_url: Option<String>,
     /// API key for the provider
     pub api_key: Option<String>,
+    /// Dialect of tool calls the model writes into its text
+    #[serde(default)]
+    pub tool_parser: ToolCallDialect,
+    /// Whether the backend accepts tool definitions; when false the tools are
+    /// described in the prompt instead (see [`PromptTools`](crate::PromptTools))
+    #[serde(default = "default_native_tools")]
+    pub native_tools: bool,
+}
+
+fn default_native_tools() -> bool {
+    true
 }
 
 impl std::fmt::Debug for ModelProvider {
@@ -66,6 +135,8 @@ impl std::fmt::Debug for ModelProvider {
             .field("backend", &self.backend)
             .field("api_url", &self.api_url)
             .field("api_key", &masked_key)
+            .field("tool_parser", &self.tool_parser)
+            .field("native_tools", &self.native_tools)
             .finish()
     }
 }
@@ -78,6 +149,8 @@ impl ModelProvider {
             backend: None,
             api_url: None,
             api_key: None,
+            tool_parser: ToolCallDialect::Auto,
+            native_tools: true,
         }
     }
     
@@ -88,6 +161,8 @@ impl ModelProvider {
             backend,
             api_url,
             api_key,
+            tool_parser: ToolCallDialect::Auto,
+            native_tools: true,
         }
     }
 }
@@ -103,6 +178,10 @@ pub struct ModelConfig {
     pub api_key: Option<String>,
     /// Model name override for this model
     pub model: Option<String>,
+    /// Tool call dialect for this model (see [`ToolCallDialect`])
+    pub tool_parser: Option<String>,
+    /// Describe tools in the prompt instead of sending them to the backend
+    pub no_native_tools: bool,
 }
 
 impl ModelColor {
@@ -255,60 +334,297 @@ fn default_pattern() -> String {
     "*".to_string()
 }
 
-#[derive(Debug, Deserialize)]
+fn default_search_pattern() -> String {
+    "**/*.rs".to_string()
+}
+
+fn default_max_results() -> usize {
+    50
+}
+
+fn default_start_line() -> usize {
+    1
+}
+
+fn default_output_lines() -> usize {
+    200
+}
+
+fn default_search_tools_limit() -> usize {
+    5
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_terminal_cols() -> u16 {
+    80
+}
+
+fn default_terminal_rows() -> u16 {
+    24
+}
+
+fn default_user_input_timeout() -> u64 {
+    300
+}
+
+fn default_subagent_timeout() -> u64 {
+    300
+}
+
+// Doc comments on these fields are the parameter descriptions sent to the
+// model, see `ToolArgs`
+
+#[derive(Debug, Deserialize, ToolArgs)]
 pub struct ReadFileArgs {
+    /// Path to the file relative to the work directory
     pub file_path: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToolArgs)]
 pub struct WriteFileArgs {
+    /// Path to the file relative to the work directory
     pub file_path: String,
+    /// Content to write to the file
     pub content: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToolArgs)]
 pub struct ListFilesArgs {
+    /// Glob pattern (e.g., 'src/**/*.rs', '**/*.json'). Use ** for recursive
+    /// search. Defaults to '*' (files in current directory). Respects
+    /// .gitignore and limits to 1000 results.
     #[serde(default = "default_pattern")]
     pub pattern: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToolArgs)]
 pub struct EditFileArgs {
+    /// Path to the file relative to the work directory
     pub file_path: String,
+    /// Old content to find and replace (must not be empty)
     pub old_content: String,
+    /// New content to replace with
     pub new_content: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToolArgs)]
 pub struct SwitchModelArgs {
+    /// Model to switch to (kimi or gpt_oss)
     pub model: String,
+    /// Reason for switching models
     pub reason: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToolArgs)]
 pub struct RunCommandArgs {
+    /// Shell command to execute
     pub command: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToolArgs)]
 pub struct SearchFilesArgs {
-    #[serde(default)]
+    /// Text or pattern to search for
     pub query: String,
-    #[serde(default = "default_pattern")]
+    /// File pattern to search in (e.g., 'src/**/*.rs', '**/*.py'). Use ** for
+    /// recursive search. Defaults to '**/*.rs' (all Rust files)
+    #[serde(default = "default_search_pattern")]
     pub pattern: String,
+    /// Use regex search instead of plain text
     #[serde(default)]
     pub regex: bool,
+    /// Case insensitive search
     #[serde(default)]
     pub case_insensitive: bool,
+    /// Maximum number of results to return
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct ReadToolOutputArgs {
+    /// Handle of the saved output, as given in the excerpted tool result
+    pub handle: String,
+    /// First line to show (1-based)
+    #[serde(default = "default_start_line")]
+    #[tool(min = 1)]
+    pub start_line: usize,
+    /// Maximum number of lines to show
+    #[serde(default = "default_output_lines")]
+    #[tool(min = 1, max = 1000)]
+    pub lines: usize,
+    /// Only show lines matching this regular expression
+    pub grep: Option<String>,
+    /// Show the last N lines instead of paging from start_line
+    pub tail: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct SearchToolsArgs {
+    /// Keywords to look for in tool names, categories and descriptions
     #[serde(default)]
-    pub max_results: u32,
+    pub query: String,
+    /// Only return tools in this category (e.g. terminal, mcp, custom)
+    pub category: Option<String>,
+    /// Maximum number of tools to return
+    #[serde(default = "default_search_tools_limit")]
+    #[tool(min = 1, max = 20)]
+    pub limit: usize,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToolArgs)]
 pub struct OpenFileArgs {
+    /// Path to the file relative to the work directory
     pub file_path: String,
+    /// Starting line number (1-based)
+    pub start_line: Option<usize>,
+    /// Ending line number (1-based)
+    pub end_line: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct PtyLaunchArgs {
+    /// Command to run in the terminal (default: shell)
+    pub command: Option<String>,
+    /// Working directory for the session (default: current)
+    pub working_dir: Option<String>,
+    /// Terminal width in columns
+    #[serde(default = "default_terminal_cols")]
+    #[tool(min = 1)]
+    pub cols: u16,
+    /// Terminal height in rows
+    #[serde(default = "default_terminal_rows")]
+    #[tool(min = 1)]
+    pub rows: u16,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct PtySendKeysArgs {
+    /// Session ID to send keys to
+    pub session_id: String,
+    /// Keys to send to the terminal. Will be auto-terminated with 'Enter' key
+    /// unless raw=true is supplied.
+    pub keys: String,
+    /// Do not add 'Enter' key at the end of the series of keystrokes
     #[serde(default)]
-    pub start_line: usize,
+    pub raw: bool,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct PtyGetScreenArgs {
+    /// Session ID to get screen from
+    pub session_id: String,
+    /// Include ANSI color codes
     #[serde(default)]
-    pub end_line: usize,
+    pub include_colors: bool,
+    /// Include cursor position
+    #[serde(default = "default_true")]
+    pub include_cursor: bool,
+}
+
+/// Arguments of the terminal tools that only name a session
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct PtySessionArgs {
+    /// ID of the terminal session
+    pub session_id: String,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct PtyResizeArgs {
+    /// Session ID to resize
+    pub session_id: String,
+    /// New terminal width in columns
+    #[tool(min = 1)]
+    pub cols: u16,
+    /// New terminal height in rows
+    #[tool(min = 1)]
+    pub rows: u16,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct PtySetScrollbackArgs {
+    /// Session ID to configure
+    pub session_id: String,
+    /// Number of scrollback lines to keep
+    pub lines: usize,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct PtyRequestUserInputArgs {
+    /// Session ID to hand over to user
+    pub session_id: String,
+    /// Message to display to the user explaining what's needed
+    pub message: String,
+    /// Timeout in seconds
+    #[serde(default = "default_user_input_timeout")]
+    pub timeout_seconds: u64,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct LaunchSubagentArgs {
+    /// The task description for the subagent to execute
+    pub task: String,
+    /// Working directory for the subagent (defaults to current directory)
+    pub working_directory: Option<String>,
+    /// Timeout in seconds
+    #[serde(default = "default_subagent_timeout")]
+    pub timeout_seconds: u64,
+    /// Whether to auto-confirm all actions without prompting. When false,
+    /// actions that need confirmation are escalated to the user of this session
+    #[serde(default = "default_true")]
+    pub auto_confirm: bool,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct TodoItemArgs {
+    /// Task in imperative form
+    pub content: String,
+    /// Task status
+    #[tool(enum("pending", "in_progress", "completed"))]
+    pub status: String,
+    /// Task in present continuous form
+    #[serde(rename = "activeForm")]
+    pub active_form: String,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct TodoWriteArgs {
+    /// The complete task list
+    pub todos: Vec<TodoItemArgs>,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct RequestMoreIterationsArgs {
+    /// Number of additional iterations requested (1-10)
+    #[tool(min = 1, max = 10)]
+    pub additional_iterations: u32,
+    /// Detailed explanation of why more iterations are needed and what will
+    /// be accomplished
+    pub justification: String,
+    /// Summary of progress made so far and current findings
+    pub progress_summary: String,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct ProjectAnalysisArgs {
+    /// Type of analysis to perform
+    #[tool(enum("structure", "dependencies", "file_types"))]
+    pub analysis_type: String,
+    /// Target path for analysis (defaults to the project root)
+    pub target_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct LoadSkillArgs {
+    /// Name of the skill to load (e.g., 'test-driven-development',
+    /// 'systematic-debugging')
+    pub skill_name: String,
+}
+
+#[derive(Debug, Deserialize, ToolArgs)]
+pub struct FindRelevantSkillsArgs {
+    /// Description of the task you're about to perform
+    pub task_description: String,
 }
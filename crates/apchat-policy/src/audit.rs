@@ -0,0 +1,250 @@
+//! Append-only JSONL audit trail of policy decisions and user responses.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{ActionType, Decision, GrantScope, PolicyDecision, PolicyLayer};
+
+/// What an audit entry records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// The policy evaluated an action
+    Decision,
+    /// A user (or confirmation provider) answered an `Ask` decision
+    Response,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub event: AuditEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub action: ActionType,
+    pub target: String,
+    pub decision: Decision,
+    /// Layer that produced a policy decision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<PolicyLayer>,
+    /// Pattern of the rule that matched, or the name of a content rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Reason the user gave with their answer, or why a content rule matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Scope the user granted their answer for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<GrantScope>,
+}
+
+impl AuditEntry {
+    /// Entry for a decision made by the policy
+    pub fn decision(action: &ActionType, target: &str, decision: &PolicyDecision) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            event: AuditEvent::Decision,
+            session_id: None,
+            action: action.clone(),
+            target: target.to_string(),
+            decision: decision.decision.clone(),
+            layer: decision.layer,
+            rule: decision
+                .content_rule
+                .as_ref()
+                .map(|rule| rule.name.clone())
+                .or_else(|| decision.rule.as_ref().map(|rule| rule.pattern.clone())),
+            reason: decision.reason(),
+            scope: None,
+        }
+    }
+
+    /// Entry for an answer to a confirmation prompt
+    pub fn response(action: &ActionType, target: &str, approved: bool, reason: Option<String>, scope: Option<GrantScope>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            event: AuditEvent::Response,
+            session_id: None,
+            action: action.clone(),
+            target: target.to_string(),
+            decision: if approved { Decision::Allow } else { Decision::Deny },
+            layer: None,
+            rule: None,
+            reason,
+            scope,
+        }
+    }
+
+    pub fn with_session_id(mut self, session_id: Option<String>) -> Self {
+        self.session_id = session_id;
+        self
+    }
+}
+
+impl std::fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} [{}] {} {} '{}' -> {}",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.session_id.as_deref().unwrap_or("-"),
+            match self.event {
+                AuditEvent::Decision => "policy",
+                AuditEvent::Response => "user",
+            },
+            self.action,
+            self.target,
+            self.decision
+        )?;
+        match (&self.layer, &self.rule) {
+            (Some(layer), Some(rule)) => write!(f, " ({} rule '{}')", layer, rule)?,
+            (Some(layer), None) => write!(f, " ({} default)", layer)?,
+            _ => {}
+        }
+        if let Some(ref scope) = self.scope {
+            write!(f, " [{}]", scope)?;
+        }
+        if let Some(ref reason) = self.reason {
+            write!(f, " reason: {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// A JSONL audit log file
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Location of the audit log inside a logs directory
+    pub fn default_path(logs_dir: &Path) -> PathBuf {
+        logs_dir.join("policy_audit.jsonl")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry as a single JSON line
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log {}", self.path.display()))?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Read entries recorded at or after `since`; unreadable lines are skipped
+    pub fn read(&self, since: Option<DateTime<Utc>>) -> Result<Vec<AuditEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = std::fs::File::open(&self.path)
+            .with_context(|| format!("Failed to open audit log {}", self.path.display()))?;
+        let mut entries = Vec::new();
+        for line in std::io::BufReader::new(file).lines() {
+            let line = line?;
+            if let Ok(entry) = serde_json::from_str::<AuditEntry>(&line) {
+                if since.is_none_or(|since| entry.timestamp >= since) {
+                    entries.push(entry);
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Parse a `--since` value: a relative age such as `30m`, `2h` or `7d`, an
+/// RFC 3339 timestamp, or a `YYYY-MM-DD` date (midnight UTC)
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Some(unit) = value.chars().last().filter(|c| c.is_ascii_alphabetic()) {
+        if let Ok(amount) = value[..value.len() - 1].parse::<i64>() {
+            let age = match unit {
+                's' => Duration::seconds(amount),
+                'm' => Duration::minutes(amount),
+                'h' => Duration::hours(amount),
+                'd' => Duration::days(amount),
+                'w' => Duration::weeks(amount),
+                _ => anyhow::bail!("Unknown time unit '{}' in '{}' (expected s, m, h, d or w)", unit, value),
+            };
+            return Ok(now - age);
+        }
+    }
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    anyhow::bail!("Invalid time '{}' (use e.g. 30m, 2h, 7d, 2024-05-01 or an RFC 3339 timestamp)", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PolicyManager, PolicyRule};
+
+    #[test]
+    fn test_parse_since() {
+        let now = chrono::DateTime::parse_from_rfc3339("2024-05-10T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        assert_eq!(parse_since("30m", now).unwrap(), now - chrono::Duration::minutes(30));
+        assert_eq!(parse_since("2d", now).unwrap(), now - chrono::Duration::days(2));
+        assert_eq!(parse_since("2024-05-01", now).unwrap().to_rfc3339(), "2024-05-01T00:00:00+00:00");
+        assert_eq!(parse_since("2024-05-09T08:00:00Z", now).unwrap().to_rfc3339(), "2024-05-09T08:00:00+00:00");
+        assert!(parse_since("3y", now).is_err());
+        assert!(parse_since("yesterday", now).is_err());
+    }
+
+    #[test]
+    fn test_audit_log_records_decisions_and_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = AuditLog::new(dir.path().join("logs/policy_audit.jsonl"));
+        let manager = PolicyManager::new().with_audit_log(audit.clone());
+        manager.add_session_rule(PolicyRule::new(ActionType::CommandExecution, "cargo *".to_string(), Decision::Allow));
+
+        let session = manager.for_session("session_1");
+        session.evaluate(&ActionType::CommandExecution, "cargo test");
+        session.record_response(&ActionType::FileWrite, "notes.md", false, Some("not now".to_string()), None);
+        manager.evaluate(&ActionType::FileRead, "README.md");
+
+        let entries = audit.read(None).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].event, AuditEvent::Decision);
+        assert_eq!(entries[0].session_id.as_deref(), Some("session_1"));
+        assert_eq!(entries[0].layer, Some(PolicyLayer::Session));
+        assert_eq!(entries[0].rule.as_deref(), Some("cargo *"));
+        assert_eq!(entries[1].event, AuditEvent::Response);
+        assert_eq!(entries[1].decision, Decision::Deny);
+        assert_eq!(entries[1].reason.as_deref(), Some("not now"));
+        assert!(entries[2].session_id.is_none());
+
+        let future = chrono::Utc::now() + chrono::Duration::hours(1);
+        assert!(audit.read(Some(future)).unwrap().is_empty());
+    }
+}
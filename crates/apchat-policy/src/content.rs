@@ -0,0 +1,135 @@
+//! Rules that inspect the payload of an action instead of its target: the
+//! content about to be written to a file, or the command line about to run.
+//!
+//! Content rules can only make a decision stricter. A rule that matches
+//! never allows something the path or command rules would ask about.
+//! Patterns are compiled once, on first use; policy files with an invalid
+//! pattern are rejected when they are loaded.
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::{ActionType, Decision};
+
+/// A rule matched against the payload of an action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentRule {
+    /// Short identifier shown in decisions, e.g. `aws-access-key`
+    pub name: String,
+    /// Actions whose payload is inspected
+    pub actions: Vec<ActionType>,
+    /// Regular expression matched against the payload
+    pub pattern: String,
+    /// Decision to make when the pattern matches
+    #[serde(default = "default_content_decision")]
+    pub decision: Decision,
+    /// Explanation given to the model when the rule matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip)]
+    compiled: CompiledPattern,
+}
+
+/// `pattern`, compiled the first time the rule is matched; `None` if it is
+/// not a valid regular expression
+#[derive(Debug, Clone, Default)]
+struct CompiledPattern(OnceLock<Option<Regex>>);
+
+/// The compiled form follows from the pattern, so it never tells rules apart
+impl PartialEq for CompiledPattern {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for CompiledPattern {}
+
+fn default_content_decision() -> Decision {
+    Decision::Deny
+}
+
+impl ContentRule {
+    pub fn new(name: &str, actions: &[ActionType], pattern: &str, decision: Decision) -> Self {
+        Self {
+            name: name.to_string(),
+            actions: actions.to_vec(),
+            pattern: pattern.to_string(),
+            decision,
+            reason: None,
+            compiled: CompiledPattern::default(),
+        }
+    }
+
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+
+    /// Check that the pattern is a valid regular expression
+    pub fn validate(&self) -> Result<()> {
+        Regex::new(&self.pattern)
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("Invalid pattern in content rule '{}': {}", self.name, e))
+    }
+
+    /// Check if this rule matches the payload of the given action. A rule
+    /// whose pattern does not compile (see [`validate`](Self::validate))
+    /// matches nothing.
+    pub fn matches(&self, action: &ActionType, content: &str) -> bool {
+        self.actions.contains(action)
+            && self
+                .compiled
+                .0
+                .get_or_init(|| Regex::new(&self.pattern).ok())
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(content))
+    }
+
+    /// Why the rule matched; never quotes the matched text, which may be a secret
+    pub fn explain(&self) -> String {
+        match self.reason {
+            Some(ref reason) => reason.clone(),
+            None => format!("content matches the '{}' pattern", self.name),
+        }
+    }
+}
+
+/// Built-in content rules that keep secrets out of files and command output
+pub fn builtin_content_rules() -> Vec<ContentRule> {
+    const WRITES: &[ActionType] = &[
+        ActionType::FileWrite,
+        ActionType::FileEdit,
+        ActionType::CommandExecution,
+        ActionType::TerminalInput,
+    ];
+    const COMMANDS: &[ActionType] = &[ActionType::CommandExecution, ActionType::TerminalInput];
+
+    vec![
+        ContentRule::new("private-key", WRITES, r"-----BEGIN (?:[A-Z0-9]+ )*PRIVATE KEY-----", Decision::Deny)
+            .with_reason("Content contains a private key"),
+        ContentRule::new("openai-api-key", WRITES, r"\bsk-(?:proj-|ant-)?[A-Za-z0-9_-]{20,}", Decision::Deny)
+            .with_reason("Content contains what looks like an OpenAI or Anthropic API key (sk-...)"),
+        ContentRule::new("groq-api-key", WRITES, r"\bgsk_[A-Za-z0-9]{20,}", Decision::Deny)
+            .with_reason("Content contains what looks like a Groq API key (gsk_...)"),
+        ContentRule::new("aws-access-key", WRITES, r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b", Decision::Deny)
+            .with_reason("Content contains what looks like an AWS access key id"),
+        ContentRule::new(
+            "aws-secret-key",
+            WRITES,
+            r#"(?i)aws_secret_access_key\s*[=:]\s*["']?[A-Za-z0-9/+=]{40}"#,
+            Decision::Deny,
+        )
+        .with_reason("Content contains an AWS secret access key"),
+        ContentRule::new(
+            "echo-env-secret",
+            COMMANDS,
+            r"(?i)\b(?:echo|printf)\b[^;&|]*\$\{?[a-z0-9_]*(?:key|token|secret|passw(?:or)?d)[a-z0-9_]*|\bprintenv\s+[a-z0-9_]*(?:key|token|secret|passw(?:or)?d)",
+            Decision::Deny,
+        )
+        .with_reason("Command would print a secret environment variable"),
+        ContentRule::new("dump-environment", COMMANDS, r"(?:^|[;&|])\s*(?:env|printenv|export -p)\s*(?:$|[;&|])", Decision::Ask)
+            .with_reason("Command prints every environment variable, including secrets"),
+    ]
+}
@@ -0,0 +1,450 @@
+//! Learning rules from user decisions.
+//!
+//! Instead of remembering the literal target of an approved action, learn
+//! mode proposes a few generalizations of it (`cargo test *`, `src/**/*.rs`,
+//! ...) and reports how each one would interact with the rules already
+//! loaded, so the user can pick the broadest rule they are comfortable with.
+
+use anyhow::Result;
+
+use crate::lint::covers;
+use crate::shell::simple_commands;
+use crate::{ActionType, Decision, PolicyLayer, PolicyManager, PolicyRule};
+
+/// How a proposed rule relates to a rule that is already loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleConflict {
+    /// An existing rule with the same decision already covers every target
+    Redundant { layer: PolicyLayer, rule: PolicyRule },
+    /// The proposed rule would override an existing rule for some targets
+    Shadows { layer: PolicyLayer, rule: PolicyRule },
+    /// An existing rule decides some targets differently and takes precedence
+    Contradicts { layer: PolicyLayer, rule: PolicyRule },
+    /// A learned rule made redundant by the proposal; removed when it is learned
+    Consolidates { rule: PolicyRule },
+}
+
+impl std::fmt::Display for RuleConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleConflict::Redundant { layer, rule } => {
+                write!(f, "already covered by {} rule '{}' ({})", layer, rule.pattern, rule.decision)
+            }
+            RuleConflict::Shadows { layer, rule } => {
+                write!(f, "overrides {} rule '{}' ({})", layer, rule.pattern, rule.decision)
+            }
+            RuleConflict::Contradicts { layer, rule } => {
+                write!(f, "overridden by {} rule '{}' ({})", layer, rule.pattern, rule.decision)
+            }
+            RuleConflict::Consolidates { rule } => write!(f, "replaces learned rule '{}'", rule.pattern),
+        }
+    }
+}
+
+/// A candidate rule offered to the user when a decision is learned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleProposal {
+    pub rule: PolicyRule,
+    pub conflicts: Vec<RuleConflict>,
+}
+
+impl RuleProposal {
+    /// Whether the proposal decides some targets differently than the rules
+    /// in effect today
+    pub fn has_contradictions(&self) -> bool {
+        self.conflicts
+            .iter()
+            .any(|c| matches!(c, RuleConflict::Shadows { .. } | RuleConflict::Contradicts { .. }))
+    }
+}
+
+/// A learned rule that another rule in the same layer makes redundant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Consolidation {
+    pub rule: PolicyRule,
+    pub covered_by: PolicyRule,
+}
+
+impl std::fmt::Display for Consolidation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} '{}' -> {} is covered by '{}'",
+            self.rule.action, self.rule.pattern, self.rule.decision, self.covered_by.pattern
+        )
+    }
+}
+
+/// Candidate patterns for a target, from the literal target to the broadest
+/// generalization. Every candidate matches the target.
+pub fn generalize(action: &ActionType, target: &str) -> Vec<String> {
+    let mut candidates = vec![target.to_string()];
+
+    match action {
+        ActionType::FileRead | ActionType::FileWrite | ActionType::FileEdit | ActionType::FileDelete => {
+            let (dir, name) = match target.rsplit_once('/') {
+                Some((dir, name)) => (Some(dir), name),
+                None => (None, target),
+            };
+            let extension = name.rsplit_once('.').map(|(_, ext)| ext).filter(|ext| !ext.is_empty());
+            let top = dir.and_then(|dir| dir.split('/').find(|segment| !segment.is_empty()));
+
+            let prefixed = |pattern: String| match dir {
+                Some(dir) => format!("{}/{}", dir, pattern),
+                None => pattern,
+            };
+            if let Some(ext) = extension {
+                candidates.push(prefixed(format!("*.{}", ext)));
+            }
+            if dir.is_some() {
+                candidates.push(prefixed("**".to_string()));
+            }
+            if let (Some(top), Some(ext)) = (top, extension) {
+                let root = if target.starts_with('/') { "/" } else { "" };
+                candidates.push(format!("{}{}/**/*.{}", root, top, ext));
+            }
+            if let Some(ext) = extension {
+                candidates.push(format!("**/*.{}", ext));
+            }
+        }
+        // A compound command line is decided part by part, so only the
+        // literal line is offered for it
+        action if action.is_shell_command() && simple_commands(target).len() == 1 => {
+            let words: Vec<&str> = target.split_whitespace().collect();
+            let leading = words
+                .iter()
+                .take_while(|word| !word.starts_with('-') && !word.contains(['/', '=', '"', '\'']))
+                .count()
+                .min(3);
+            for len in (1..=leading).rev() {
+                candidates.push(format!("{} *", words[..len].join(" ")));
+            }
+        }
+        ActionType::NetworkAccess => {
+            if let Some((scheme, rest)) = target.split_once("://") {
+                let host = rest.split('/').next().unwrap_or(rest);
+                candidates.push(format!("{}://{}/*", scheme, host));
+            }
+        }
+        ActionType::McpToolCall => {
+            if let Some((server, _)) = target.split_once('/') {
+                candidates.push(format!("{}/*", server));
+            }
+        }
+        _ => {}
+    }
+
+    let mut unique: Vec<String> = Vec::new();
+    for candidate in candidates {
+        let rule = PolicyRule::new(action.clone(), candidate.clone(), Decision::Allow);
+        if !unique.contains(&candidate) && rule.matches(action, target) {
+            unique.push(candidate);
+        }
+    }
+    unique
+}
+
+fn is_learned(rule: &PolicyRule) -> bool {
+    rule.description
+        .as_deref()
+        .is_some_and(|d| d.starts_with("Learned") || d.starts_with("Granted"))
+}
+
+fn overlaps(a: &PolicyRule, b: &PolicyRule) -> bool {
+    covers(a, b) || covers(b, a)
+}
+
+/// Whether `broad` would decide everything `narrow` decides if `narrow` were
+/// removed: no rule among `rules` with another decision overlaps `narrow`
+/// with a specificity between the two, e.g. `src/** deny` between
+/// `** allow` and `src/main.rs allow`
+fn absorbs(rules: &[PolicyRule], broad: &PolicyRule, narrow: &PolicyRule) -> bool {
+    let range = broad.specificity()..=narrow.specificity();
+    !rules.iter().any(|middle| {
+        !middle.is_expired()
+            && middle.decision != narrow.decision
+            && range.contains(&middle.specificity())
+            && overlaps(middle, narrow)
+    })
+}
+
+impl PolicyManager {
+    /// Propose rules generalizing a decision about `target`, each with the
+    /// rules it would conflict with. The first proposal is the literal target.
+    pub fn propose_rules(&self, action: &ActionType, target: &str, decision: Decision) -> Vec<RuleProposal> {
+        let stack = self.stack.read().unwrap();
+        let learn_position = PolicyLayer::PRECEDENCE
+            .iter()
+            .position(|l| *l == self.learn_layer)
+            .unwrap_or_default();
+
+        generalize(action, target)
+            .into_iter()
+            .map(|pattern| {
+                let rule = PolicyRule::new(action.clone(), pattern, decision.clone());
+                let mut conflicts = Vec::new();
+
+                for (position, layer) in PolicyLayer::PRECEDENCE.iter().enumerate() {
+                    let Some(config) = stack.layer(*layer) else {
+                        continue;
+                    };
+                    for existing in config.rules.iter().filter(|r| !r.is_expired() && r.action == rule.action) {
+                        // A rule with the same pattern in the learn layer is replaced
+                        let replaced = *layer == self.learn_layer && existing.same_scope(&rule);
+                        if replaced || !overlaps(existing, &rule) {
+                            continue;
+                        }
+
+                        let conflict = if existing.decision == rule.decision {
+                            if covers(existing, &rule) {
+                                RuleConflict::Redundant { layer: *layer, rule: existing.clone() }
+                            } else if *layer == self.learn_layer
+                                && is_learned(existing)
+                                && absorbs(&config.rules, &rule, existing)
+                            {
+                                RuleConflict::Consolidates { rule: existing.clone() }
+                            } else {
+                                continue;
+                            }
+                        } else {
+                            // Higher layers win; within a layer the more specific
+                            // rule does, and the existing rule on a tie
+                            let proposal_wins = if learn_position != position {
+                                learn_position < position
+                            } else {
+                                rule.specificity() > existing.specificity()
+                            };
+                            if proposal_wins {
+                                RuleConflict::Shadows { layer: *layer, rule: existing.clone() }
+                            } else {
+                                RuleConflict::Contradicts { layer: *layer, rule: existing.clone() }
+                            }
+                        };
+                        conflicts.push(conflict);
+                    }
+                }
+
+                RuleProposal { rule, conflicts }
+            })
+            .collect()
+    }
+
+    /// Add a rule picked from [`propose_rules`](Self::propose_rules) to the
+    /// learn layer, removing the learned rules it consolidates. Returns the
+    /// removed rules.
+    pub fn learn_rule(&self, rule: PolicyRule, reason: Option<String>) -> Result<Vec<PolicyRule>> {
+        let layer = self.learn_layer;
+        let description = match reason {
+            Some(ref reason_text) => format!("Learned from user decision: {}", reason_text),
+            None => "Learned from user decision".to_string(),
+        };
+        let rule = rule.with_description(description);
+
+        let mut stack = self.stack.write().unwrap();
+        let config = stack.layer_mut(layer)?;
+
+        let rules = std::mem::take(&mut config.rules);
+        let (removed, kept): (Vec<PolicyRule>, Vec<PolicyRule>) = rules.iter().cloned().partition(|existing| {
+            existing.same_scope(&rule)
+                || (existing.decision == rule.decision
+                    && is_learned(existing)
+                    && covers(&rule, existing)
+                    && absorbs(&rules, &rule, existing))
+        });
+        config.rules = kept;
+        let removed: Vec<PolicyRule> = removed.into_iter().filter(|r| !r.same_scope(&rule)).collect();
+
+        eprintln!("📚 Learned {} policy: {} {} -> {}", layer, rule.action, rule.pattern, rule.decision);
+        for old in &removed {
+            eprintln!("   replaces '{}'", old.pattern);
+        }
+        config.add_rule(rule);
+        self.save_layer(layer, config)?;
+
+        Ok(removed)
+    }
+
+    /// Find learned rules in a layer that another rule of the same layer
+    /// already decides the same way, with no differently deciding rule
+    /// between the two in specificity, and remove them if `apply` is set
+    pub fn consolidate(&self, layer: PolicyLayer, apply: bool) -> Result<Vec<Consolidation>> {
+        let mut stack = self.stack.write().unwrap();
+        let config = stack.layer_mut(layer)?;
+
+        let mut redundant = Vec::new();
+        for (index, rule) in config.rules.iter().enumerate() {
+            if !is_learned(rule) || rule.is_expired() {
+                continue;
+            }
+            let covering = config.rules.iter().enumerate().find(|(other_index, other)| {
+                *other_index != index
+                    && !other.is_expired()
+                    && other.decision == rule.decision
+                    && covers(other, rule)
+                    // Of two identical rules, keep the first
+                    && (!covers(rule, other) || *other_index < index)
+                    && absorbs(&config.rules, other, rule)
+            });
+            if let Some((_, other)) = covering {
+                redundant.push((
+                    index,
+                    Consolidation {
+                        rule: rule.clone(),
+                        covered_by: other.clone(),
+                    },
+                ));
+            }
+        }
+
+        if apply && !redundant.is_empty() {
+            let mut index = 0;
+            config.rules.retain(|_| {
+                index += 1;
+                !redundant.iter().any(|(i, _)| *i == index - 1)
+            });
+            self.save_layer(layer, config)?;
+        }
+        Ok(redundant.into_iter().map(|(_, consolidation)| consolidation).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::write_policy;
+    use crate::{PolicyConfig, PolicyLayer};
+
+    #[test]
+    fn test_generalize() {
+        assert_eq!(
+            generalize(&ActionType::CommandExecution, "cargo test --all"),
+            vec!["cargo test --all", "cargo test *", "cargo *"]
+        );
+        assert_eq!(generalize(&ActionType::CommandExecution, "cargo build && ls"), vec!["cargo build && ls"]);
+        assert_eq!(
+            generalize(&ActionType::FileEdit, "src/policy/lib.rs"),
+            vec!["src/policy/lib.rs", "src/policy/*.rs", "src/policy/**", "src/**/*.rs", "**/*.rs"]
+        );
+        assert_eq!(generalize(&ActionType::FileWrite, "README.md"), vec!["README.md", "*.md", "**/*.md"]);
+        assert_eq!(
+            generalize(&ActionType::NetworkAccess, "https://docs.rs/serde/latest"),
+            vec!["https://docs.rs/serde/latest", "https://docs.rs/*"]
+        );
+        assert_eq!(generalize(&ActionType::ModelSwitch, "anthropic"), vec!["anthropic"]);
+        assert_eq!(
+            generalize(&ActionType::McpToolCall, "tracker/create_issue"),
+            vec!["tracker/create_issue", "tracker/*"]
+        );
+    }
+
+    #[test]
+    fn test_propose_and_learn_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("home/policy.toml");
+        let project = PolicyLayer::project_policy_path(dir.path());
+        write_policy(&global, &[PolicyRule::new(ActionType::CommandExecution, "cargo publish *".to_string(), Decision::Deny)]);
+
+        let manager = PolicyManager::layered(Some(global), Some(project.clone()), true).unwrap();
+        manager
+            .learn(ActionType::CommandExecution, "cargo test --all".to_string(), Decision::Allow, None)
+            .unwrap();
+
+        let proposals = manager.propose_rules(&ActionType::CommandExecution, "cargo test -p foo", Decision::Allow);
+        let patterns: Vec<&str> = proposals.iter().map(|p| p.rule.pattern.as_str()).collect();
+        assert_eq!(patterns, vec!["cargo test -p foo", "cargo test *", "cargo *"]);
+        assert!(proposals[0].conflicts.is_empty());
+        assert!(!proposals[1].has_contradictions());
+        assert!(matches!(
+            &proposals[1].conflicts[..],
+            [RuleConflict::Consolidates { rule }] if rule.pattern == "cargo test --all"
+        ));
+        // The global deny takes precedence over a project rule
+        assert!(proposals[2].has_contradictions());
+        assert!(proposals[2].conflicts.iter().any(|c| matches!(
+            c,
+            RuleConflict::Contradicts { layer: PolicyLayer::Global, rule } if rule.pattern == "cargo publish *"
+        )));
+
+        let removed = manager.learn_rule(proposals[1].rule.clone(), None).unwrap();
+        assert_eq!(removed.len(), 1);
+        let saved = PolicyConfig::load_from_file(&project).unwrap();
+        assert_eq!(saved.rules.len(), 1);
+        assert_eq!(saved.rules[0].pattern, "cargo test *");
+        assert_eq!(manager.evaluate(&ActionType::CommandExecution, "cargo test -p bar"), Decision::Allow);
+
+        let proposals = manager.propose_rules(&ActionType::CommandExecution, "cargo test --doc", Decision::Allow);
+        assert!(matches!(
+            &proposals[0].conflicts[..],
+            [RuleConflict::Redundant { layer: PolicyLayer::Project, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_consolidate_learned_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = PolicyLayer::project_policy_path(dir.path());
+        let learned = |pattern: &str| {
+            PolicyRule::new(ActionType::FileEdit, pattern.to_string(), Decision::Allow)
+                .with_description("Learned from user decision".to_string())
+        };
+        write_policy(
+            &project,
+            &[
+                learned("src/main.rs"),
+                learned("src/**/*.rs"),
+                learned("src/lib.rs"),
+                PolicyRule::new(ActionType::FileEdit, "src/app.rs".to_string(), Decision::Allow),
+                learned("src/**/*.rs"),
+            ],
+        );
+
+        let manager = PolicyManager::layered(None, Some(project.clone()), false).unwrap();
+        let consolidations = manager.consolidate(PolicyLayer::Project, false).unwrap();
+        let redundant: Vec<&str> = consolidations.iter().map(|c| c.rule.pattern.as_str()).collect();
+        // Hand-written rules are left alone, and one of two duplicates is kept
+        assert_eq!(redundant, vec!["src/main.rs", "src/lib.rs", "src/**/*.rs"]);
+        assert_eq!(PolicyConfig::load_from_file(&project).unwrap().rules.len(), 5);
+
+        manager.consolidate(PolicyLayer::Project, true).unwrap();
+        let patterns: Vec<String> = PolicyConfig::load_from_file(&project)
+            .unwrap()
+            .rules
+            .into_iter()
+            .map(|rule| rule.pattern)
+            .collect();
+        assert_eq!(patterns, vec!["src/**/*.rs", "src/app.rs"]);
+    }
+
+    #[test]
+    fn test_consolidation_keeps_rules_a_more_specific_rule_relies_on() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = PolicyLayer::project_policy_path(dir.path());
+        let learned = |pattern: &str, decision: Decision| {
+            PolicyRule::new(ActionType::FileEdit, pattern.to_string(), decision)
+                .with_description("Learned from user decision".to_string())
+        };
+        write_policy(
+            &project,
+            &[
+                learned("**", Decision::Allow),
+                learned("src/**", Decision::Deny),
+                learned("src/main.rs", Decision::Allow),
+                learned("docs/*.md", Decision::Allow),
+            ],
+        );
+
+        let manager = PolicyManager::layered(None, Some(project.clone()), true).unwrap();
+        let consolidations = manager.consolidate(PolicyLayer::Project, true).unwrap();
+        let redundant: Vec<&str> = consolidations.iter().map(|c| c.rule.pattern.as_str()).collect();
+        assert_eq!(redundant, vec!["docs/*.md"]);
+        assert_eq!(manager.evaluate(&ActionType::FileEdit, "src/main.rs"), Decision::Allow);
+        assert_eq!(manager.evaluate(&ActionType::FileEdit, "src/lib.rs"), Decision::Deny);
+
+        // Learning a broader allow does not swallow it either
+        let proposals = manager.propose_rules(&ActionType::FileEdit, "src/main.rs", Decision::Allow);
+        let broad = proposals.iter().find(|p| p.rule.pattern == "**/*.rs").unwrap();
+        assert!(!broad.conflicts.iter().any(|c| matches!(c, RuleConflict::Consolidates { .. })));
+        assert!(manager.learn_rule(broad.rule.clone(), None).unwrap().is_empty());
+        assert_eq!(manager.evaluate(&ActionType::FileEdit, "src/main.rs"), Decision::Allow);
+    }
+}
@@ -0,0 +1,223 @@
+//! Static checks that find policy rules which can never decide anything, or
+//! which are partly overridden by a layer with higher precedence.
+//!
+//! Pattern overlap is approximated by matching one pattern against a sample
+//! target built from the other, so findings are hints rather than proofs.
+
+use crate::pattern::expand_braces;
+use crate::{ActionType, Decision, PolicyLayer, PolicyManager, PolicyRule};
+
+/// Why a rule was flagged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintKind {
+    /// The rule has expired and is ignored
+    Expired,
+    /// The pattern cannot match any target
+    MatchesNothing,
+    /// An earlier rule in the same layer has the same action and pattern
+    Duplicate { index: usize },
+    /// Every target of the rule is decided first by a higher-precedence layer
+    Covered { layer: PolicyLayer, pattern: String, decision: Decision },
+    /// Some targets of the rule are decided differently by a higher-precedence layer
+    Shadowed { layer: PolicyLayer, pattern: String, decision: Decision },
+}
+
+/// A rule flagged by [`PolicyManager::lint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFinding {
+    pub layer: PolicyLayer,
+    /// Position of the rule within its layer
+    pub index: usize,
+    pub rule: PolicyRule,
+    pub kind: LintKind,
+}
+
+impl LintFinding {
+    /// Whether the rule can never be the one that decides
+    pub fn is_unreachable(&self) -> bool {
+        !matches!(self.kind, LintKind::Shadowed { .. })
+    }
+}
+
+impl std::fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} rule #{} ({} '{}' -> {}): ",
+            self.layer,
+            self.index + 1,
+            self.rule.action,
+            self.rule.pattern,
+            self.rule.decision
+        )?;
+        match &self.kind {
+            LintKind::Expired => write!(f, "unreachable, the rule has expired"),
+            LintKind::MatchesNothing => write!(f, "unreachable, the pattern matches nothing"),
+            LintKind::Duplicate { index } => write!(f, "unreachable, duplicate of rule #{}", index + 1),
+            LintKind::Covered { layer, pattern, decision } => write!(
+                f,
+                "unreachable, every target is decided first by {} rule '{}' ({})",
+                layer, pattern, decision
+            ),
+            LintKind::Shadowed { layer, pattern, decision } => write!(
+                f,
+                "shadowed, targets matching {} rule '{}' are decided as {}",
+                layer, pattern, decision
+            ),
+        }
+    }
+}
+
+impl PolicyManager {
+    /// Find unreachable and shadowed rules across all loaded layers
+    pub fn lint(&self) -> Vec<LintFinding> {
+        let stack = self.stack.read().unwrap();
+        let mut findings = Vec::new();
+
+        for (position, layer) in PolicyLayer::PRECEDENCE.iter().enumerate() {
+            // Built-in rules are not the user's to fix
+            if *layer == PolicyLayer::Builtin {
+                continue;
+            }
+            let Some(config) = stack.layer(*layer) else {
+                continue;
+            };
+
+            let higher: Vec<(PolicyLayer, &PolicyRule)> = PolicyLayer::PRECEDENCE[..position]
+                .iter()
+                .filter_map(|l| stack.layer(*l).map(|config| (*l, config)))
+                .flat_map(|(l, config)| config.rules.iter().filter(|r| !r.is_expired()).map(move |r| (l, r)))
+                .collect();
+
+            for (index, rule) in config.rules.iter().enumerate() {
+                let mut flag = |kind| {
+                    findings.push(LintFinding {
+                        layer: *layer,
+                        index,
+                        rule: rule.clone(),
+                        kind,
+                    })
+                };
+
+                if rule.is_expired() {
+                    flag(LintKind::Expired);
+                } else if matches_nothing(rule) {
+                    flag(LintKind::MatchesNothing);
+                } else if let Some(earlier) = config.rules[..index].iter().position(|r| r.same_scope(rule)) {
+                    flag(LintKind::Duplicate { index: earlier });
+                } else if let Some((l, r)) = higher.iter().find(|(_, r)| covers(r, rule)) {
+                    flag(LintKind::Covered {
+                        layer: *l,
+                        pattern: r.pattern.clone(),
+                        decision: r.decision.clone(),
+                    });
+                } else {
+                    for (l, r) in higher.iter().filter(|(_, r)| r.decision != rule.decision && covers(rule, r)) {
+                        flag(LintKind::Shadowed {
+                            layer: *l,
+                            pattern: r.pattern.clone(),
+                            decision: r.decision.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+fn is_file_action(action: &ActionType) -> bool {
+    matches!(
+        action,
+        ActionType::FileRead | ActionType::FileWrite | ActionType::FileEdit | ActionType::FileDelete
+    )
+}
+
+fn matches_nothing(rule: &PolicyRule) -> bool {
+    if matches!(rule.action, ActionType::PlanEdits | ActionType::ApplyEditPlan) {
+        return false;
+    }
+    let pattern = rule.pattern.trim();
+    matches!(pattern, "" | "!*" | "!**")
+}
+
+/// Whether `broad` matches every target that `narrow` matches
+pub(crate) fn covers(broad: &PolicyRule, narrow: &PolicyRule) -> bool {
+    if broad.action != narrow.action {
+        return false;
+    }
+    if matches!(broad.action, ActionType::PlanEdits | ActionType::ApplyEditPlan) {
+        return true;
+    }
+    if broad.pattern.starts_with('!') || narrow.pattern.starts_with('!') {
+        return broad.pattern == narrow.pattern;
+    }
+
+    expand_braces(&narrow.pattern)
+        .iter()
+        .all(|alternative| broad.matches(&broad.action, &sample_target(&broad.action, alternative)))
+}
+
+/// Build a target that stands for everything a pattern matches: wildcards
+/// become literal text that only an equally broad wildcard can absorb
+fn sample_target(action: &ActionType, pattern: &str) -> String {
+    const FILLER: char = '\u{1}';
+
+    let widen = |text: &str| text.replace('*', &format!("*{}", FILLER));
+    if !is_file_action(action) {
+        return widen(pattern);
+    }
+
+    pattern
+        .split('/')
+        .map(|segment| {
+            if segment == "**" {
+                // Stands for several segments, so `*` alone cannot cover it
+                format!("{0}/{0}", FILLER)
+            } else {
+                widen(segment)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::write_policy;
+
+    #[test]
+    fn test_lint_finds_unreachable_and_shadowed_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("home/policy.toml");
+        let project = PolicyLayer::project_policy_path(dir.path());
+
+        write_policy(&global, &[
+            PolicyRule::new(ActionType::FileWrite, "src/**".to_string(), Decision::Ask),
+            PolicyRule::new(ActionType::CommandExecution, "git push *".to_string(), Decision::Deny),
+        ]);
+        write_policy(&project, &[
+            PolicyRule::new(ActionType::FileWrite, "src/**/*.rs".to_string(), Decision::Allow),
+            PolicyRule::new(ActionType::FileWrite, "docs/*.md".to_string(), Decision::Allow),
+            PolicyRule::new(ActionType::FileWrite, "docs/*.md".to_string(), Decision::Deny),
+            PolicyRule::new(ActionType::CommandExecution, "git *".to_string(), Decision::Allow),
+            PolicyRule::new(ActionType::CommandExecution, "!*".to_string(), Decision::Allow),
+            PolicyRule::new(ActionType::CommandExecution, "rm -rf /tmp/x".to_string(), Decision::Allow),
+        ]);
+
+        let manager = PolicyManager::layered(Some(global), Some(project), false).unwrap();
+        let findings = manager.lint();
+        let kinds: Vec<(usize, &LintKind)> = findings.iter().map(|f| (f.index, &f.kind)).collect();
+
+        assert_eq!(findings.len(), 5, "{:#?}", findings);
+        assert!(matches!(kinds[0], (0, LintKind::Covered { layer: PolicyLayer::Global, .. })));
+        assert!(matches!(kinds[1], (2, LintKind::Duplicate { index: 1 })));
+        assert!(matches!(kinds[2], (3, LintKind::Shadowed { layer: PolicyLayer::Global, .. })));
+        assert!(matches!(kinds[3], (4, LintKind::MatchesNothing)));
+        assert!(matches!(kinds[4], (5, LintKind::Covered { layer: PolicyLayer::Builtin, .. })));
+        assert!(!findings[2].is_unreachable());
+        assert!(findings[1].to_string().contains("duplicate of rule #2"));
+    }
+}
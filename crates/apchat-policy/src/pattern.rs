@@ -0,0 +1,391 @@
+//! Glob and command pattern matching used by policy rules.
+//!
+//! File patterns are matched segment by segment: `*`, `?` and `[...]` never
+//! cross a `/`, while a segment consisting of `**` matches zero or more whole
+//! segments. `{a,b}` alternation may be nested, and a leading `!` negates the
+//! whole pattern. Command patterns use the same syntax, but `*` matches any
+//! run of characters including spaces and slashes.
+
+use std::cmp::Reverse;
+
+/// How specific a pattern is, used to pick between several matching rules.
+///
+/// Larger values are more specific. Non-negated patterns always beat negated
+/// ones, exact (wildcard-free) patterns beat globs, and among globs the one
+/// with more literal characters wins, then fewer `**` segments, then fewer
+/// single-character wildcards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity {
+    positive: bool,
+    exact: bool,
+    literals: usize,
+    globstars: Reverse<usize>,
+    wildcards: Reverse<usize>,
+}
+
+impl Specificity {
+    /// The lowest possible specificity, used for rules whose pattern is ignored
+    pub fn lowest() -> Self {
+        Self {
+            positive: false,
+            exact: false,
+            literals: 0,
+            globstars: Reverse(usize::MAX),
+            wildcards: Reverse(usize::MAX),
+        }
+    }
+
+    /// Compute the specificity of a pattern
+    pub fn of(pattern: &str) -> Self {
+        let (negated, body) = split_negation(pattern);
+
+        // With alternation, a pattern is only as specific as its weakest branch
+        expand_braces(body)
+            .iter()
+            .map(|alt| {
+                let tokens = tokenize(alt);
+                let literals = tokens.iter().filter(|t| matches!(t, Token::Literal(_))).count();
+                let globstars = alt.split('/').filter(|s| *s == "**").count();
+                let wildcards = tokens.len() - literals;
+                Self {
+                    positive: !negated,
+                    exact: wildcards == 0,
+                    literals,
+                    globstars: Reverse(globstars),
+                    wildcards: Reverse(wildcards),
+                }
+            })
+            .min()
+            .unwrap_or_else(Self::lowest)
+    }
+}
+
+/// Match a file path against a glob pattern
+pub fn glob_match(pattern: &str, target: &str) -> bool {
+    let (negated, body) = split_negation(pattern);
+
+    // A bare `*` has always meant "any file" in policy files, keep it that way
+    if body == "*" || body == "**" {
+        return !negated;
+    }
+
+    let body = normalize_path(body);
+    let target = normalize_path(target);
+    let target_segments: Vec<&str> = target.split('/').collect();
+
+    let matched = expand_braces(&body).iter().any(|alt| {
+        let segments: Vec<Segment> = alt.split('/').map(Segment::parse).collect();
+        match_segments(&segments, &target_segments)
+    });
+
+    matched != negated
+}
+
+/// Match a shell command against a command pattern
+pub fn command_match(pattern: &str, command: &str) -> bool {
+    let (negated, body) = split_negation(pattern.trim());
+    let command = normalize_command(command);
+    let body = normalize_command(body);
+
+    let matched = expand_braces(&body).iter().any(|alt| {
+        if match_tokens(&tokenize(alt), &command.chars().collect::<Vec<_>>()) {
+            return true;
+        }
+        // "cargo *" also covers a bare "cargo" with no arguments
+        alt.strip_suffix(" *").is_some_and(|prefix| prefix == command)
+    });
+
+    matched != negated
+}
+
+fn split_negation(pattern: &str) -> (bool, &str) {
+    match pattern.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut path = path.as_str();
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+    path.to_string()
+}
+
+fn normalize_command(command: &str) -> String {
+    command.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Expand `{a,b}` alternation (including nested groups) into plain patterns.
+/// Unbalanced braces are kept as literal characters.
+pub(crate) fn expand_braces(pattern: &str) -> Vec<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+
+    let Some(open) = chars.iter().position(|c| *c == '{') else {
+        return vec![pattern.to_string()];
+    };
+
+    let mut depth = 0;
+    let mut close = None;
+    let mut commas = Vec::new();
+    for (i, c) in chars.iter().enumerate().skip(open) {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => commas.push(i),
+            _ => {}
+        }
+    }
+
+    let Some(close) = close else {
+        return vec![pattern.to_string()];
+    };
+
+    let prefix: String = chars[..open].iter().collect();
+    let suffix: String = chars[close + 1..].iter().collect();
+
+    let mut bounds = vec![open];
+    bounds.extend(&commas);
+    bounds.push(close);
+
+    let mut expanded = Vec::new();
+    for window in bounds.windows(2) {
+        let alternative: String = chars[window[0] + 1..window[1]].iter().collect();
+        expanded.extend(expand_braces(&format!("{}{}{}", prefix, alternative, suffix)));
+    }
+    expanded
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(char),
+    AnyChar,
+    AnyRun,
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+impl Token {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Token::Literal(l) => *l == c,
+            Token::AnyChar | Token::AnyRun => true,
+            Token::Class { negated, ranges } => {
+                let hit = ranges.iter().any(|(lo, hi)| *lo <= c && c <= *hi);
+                hit != *negated
+            }
+        }
+    }
+}
+
+fn tokenize(pattern: &str) -> Vec<Token> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                // Consecutive stars inside a segment behave like a single one
+                if tokens.last() != Some(&Token::AnyRun) {
+                    tokens.push(Token::AnyRun);
+                }
+                i += 1;
+            }
+            '?' => {
+                tokens.push(Token::AnyChar);
+                i += 1;
+            }
+            '[' => match parse_class(&chars, i) {
+                Some((token, next)) => {
+                    tokens.push(token);
+                    i = next;
+                }
+                None => {
+                    tokens.push(Token::Literal('['));
+                    i += 1;
+                }
+            },
+            c => {
+                tokens.push(Token::Literal(c));
+                i += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Parse a `[...]` character class starting at `start`, returning the token
+/// and the index just past the closing bracket
+fn parse_class(chars: &[char], start: usize) -> Option<(Token, usize)> {
+    let mut i = start + 1;
+    let negated = matches!(chars.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut ranges = Vec::new();
+    let mut first = true;
+    while i < chars.len() {
+        let c = chars[i];
+        // A `]` right after the opening bracket is a literal member
+        if c == ']' && !first {
+            return Some((Token::Class { negated, ranges }, i + 1));
+        }
+        if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|e| *e != ']') {
+            ranges.push((c, chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+        first = false;
+    }
+
+    None
+}
+
+/// Match a token sequence against the full input
+fn match_tokens(tokens: &[Token], input: &[char]) -> bool {
+    let (mut t, mut s) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while s < input.len() {
+        match tokens.get(t) {
+            Some(Token::AnyRun) => {
+                backtrack = Some((t, s));
+                t += 1;
+            }
+            Some(token) if token.matches(input[s]) => {
+                t += 1;
+                s += 1;
+            }
+            _ => match backtrack {
+                Some((bt, bs)) => {
+                    t = bt + 1;
+                    s = bs + 1;
+                    backtrack = Some((bt, bs + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    tokens[t..].iter().all(|token| *token == Token::AnyRun)
+}
+
+enum Segment {
+    Globstar,
+    Pattern(Vec<Token>),
+}
+
+impl Segment {
+    fn parse(segment: &str) -> Self {
+        if segment == "**" {
+            Segment::Globstar
+        } else {
+            Segment::Pattern(tokenize(segment))
+        }
+    }
+}
+
+/// Match pattern segments against path segments, letting `**` absorb any
+/// number of whole segments
+fn match_segments(pattern: &[Segment], target: &[&str]) -> bool {
+    // reachable[j] is true when the pattern consumed so far can end right
+    // before target segment j
+    let mut reachable = vec![false; target.len() + 1];
+    reachable[0] = true;
+
+    for segment in pattern {
+        let mut next = vec![false; target.len() + 1];
+        match segment {
+            Segment::Globstar => {
+                let mut seen = false;
+                for j in 0..=target.len() {
+                    seen |= reachable[j];
+                    next[j] = seen;
+                }
+            }
+            Segment::Pattern(tokens) => {
+                for j in 0..target.len() {
+                    if reachable[j] {
+                        let chars: Vec<char> = target[j].chars().collect();
+                        next[j + 1] = match_tokens(tokens, &chars);
+                    }
+                }
+            }
+        }
+        reachable = next;
+    }
+
+    reachable[target.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("**/*.rs", "src/main.rs"));
+        assert!(glob_match("**/*.rs", "src/tools/system.rs"));
+        assert!(!glob_match("**/*.rs", "README.md"));
+        assert!(glob_match("src/**", "src/main.rs"));
+        assert!(glob_match("src/**", "src/tools/system.rs"));
+        assert!(glob_match("*.md", "README.md"));
+        assert!(!glob_match("*.md", "src/main.rs"));
+    }
+
+    #[test]
+    fn test_command_match() {
+        assert!(command_match("cargo *", "cargo build"));
+        assert!(command_match("cargo *", "cargo test --all"));
+        assert!(!command_match("cargo *", "rustc main.rs"));
+        assert!(command_match("*", "any command"));
+        assert!(command_match("cargo *", "cargo"));
+        assert!(!command_match("cargo *", "cargobuild"));
+        assert!(command_match("git {status,diff} *", "git diff --stat"));
+        assert!(!command_match("git {status,diff} *", "git push origin"));
+        assert!(command_match("ls  -la", "ls -la"));
+        assert!(command_match("!rm *", "ls -la"));
+        assert!(!command_match("!rm *", "rm -rf target"));
+    }
+
+    #[test]
+    fn test_glob_wildcards() {
+        assert!(glob_match("src/?.rs", "src/a.rs"));
+        assert!(!glob_match("src/?.rs", "src/ab.rs"));
+        assert!(glob_match("src/[a-c]*.rs", "src/build.rs"));
+        assert!(!glob_match("src/[a-c]*.rs", "src/main.rs"));
+        assert!(glob_match("src/[!a-c]*.rs", "src/main.rs"));
+        assert!(glob_match("*.{md,txt}", "NOTES.txt"));
+        assert!(!glob_match("*.{md,txt}", "main.rs"));
+        assert!(glob_match("{src,tests}/**/*.{rs,toml}", "tests/fixtures/data.toml"));
+        assert!(!glob_match("src/*.rs", "src/tools/system.rs"));
+        assert!(glob_match("./src/*.rs", "src/main.rs"));
+    }
+
+    #[test]
+    fn test_glob_multiple_globstars() {
+        assert!(glob_match("crates/**/src/**/*.rs", "crates/apchat-policy/src/lib.rs"));
+        assert!(glob_match("crates/**/src/**/*.rs", "crates/a/b/src/c/d/e.rs"));
+        assert!(!glob_match("crates/**/src/**/*.rs", "crates/a/tests/e.rs"));
+        assert!(glob_match("**/target/**", "crates/x/target/debug/app"));
+    }
+
+    #[test]
+    fn test_glob_negation() {
+        assert!(glob_match("!**/*.rs", "README.md"));
+        assert!(!glob_match("!**/*.rs", "src/main.rs"));
+        assert!(!glob_match("!*", "anything"));
+    }
+}
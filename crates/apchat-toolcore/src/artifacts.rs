@@ -0,0 +1,114 @@
+//! Per-session storage for tool output too large to put in the conversation.
+//!
+//! [`SpillMiddleware`](crate::SpillMiddleware) moves oversized output here
+//! and leaves the model an excerpt plus a handle; the `read_tool_output`
+//! tool pages through the stored output by that handle.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Directory of saved tool outputs, one file per handle
+#[derive(Debug)]
+pub struct ArtifactStore {
+    dir: PathBuf,
+    next: AtomicUsize,
+}
+
+impl ArtifactStore {
+    /// Store artifacts in `dir`, which is created on first use
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            next: AtomicUsize::new(1),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save the output of a tool call and return its handle
+    pub fn save(&self, tool: &str, content: &str) -> Result<String> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create artifact directory {}", self.dir.display()))?;
+        let tool: String = tool
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+            .collect();
+        let handle = format!("{}-{}", tool, self.next.fetch_add(1, Ordering::SeqCst));
+        let path = self.dir.join(format!("{}.txt", handle));
+        std::fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(handle)
+    }
+
+    /// File holding the output saved under `handle`
+    pub fn path(&self, handle: &str) -> Result<PathBuf> {
+        // Handles name files in the store and nothing else
+        let valid = !handle.is_empty()
+            && handle.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid {
+            anyhow::bail!("Invalid output handle '{}'", handle);
+        }
+        let path = self.dir.join(format!("{}.txt", handle));
+        if !path.is_file() {
+            anyhow::bail!("No tool output with handle '{}'", handle);
+        }
+        Ok(path)
+    }
+
+    /// Output saved under `handle`
+    pub fn read(&self, handle: &str) -> Result<String> {
+        let path = self.path(handle)?;
+        std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+}
+
+/// First and last lines of `content`, each part at most `budget / 2` bytes,
+/// with a marker for what was left out
+pub fn excerpt(content: &str, budget: usize) -> String {
+    let half = budget / 2;
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut head = Vec::new();
+    let mut used = 0;
+    for line in &lines {
+        if used + line.len() + 1 > half {
+            break;
+        }
+        used += line.len() + 1;
+        head.push(*line);
+    }
+
+    let mut tail = Vec::new();
+    used = 0;
+    for line in lines[head.len()..].iter().rev() {
+        if used + line.len() + 1 > half {
+            break;
+        }
+        used += line.len() + 1;
+        tail.push(*line);
+    }
+    tail.reverse();
+
+    let omitted = lines.len() - head.len() - tail.len();
+    if omitted == 0 {
+        return content.to_string();
+    }
+    // A single huge line would otherwise leave nothing to show
+    let head = if head.is_empty() {
+        format!("{}...", prefix(lines[0], half))
+    } else {
+        head.join("\n")
+    };
+    format!("{}\n[... {} line(s) omitted ...]\n{}", head, omitted, tail.join("\n"))
+}
+
+/// Longest prefix of `text` of at most `max` bytes that ends on a character
+fn prefix(text: &str, max: usize) -> &str {
+    let mut end = max.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
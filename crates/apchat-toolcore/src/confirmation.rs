@@ -0,0 +1,362 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use apchat_policy::{ActionType, GrantScope, RuleProposal};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Line prefix a subagent uses to escalate a confirmation request to its parent
+pub const ESCALATION_PREFIX: &str = "@@apchat-confirm ";
+
+/// A request to confirm an action that policy answered with `Ask`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmationRequest {
+    pub action: ActionType,
+    pub target: String,
+    pub prompt: String,
+    /// Longer detail for clients that can show it, e.g. the diff of an edit plan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    pub session_id: String,
+    /// Who is asking, e.g. the name of a subagent; `None` for the session itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+/// The answer to a confirmation request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmationResponse {
+    pub approved: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// How far the answer reaches; `None` for a plain yes or no, which is
+    /// only remembered in learn mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<GrantScope>,
+}
+
+impl ConfirmationResponse {
+    pub fn approve() -> Self {
+        Self {
+            approved: true,
+            reason: None,
+            scope: None,
+        }
+    }
+
+    pub fn deny(reason: Option<String>) -> Self {
+        Self {
+            approved: false,
+            reason,
+            scope: None,
+        }
+    }
+
+    pub fn with_scope(mut self, scope: GrantScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Parse a typed answer to a confirmation prompt.
+    ///
+    /// Accepts `y`/`yes` (or nothing), `n`/`no`, `o`/`once`, `s`/`session`,
+    /// `m <N>`/`minutes <N>`, `p <pattern>`/`pattern <pattern>`, `a`/`always`
+    /// and `d`/`deny always`. Returns `None` for anything else.
+    pub fn parse_answer(answer: &str) -> Option<Self> {
+        let answer = answer.trim();
+        let (command, argument) = match answer.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command.to_lowercase(), argument.trim()),
+            None => (answer.to_lowercase(), ""),
+        };
+
+        let response = match (command.as_str(), argument) {
+            ("" | "y" | "yes", "") => Self::approve(),
+            ("n" | "no", "") => Self::deny(None),
+            ("o" | "once", "") => Self::approve().with_scope(GrantScope::Once),
+            ("s" | "session", "") => Self::approve().with_scope(GrantScope::Session),
+            ("m" | "minutes", minutes) => match minutes.parse::<u64>() {
+                Ok(minutes) if minutes > 0 => Self::approve().with_scope(GrantScope::Minutes(minutes)),
+                _ => return None,
+            },
+            ("p" | "pattern", pattern) if !pattern.is_empty() => {
+                Self::approve().with_scope(GrantScope::Pattern(pattern.to_string()))
+            }
+            ("a" | "always", "") => Self::approve().with_scope(GrantScope::Always),
+            ("d", "") | ("deny", "always") => Self::deny(None).with_scope(GrantScope::Always),
+            _ => return None,
+        };
+        Some(response)
+    }
+}
+
+/// Answers `Ask` decisions on behalf of the user
+///
+/// Implementations decide how the question reaches a human: the terminal,
+/// a web socket, a parent process, or nobody at all.
+#[async_trait]
+pub trait ConfirmationProvider: Send + Sync + std::fmt::Debug {
+    /// Ask for confirmation of an action
+    async fn confirm(&self, request: &ConfirmationRequest) -> anyhow::Result<ConfirmationResponse>;
+
+    /// Whether answers come from a person; only those are learned as policy
+    fn is_interactive(&self) -> bool {
+        true
+    }
+
+    /// Pick which proposed rule to learn from a plain answer, or `None` to
+    /// learn nothing. Proposals run from the literal target to the broadest
+    /// generalization; by default the literal target is learned.
+    async fn choose_rule(
+        &self,
+        _request: &ConfirmationRequest,
+        proposals: &[RuleProposal],
+    ) -> anyhow::Result<Option<usize>> {
+        Ok(if proposals.is_empty() { None } else { Some(0) })
+    }
+}
+
+/// Prompts on the terminal and reads the answer from stdin
+#[derive(Debug, Default)]
+pub struct TerminalConfirmation;
+
+#[async_trait]
+impl ConfirmationProvider for TerminalConfirmation {
+    async fn confirm(&self, request: &ConfirmationRequest) -> anyhow::Result<ConfirmationResponse> {
+        use colored::Colorize;
+        use std::io::{self, BufRead, Write};
+
+        if let Some(ref origin) = request.origin {
+            println!("\n{} {}", "Request from".bright_black(), origin.cyan());
+        }
+        println!("\n{}", request.prompt.bright_green().bold());
+        println!(
+            "{}",
+            "[y]es, [n]o, [o]nce, [s]ession, [m]inutes <N>, [p]attern <glob>, [a]lways, [d]eny always".bright_black()
+        );
+
+        let stdin = io::stdin();
+        let mut handle = stdin.lock();
+        let response = loop {
+            print!(">>> ");
+            io::stdout().flush()?;
+
+            let mut answer = String::new();
+            if handle.read_line(&mut answer)? == 0 {
+                break ConfirmationResponse::deny(None);
+            }
+            match ConfirmationResponse::parse_answer(&answer) {
+                Some(response) => break response,
+                None => println!("{}", "Unrecognized answer, please try again.".bright_yellow()),
+            }
+        };
+        if response.approved {
+            return Ok(response);
+        }
+
+        // Ask for reason if rejected
+        println!("{}", "Why not? (optional - helps the AI understand):".bright_yellow());
+        print!(">>> ");
+        io::stdout().flush()?;
+
+        let mut reason = String::new();
+        let reason = match handle.read_line(&mut reason) {
+            Ok(_) if !reason.trim().is_empty() => Some(reason.trim().to_string()),
+            _ => None,
+        };
+
+        Ok(ConfirmationResponse { reason, ..response })
+    }
+
+    async fn choose_rule(
+        &self,
+        _request: &ConfirmationRequest,
+        proposals: &[RuleProposal],
+    ) -> anyhow::Result<Option<usize>> {
+        use colored::Colorize;
+        use std::io::{self, BufRead, Write};
+
+        if proposals.len() <= 1 {
+            return Ok(if proposals.is_empty() { None } else { Some(0) });
+        }
+
+        println!("{}", "📚 Remember this decision as:".bright_cyan());
+        for (i, proposal) in proposals.iter().enumerate() {
+            println!("  {}. {}", i + 1, proposal.rule.pattern.bright_white());
+            for conflict in &proposal.conflicts {
+                let line = format!("     {}", conflict);
+                if proposal.has_contradictions() {
+                    println!("{}", line.yellow());
+                } else {
+                    println!("{}", line.bright_black());
+                }
+            }
+        }
+        println!("{}", "[Enter] for 1, a number, or [n]one".bright_black());
+
+        let stdin = io::stdin();
+        let mut handle = stdin.lock();
+        loop {
+            print!(">>> ");
+            io::stdout().flush()?;
+
+            let mut answer = String::new();
+            if handle.read_line(&mut answer)? == 0 {
+                return Ok(None);
+            }
+            match answer.trim() {
+                "" => return Ok(Some(0)),
+                "n" | "none" => return Ok(None),
+                number => match number.parse::<usize>() {
+                    Ok(choice) if (1..=proposals.len()).contains(&choice) => return Ok(Some(choice - 1)),
+                    _ => println!("{}", "Unrecognized answer, please try again.".bright_yellow()),
+                },
+            }
+        }
+    }
+}
+
+/// Denies every request; used where nobody can answer
+#[derive(Debug)]
+pub struct AlwaysDenyConfirmation {
+    reason: String,
+}
+
+impl AlwaysDenyConfirmation {
+    pub fn new() -> Self {
+        Self::with_reason("No interactive confirmation available".to_string())
+    }
+
+    pub fn with_reason(reason: String) -> Self {
+        Self { reason }
+    }
+}
+
+impl Default for AlwaysDenyConfirmation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ConfirmationProvider for AlwaysDenyConfirmation {
+    async fn confirm(&self, _request: &ConfirmationRequest) -> anyhow::Result<ConfirmationResponse> {
+        Ok(ConfirmationResponse::deny(Some(self.reason.clone())))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+/// Replays a fixed list of answers and records every request, for tests
+#[derive(Debug, Default)]
+pub struct ScriptedConfirmation {
+    responses: Mutex<VecDeque<ConfirmationResponse>>,
+    requests: Mutex<Vec<ConfirmationRequest>>,
+    rule_choices: Mutex<VecDeque<Option<usize>>>,
+}
+
+impl ScriptedConfirmation {
+    pub fn new(responses: impl IntoIterator<Item = ConfirmationResponse>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into_iter().collect()),
+            requests: Mutex::new(Vec::new()),
+            rule_choices: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Answers to rule proposals in learn mode; the literal target is
+    /// learned once they run out
+    pub fn with_rule_choices(self, choices: impl IntoIterator<Item = Option<usize>>) -> Self {
+        *self.rule_choices.lock().unwrap() = choices.into_iter().collect();
+        self
+    }
+
+    /// Requests received so far, in order
+    pub fn requests(&self) -> Vec<ConfirmationRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl ConfirmationProvider for ScriptedConfirmation {
+    async fn confirm(&self, request: &ConfirmationRequest) -> anyhow::Result<ConfirmationResponse> {
+        self.requests.lock().unwrap().push(request.clone());
+        let response = self.responses.lock().unwrap().pop_front();
+        Ok(response.unwrap_or_else(|| ConfirmationResponse::deny(Some("No scripted response left".to_string()))))
+    }
+
+    async fn choose_rule(
+        &self,
+        _request: &ConfirmationRequest,
+        proposals: &[RuleProposal],
+    ) -> anyhow::Result<Option<usize>> {
+        let choice = self.rule_choices.lock().unwrap().pop_front();
+        Ok(choice.unwrap_or(Some(0)).filter(|i| *i < proposals.len()))
+    }
+}
+
+/// Forwards requests to a parent provider, tagging them with their origin
+#[derive(Debug)]
+pub struct EscalatingConfirmation {
+    origin: String,
+    parent: Arc<dyn ConfirmationProvider>,
+}
+
+impl EscalatingConfirmation {
+    pub fn new(origin: String, parent: Arc<dyn ConfirmationProvider>) -> Self {
+        Self { origin, parent }
+    }
+}
+
+#[async_trait]
+impl ConfirmationProvider for EscalatingConfirmation {
+    async fn confirm(&self, request: &ConfirmationRequest) -> anyhow::Result<ConfirmationResponse> {
+        let mut request = request.clone();
+        request.origin = Some(match request.origin.take() {
+            Some(inner) => format!("{} / {}", self.origin, inner),
+            None => self.origin.clone(),
+        });
+        self.parent.confirm(&request).await
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.parent.is_interactive()
+    }
+
+    async fn choose_rule(
+        &self,
+        request: &ConfirmationRequest,
+        proposals: &[RuleProposal],
+    ) -> anyhow::Result<Option<usize>> {
+        self.parent.choose_rule(request, proposals).await
+    }
+}
+
+/// Escalates requests from a subagent process to the process that launched it.
+///
+/// Each request is written to stderr as a single line starting with
+/// [`ESCALATION_PREFIX`]; the parent answers with one JSON line on stdin.
+#[derive(Debug, Default)]
+pub struct StdioConfirmation;
+
+#[async_trait]
+impl ConfirmationProvider for StdioConfirmation {
+    async fn confirm(&self, request: &ConfirmationRequest) -> anyhow::Result<ConfirmationResponse> {
+        let line = format!("{}{}", ESCALATION_PREFIX, serde_json::to_string(request)?);
+
+        tokio::task::spawn_blocking(move || {
+            use std::io::{BufRead, Write};
+
+            let mut stderr = std::io::stderr().lock();
+            writeln!(stderr, "{}", line)?;
+            stderr.flush()?;
+
+            let mut answer = String::new();
+            if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+                return Ok(ConfirmationResponse::deny(Some("Parent session closed".to_string())));
+            }
+            Ok(serde_json::from_str(answer.trim())?)
+        })
+        .await?
+    }
+}
@@ -5,10 +5,23 @@
 
 pub mod tool;
 pub mod tool_registry;
+pub mod tool_profiles;
+pub mod tool_search;
 pub mod tool_context;
-pub mod tool_parsing;
+pub mod confirmation;
+pub mod workspace;
+pub mod validation;
+pub mod middleware;
+pub mod artifacts;
 
 pub use tool::*;
 pub use tool_registry::*;
+pub use tool_profiles::*;
+pub use tool_search::*;
 pub use tool_context::*;
-pub use tool_parsing::*;
+pub use apchat_models::tool_parsing::{self, *};
+pub use confirmation::*;
+pub use workspace::*;
+pub use validation::*;
+pub use middleware::*;
+pub use artifacts::*;
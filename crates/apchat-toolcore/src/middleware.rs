@@ -0,0 +1,248 @@
+//! Hooks that run around every tool call made through a
+//! [`ToolRegistry`](crate::ToolRegistry).
+//!
+//! Middleware is called in registration order before a tool runs and in
+//! reverse order afterwards, so the first middleware registered sees the
+//! call first and the result last. Cross-cutting behavior such as timing,
+//! output limits and redaction is implemented once here and applies to the
+//! REPL, web, task and agent paths alike.
+
+use async_trait::async_trait;
+use regex::Regex;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::artifacts::excerpt;
+use crate::tool::{ToolParameters, ToolResult};
+use crate::tool_context::ToolContext;
+
+/// A tool call on its way through the middleware chain
+#[derive(Debug, Clone)]
+pub struct ToolInvocation {
+    /// Name of the tool to run; middleware may redirect the call
+    pub tool: String,
+    pub params: ToolParameters,
+    /// The model's id for the call, when it came from a model response
+    pub id: Option<String>,
+    started: Instant,
+}
+
+impl ToolInvocation {
+    pub fn new(tool: &str, params: ToolParameters) -> Self {
+        Self {
+            tool: tool.to_string(),
+            params,
+            id: None,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Restart the clock, for calls that waited in a queue before running
+    pub(crate) fn restart(&mut self) {
+        self.started = Instant::now();
+    }
+
+    /// Time since the call entered the chain
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// What a middleware decides before a tool runs
+#[derive(Debug, Clone)]
+pub enum MiddlewareAction {
+    /// Pass the (possibly rewritten) call on
+    Continue,
+    /// Do not run the tool; use this result instead
+    Veto(Box<ToolResult>),
+}
+
+/// A hook around tool execution
+#[async_trait]
+pub trait ToolMiddleware: Send + Sync {
+    /// Name used in logs and diagnostics
+    fn name(&self) -> &str;
+
+    /// Inspect or rewrite a call before it runs, or veto it
+    async fn before(&self, _call: &mut ToolInvocation, _context: &ToolContext) -> MiddlewareAction {
+        MiddlewareAction::Continue
+    }
+
+    /// Observe or transform the result of a call. Runs for every call whose
+    /// `before` hook ran, including vetoed and failed ones.
+    async fn after(&self, _call: &ToolInvocation, _context: &ToolContext, _result: &mut ToolResult) {}
+}
+
+/// Reports tool calls that take longer than a threshold
+pub struct TimingMiddleware {
+    threshold: Duration,
+}
+
+impl TimingMiddleware {
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for TimingMiddleware {
+    fn name(&self) -> &str {
+        "timing"
+    }
+
+    async fn after(&self, call: &ToolInvocation, _context: &ToolContext, _result: &mut ToolResult) {
+        let elapsed = call.elapsed();
+        if elapsed >= self.threshold {
+            eprintln!("⏱️  {} took {:.1}s", call.tool, elapsed.as_secs_f64());
+        }
+    }
+}
+
+/// Cuts tool output down to a maximum number of characters
+pub struct OutputLimitMiddleware {
+    max_chars: usize,
+}
+
+impl OutputLimitMiddleware {
+    pub fn new(max_chars: usize) -> Self {
+        Self { max_chars }
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for OutputLimitMiddleware {
+    fn name(&self) -> &str {
+        "output_limit"
+    }
+
+    async fn after(&self, _call: &ToolInvocation, _context: &ToolContext, result: &mut ToolResult) {
+        let total = result.content.chars().count();
+        if total > self.max_chars {
+            let kept: String = result.content.chars().take(self.max_chars).collect();
+            result.content = format!("{}\n[output truncated: {} of {} characters shown]", kept, self.max_chars, total);
+            result.metadata.truncated = true;
+        }
+    }
+}
+
+/// Name of the tool that pages through spilled output
+pub const READ_TOOL_OUTPUT: &str = "read_tool_output";
+
+/// Moves output larger than a limit into the session's artifact store and
+/// leaves the model the first and last lines plus a handle to read the rest
+/// with `read_tool_output`. Does nothing when the context has no store.
+pub struct SpillMiddleware {
+    max_bytes: usize,
+}
+
+impl SpillMiddleware {
+    pub fn new(max_bytes: usize) -> Self {
+        Self { max_bytes }
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for SpillMiddleware {
+    fn name(&self) -> &str {
+        "spill"
+    }
+
+    async fn after(&self, call: &ToolInvocation, context: &ToolContext, result: &mut ToolResult) {
+        if result.content.len() <= self.max_bytes {
+            return;
+        }
+        let Some(ref store) = context.artifacts else {
+            return;
+        };
+        let handle = match store.save(&call.tool, &result.content) {
+            Ok(handle) => handle,
+            Err(e) => {
+                eprintln!("⚠️  Could not save output of {}: {:#}", call.tool, e);
+                return;
+            }
+        };
+
+        let total_bytes = result.content.len();
+        let total_lines = result.content.lines().count();
+        result.content = format!(
+            "{}\n\n[Output was {} bytes in {} lines; this is an excerpt. The full output is saved as \
+             handle '{}'. Call {} with this handle to page through, grep or tail it.]",
+            excerpt(&result.content, self.max_bytes),
+            total_bytes,
+            total_lines,
+            handle,
+            READ_TOOL_OUTPUT
+        );
+        result.metadata.truncated = true;
+        if let Ok(path) = store.path(&handle) {
+            result.metadata.artifacts.push(path);
+        }
+    }
+}
+
+/// Masks secrets in tool output before the model sees it
+pub struct RedactionMiddleware {
+    patterns: Vec<(String, Regex)>,
+    exempt: HashSet<String>,
+}
+
+impl RedactionMiddleware {
+    /// Redact every match of the given named patterns
+    pub fn new(patterns: Vec<(String, Regex)>) -> Self {
+        Self {
+            patterns,
+            exempt: HashSet::new(),
+        }
+    }
+
+    /// Redact the secrets the builtin content rules keep out of files
+    pub fn secrets() -> Self {
+        let patterns = apchat_policy::builtin_content_rules()
+            .into_iter()
+            .filter(|rule| rule.actions.contains(&apchat_policy::ActionType::FileWrite))
+            .filter_map(|rule| Regex::new(&rule.pattern).ok().map(|regex| (rule.name, regex)))
+            .collect();
+        Self::new(patterns)
+    }
+
+    /// Leave the output of these tools alone. File contents must reach the
+    /// model as they are, or edits made from them write the placeholders back.
+    pub fn with_exempt_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exempt.extend(tools.into_iter().map(Into::into));
+        self
+    }
+
+    fn redact(&self, text: &mut String) {
+        for (name, regex) in &self.patterns {
+            if regex.is_match(text) {
+                *text = regex.replace_all(text, format!("[REDACTED {}]", name)).into_owned();
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ToolMiddleware for RedactionMiddleware {
+    fn name(&self) -> &str {
+        "redaction"
+    }
+
+    async fn after(&self, call: &ToolInvocation, _context: &ToolContext, result: &mut ToolResult) {
+        if self.exempt.contains(&call.tool) {
+            return;
+        }
+        self.redact(&mut result.content);
+        if let Some(ref mut error) = result.error {
+            self.redact(error);
+        }
+    }
+}
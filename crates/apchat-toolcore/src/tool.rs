@@ -1,65 +1,58 @@
-use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use async_trait::async_trait;
 
-/// Tool parameters
-#[derive(Debug, Clone)]
-pub struct ToolParameters {
-    pub data: HashMap<String, Value>,
-}
-
-impl ToolParameters {
-    pub fn new() -> Self {
-        Self {
-            data: HashMap::new(),
-        }
-    }
-
-    pub fn from_json(json_str: &str) -> Result<Self> {
-        let data: HashMap<String, Value> = serde_json::from_str(json_str)?;
-        Ok(Self { data })
-    }
-
-    pub fn set<T: Serialize>(&mut self, key: &str, value: T) {
-        if let Ok(json_value) = serde_json::to_value(value) {
-            self.data.insert(key.to_string(), json_value);
-        }
-    }
-
-    pub fn get_required<T>(&self, key: &str) -> Result<T>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        let value = self.data.get(key)
-            .ok_or_else(|| anyhow::anyhow!("Required parameter '{}' missing", key))?;
-
-        serde_json::from_value(value.clone())
-            .map_err(|e| anyhow::anyhow!("Failed to parse parameter '{}': {}", key, e))
-    }
-
-    pub fn get_optional<T>(&self, key: &str) -> Result<Option<T>>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        match self.data.get(key) {
-            Some(value) => {
-                let parsed: T = serde_json::from_value(value.clone())
-                    .map_err(|e| anyhow::anyhow!("Failed to parse parameter '{}': {}", key, e))?;
-                Ok(Some(parsed))
-            }
-            None => Ok(None),
-        }
-    }
-}
+use apchat_models::tool_args::schema_properties;
+pub use apchat_models::tool_args::{ParameterDefinition, ToolArgs, ToolParameters};
 
 /// Tool execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResult {
     pub success: bool,
+    /// Output sent to the model
     pub content: String,
     pub error: Option<String>,
+    /// Shorter rendering of `content` shown to the user, when they differ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+    #[serde(default)]
+    pub metadata: ToolMetadata,
+}
+
+/// Facts about a tool call, so consumers do not have to parse them out of
+/// the output
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolMetadata {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_read: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_written: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_deleted: Vec<PathBuf>,
+    /// Exit code of the process the tool ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Whether `content` holds less than the tool produced
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_read: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_written: Option<u64>,
+    /// Wall-clock time of the call; filled in by the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Files holding output that did not fit in `content`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<PathBuf>,
+}
+
+impl ToolMetadata {
+    /// Files the call wrote or deleted
+    pub fn files_changed(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files_written.iter().chain(&self.files_deleted)
+    }
 }
 
 impl ToolResult {
@@ -68,6 +61,8 @@ impl ToolResult {
             success: true,
             content,
             error: None,
+            display: None,
+            metadata: ToolMetadata::default(),
         }
     }
 
@@ -76,17 +71,65 @@ impl ToolResult {
             success: false,
             content: String::new(),
             error: Some(error),
+            display: None,
+            metadata: ToolMetadata::default(),
+        }
+    }
+
+    /// Show `display` to the user instead of the model's content
+    pub fn with_display(mut self, display: String) -> Self {
+        self.display = Some(display);
+        self
+    }
+
+    pub fn with_file_read(mut self, path: impl Into<PathBuf>, bytes: u64) -> Self {
+        self.metadata.files_read.push(path.into());
+        self.metadata.bytes_read = Some(self.metadata.bytes_read.unwrap_or(0) + bytes);
+        self
+    }
+
+    pub fn with_file_written(mut self, path: impl Into<PathBuf>, bytes: u64) -> Self {
+        self.metadata.files_written.push(path.into());
+        self.metadata.bytes_written = Some(self.metadata.bytes_written.unwrap_or(0) + bytes);
+        self
+    }
+
+    pub fn with_file_deleted(mut self, path: impl Into<PathBuf>) -> Self {
+        self.metadata.files_deleted.push(path.into());
+        self
+    }
+
+    pub fn with_exit_code(mut self, exit_code: i32) -> Self {
+        self.metadata.exit_code = Some(exit_code);
+        self
+    }
+
+    pub fn with_artifact(mut self, path: impl Into<PathBuf>) -> Self {
+        self.metadata.artifacts.push(path.into());
+        self
+    }
+
+    /// What to show the user for this result
+    pub fn display_content(&self) -> &str {
+        match (&self.display, &self.error) {
+            (Some(display), _) => display,
+            (None, Some(error)) if !self.success => error,
+            _ => &self.content,
         }
     }
 }
 
-/// Tool parameter definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ParameterDefinition {
-    pub param_type: String,
-    pub description: String,
-    pub required: bool,
-    pub default: Option<Value>,
+/// What a tool call can change, which decides whether it may run alongside
+/// other calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SideEffects {
+    /// Only reads files or state; safe to run concurrently with other reads
+    ReadOnly,
+    /// Changes files or state in the workspace that later calls may read
+    MutatesWorkspace,
+    /// Acts outside the workspace: processes, terminals, the network, the user
+    External,
 }
 
 /// Tool trait that all tools must implement
@@ -104,42 +147,27 @@ pub trait Tool: Send + Sync {
     /// Execute the tool
     async fn execute(&self, params: ToolParameters, context: &crate::tool_context::ToolContext) -> ToolResult;
 
-    /// Get OpenAI-compatible tool definition
-    fn to_openai_definition(&self) -> serde_json::Value {
-        let mut properties = serde_json::Map::new();
-        let mut required = Vec::new();
-
-        for (name, param_def) in self.parameters() {
-            // Build parameter definition with sorted keys
-            let mut param_obj = serde_json::Map::new();
-            if let Some(default) = &param_def.default {
-                param_obj.insert("default".to_string(), default.clone());
-            }
-            param_obj.insert("description".to_string(), serde_json::Value::String(param_def.description.clone()));
-            param_obj.insert("type".to_string(), serde_json::Value::String(param_def.param_type.clone()));
-
-            properties.insert(name.clone(), serde_json::Value::Object(param_obj));
-
-            if param_def.required {
-                required.push(name);
-            }
-        }
+    /// What calls of this tool can change. Tools that do not say are assumed
+    /// to act externally and never run concurrently.
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::External
+    }
 
-        // Sort required array alphabetically for consistent caching
-        required.sort();
+    /// How long a call may run before it is cancelled, unless the registry
+    /// is configured otherwise. `None` means no limit.
+    fn default_timeout(&self) -> Option<std::time::Duration> {
+        None
+    }
 
-        // Build properties in sorted order
-        let mut sorted_properties = serde_json::Map::new();
-        let mut prop_keys: Vec<_> = properties.keys().cloned().collect();
-        prop_keys.sort();
-        for key in prop_keys {
-            sorted_properties.insert(key.clone(), properties[&key].clone());
-        }
+    /// Get OpenAI-compatible tool definition
+    fn to_openai_definition(&self) -> serde_json::Value {
+        // Properties and the required list are sorted for consistent caching
+        let (properties, required) = schema_properties(&self.parameters());
 
         // Build parameters object with sorted keys
         let mut parameters = serde_json::Map::new();
-        parameters.insert("properties".to_string(), serde_json::Value::Object(sorted_properties));
-        parameters.insert("required".to_string(), serde_json::Value::Array(required.into_iter().map(serde_json::Value::String).collect()));
+        parameters.insert("properties".to_string(), properties);
+        parameters.insert("required".to_string(), required);
         parameters.insert("type".to_string(), serde_json::Value::String("object".to_string()));
 
         // Build function object with sorted keys
@@ -160,6 +188,9 @@ pub trait Tool: Send + Sync {
 /// Helper macro for creating parameter definitions
 #[macro_export]
 macro_rules! param {
+    ($name:expr, $definition:expr) => {
+        ($name.to_string(), $definition)
+    };
     ($name:expr, $type:expr, $desc:expr, required) => {
         (
             $name.to_string(),
@@ -168,6 +199,7 @@ macro_rules! param {
                 description: $desc.to_string(),
                 required: true,
                 default: None,
+                ..Default::default()
             }
         )
     };
@@ -179,6 +211,7 @@ macro_rules! param {
                 description: $desc.to_string(),
                 required: false,
                 default: Some(serde_json::Value::from($default)),
+                ..Default::default()
             }
         )
     };
@@ -190,6 +223,7 @@ macro_rules! param {
                 description: $desc.to_string(),
                 required: false,
                 default: None,
+                ..Default::default()
             }
         )
     };
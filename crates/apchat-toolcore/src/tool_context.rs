@@ -1,11 +1,17 @@
 use std::path::PathBuf;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use apchat_policy::PolicyManager;
 use apchat_terminal::TerminalManager;
 use apchat_skills::SkillRegistry;
 use apchat_todo::TodoManager;
+use crate::confirmation::{AlwaysDenyConfirmation, ConfirmationProvider, ConfirmationRequest, TerminalConfirmation};
+use crate::artifacts::ArtifactStore;
+use crate::tool_search::ToolSearch;
+use crate::workspace::{resolve_path, ResolvedPath};
 
 /// Tool execution context
 ///
@@ -18,7 +24,14 @@ use apchat_todo::TodoManager;
 /// - Skill registry for accessing skills
 /// - Todo manager for task tracking
 /// - Non-interactive flag for web/API mode
+/// - Confirmation provider that answers `Ask` policy decisions
 /// - Current model string for subagent spawning (formatted as "modname@backend(url)")
+/// - Tool profile the session runs with, passed on to subagents
+/// - Directories outside the work directory that file tools may also access
+/// - Cancellation token and deadline of the current call; long-running tools
+///   should stop, and clean up what they started, when the token is cancelled
+/// - Artifact store holding tool output too large for the conversation
+/// - Tool search state, set by the registry when tool search is on
 #[derive(Debug, Clone)]
 pub struct ToolContext {
     pub work_dir: PathBuf,
@@ -29,11 +42,20 @@ pub struct ToolContext {
     pub skill_registry: Option<Arc<SkillRegistry>>,
     pub todo_manager: Option<Arc<TodoManager>>,
     pub non_interactive: bool,
+    pub confirmation_provider: Option<Arc<dyn ConfirmationProvider>>,
     pub current_model_string: Option<String>,
+    pub tool_profile: Option<String>,
+    pub allowed_roots: Vec<PathBuf>,
+    pub cancellation: CancellationToken,
+    pub deadline: Option<Arc<Deadline>>,
+    pub artifacts: Option<Arc<ArtifactStore>>,
+    pub tool_search: Option<Arc<ToolSearch>>,
 }
 
 impl ToolContext {
     pub fn new(work_dir: PathBuf, session_id: String, policy_manager: PolicyManager) -> Self {
+        // Audit entries written through this context are tagged with its session
+        let policy_manager = policy_manager.for_session(&session_id);
         Self {
             work_dir,
             session_id,
@@ -43,7 +65,14 @@ impl ToolContext {
             skill_registry: None,
             todo_manager: None,
             non_interactive: false,
+            confirmation_provider: None,
             current_model_string: None,
+            tool_profile: None,
+            allowed_roots: Vec::new(),
+            cancellation: CancellationToken::new(),
+            deadline: None,
+            artifacts: None,
+            tool_search: None,
         }
     }
 
@@ -72,80 +101,256 @@ impl ToolContext {
         self
     }
 
+    pub fn with_confirmation_provider(mut self, provider: Arc<dyn ConfirmationProvider>) -> Self {
+        self.confirmation_provider = Some(provider);
+        self
+    }
+
+    /// The provider that answers `Ask` decisions: the configured one, otherwise
+    /// the terminal, or nobody at all in non-interactive mode
+    pub fn confirmation_provider(&self) -> Arc<dyn ConfirmationProvider> {
+        match self.confirmation_provider {
+            Some(ref provider) => Arc::clone(provider),
+            None if self.non_interactive => Arc::new(AlwaysDenyConfirmation::new()),
+            None => Arc::new(TerminalConfirmation),
+        }
+    }
+
     pub fn with_current_model_string(mut self, model_string: String) -> Self {
         self.current_model_string = Some(model_string);
         self
     }
 
+    /// Name of the tool profile the session runs with
+    pub fn with_tool_profile(mut self, profile: String) -> Self {
+        self.tool_profile = Some(profile);
+        self
+    }
+
+    /// Let file tools access paths under these directories as well as the work directory
+    pub fn with_allowed_roots(mut self, allowed_roots: Vec<PathBuf>) -> Self {
+        self.allowed_roots = allowed_roots;
+        self
+    }
+
+    /// Keep oversized tool output of this session in `store`
+    pub fn with_artifact_store(mut self, store: Arc<ArtifactStore>) -> Self {
+        self.artifacts = Some(store);
+        self
+    }
+
+    /// Cancel tool calls made with this context when `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Whether the current call should stop, because it was cancelled or
+    /// ran out of time
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Resolve a tool-supplied path, refusing paths that escape the workspace
+    pub fn resolve_path(&self, path: &str) -> anyhow::Result<ResolvedPath> {
+        resolve_path(&self.work_dir, &self.allowed_roots, path)
+    }
+
     /// Check if an action is permitted by the policy
     /// Returns (approved: bool, rejection_reason: Option<String>)
-    pub fn check_permission(
+    pub async fn check_permission(
         &self,
         action: apchat_policy::ActionType,
         target: &str,
         prompt_message: &str,
     ) -> anyhow::Result<(bool, Option<String>)> {
-        use apchat_policy::Decision;
-        use colored::Colorize;
-        use std::io::{self, BufRead, Write};
+        let policy_decision = self.policy_manager.decide(&action, target);
+        self.resolve_decision(action, target, prompt_message, policy_decision).await
+    }
 
-        let decision = self.policy_manager.evaluate(&action, target);
+    /// Like [`check_permission`](Self::check_permission), but content rules
+    /// also inspect the payload, e.g. the text about to be written
+    pub async fn check_permission_with_content(
+        &self,
+        action: apchat_policy::ActionType,
+        target: &str,
+        content: &str,
+        prompt_message: &str,
+    ) -> anyhow::Result<(bool, Option<String>)> {
+        let policy_decision = self.policy_manager.decide_with_content(&action, target, content);
+        self.resolve_decision(action, target, prompt_message, policy_decision).await
+    }
 
-        match decision {
+    /// Check a payload against content rules only, for tools that have
+    /// already checked their target. Approves when no content rule matches.
+    pub async fn check_content(
+        &self,
+        action: apchat_policy::ActionType,
+        target: &str,
+        content: &str,
+        prompt_message: &str,
+    ) -> anyhow::Result<(bool, Option<String>)> {
+        match self.policy_manager.inspect_content(&action, target, content) {
+            Some(policy_decision) => self.resolve_decision(action, target, prompt_message, policy_decision).await,
+            None => Ok((true, None)),
+        }
+    }
+
+    /// Check a file read. Only a matching `file_read` rule refuses or asks
+    /// about a read; without one the read is approved whatever the policy
+    /// default, so that a default of `ask` does not prompt for every file.
+    pub async fn check_read(&self, target: &str, prompt_message: &str) -> anyhow::Result<(bool, Option<String>)> {
+        let action = apchat_policy::ActionType::FileRead;
+        let policy_decision = self.policy_manager.decide(&action, target);
+        match policy_decision.rule {
+            Some(_) => self.resolve_decision(action, target, prompt_message, policy_decision).await,
+            None => Ok((true, None)),
+        }
+    }
+
+    /// Act on a decision the tool has already asked the policy for: approve,
+    /// refuse or ask the user. Lets a tool inspect the decision first without
+    /// deciding (and auditing) the same action twice.
+    pub async fn resolve_decision(
+        &self,
+        action: apchat_policy::ActionType,
+        target: &str,
+        prompt_message: &str,
+        policy_decision: apchat_policy::PolicyDecision,
+    ) -> anyhow::Result<(bool, Option<String>)> {
+        use apchat_policy::Decision;
+
+        match policy_decision.decision {
             Decision::Allow => Ok((true, None)),
-            Decision::Deny => Ok((false, Some("Denied by policy".to_string()))),
+            Decision::Deny => Ok((false, Some(match policy_decision.reason() {
+                Some(reason) => format!("Denied by policy: {} ({})", reason, policy_decision.source()),
+                None => format!("Denied by policy ({})", policy_decision.source()),
+            }))),
             Decision::Ask => {
-                // In non-interactive mode (web/API), auto-approve since confirmation
-                // was already handled via web UI
-                if self.non_interactive {
-                    println!("{} {}", "✓".green(), "Auto-confirmed (web UI)".bright_black());
-                    return Ok((true, None));
-                }
+                let provider = self.confirmation_provider();
+                let prompt = match policy_decision.reason() {
+                    Some(reason) => format!("{}\n⚠️  {}", prompt_message, reason),
+                    None => prompt_message.to_string(),
+                };
+                let request = ConfirmationRequest {
+                    action: action.clone(),
+                    target: target.to_string(),
+                    prompt,
+                    preview: None,
+                    session_id: self.session_id.clone(),
+                    origin: None,
+                };
+                // Waiting for the user does not count against the deadline
+                let _paused = self.deadline.as_ref().map(|deadline| deadline.pause());
+                let response = provider.confirm(&request).await?;
+                self.policy_manager.record_response(
+                    &action,
+                    target,
+                    response.approved,
+                    response.reason.clone(),
+                    response.scope.clone(),
+                );
 
-                // Ask the user for confirmation in interactive mode
-                println!("\n{}", prompt_message.bright_green().bold());
-                print!(">>> ");
-                io::stdout().flush()?;
-
-                let stdin = io::stdin();
-                let mut handle = stdin.lock();
-                let mut response = String::new();
-                handle.read_line(&mut response)?;
-
-                let response = response.trim();
-                let response_lower = response.to_lowercase();
-                let approved = response_lower.is_empty() || response_lower == "y" || response_lower == "yes";
-
-                let rejection_reason = if !approved {
-                    // Ask for reason if rejected
-                    println!("{}", "Why not? (optional - helps the AI understand):".bright_yellow());
-                    print!(">>> ");
-                    io::stdout().flush()?;
-
-                    let mut reason = String::new();
-                    match handle.read_line(&mut reason) {
-                        Ok(_) => {
-                            let reason = reason.trim();
-                            if reason.is_empty() {
-                                None
-                            } else {
-                                Some(reason.to_string())
+                // Remember the user's decision: as far as they asked for, or
+                // permanently if learning is enabled and they gave a plain answer.
+                // An answer about one payload says nothing about the target.
+                if provider.is_interactive() && policy_decision.content_rule.is_none() {
+                    let decision = if response.approved { Decision::Allow } else { Decision::Deny };
+                    match response.scope {
+                        Some(ref scope) => {
+                            if let Err(e) = self.policy_manager.grant(action, target, decision, scope, response.reason.clone()) {
+                                eprintln!("⚠️  Failed to record grant: {}", e);
+                            }
+                        }
+                        None if self.policy_manager.is_learning() => {
+                            // Offer generalizations of the target rather than
+                            // learning only the literal one
+                            let proposals = self.policy_manager.propose_rules(&action, target, decision);
+                            if let Some(choice) = provider.choose_rule(&request, &proposals).await? {
+                                let rule = proposals[choice].rule.clone();
+                                if let Err(e) = self.policy_manager.learn_rule(rule, response.reason.clone()) {
+                                    eprintln!("⚠️  Failed to learn policy: {}", e);
+                                }
                             }
                         }
-                        Err(_) => None,
+                        None => {}
                     }
-                } else {
-                    None
-                };
-
-                // Learn from the user's decision if learning is enabled
-                if self.policy_manager.is_learning() {
-                    let decision = if approved { Decision::Allow } else { Decision::Deny };
-                    let _ = self.policy_manager.learn(action, target.to_string(), decision, rejection_reason.clone());
                 }
 
-                Ok((approved, rejection_reason))
+                Ok((response.approved, response.reason))
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Time limit of one tool call. Time spent waiting for the user to answer
+/// a confirmation does not count against it.
+#[derive(Debug)]
+pub struct Deadline {
+    limit: Duration,
+    started: Instant,
+    waiting: std::sync::Mutex<Waiting>,
+}
+
+#[derive(Debug, Default)]
+struct Waiting {
+    total: Duration,
+    since: Option<Instant>,
+    depth: usize,
+}
+
+impl Deadline {
+    pub fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            started: Instant::now(),
+            waiting: std::sync::Mutex::new(Waiting::default()),
+        }
+    }
+
+    pub fn limit(&self) -> Duration {
+        self.limit
+    }
+
+    /// Time counted against the limit so far
+    pub fn elapsed(&self) -> Duration {
+        let waiting = self.waiting.lock().unwrap();
+        let waited = waiting.total + waiting.since.map(|since| since.elapsed()).unwrap_or_default();
+        self.started.elapsed().saturating_sub(waited)
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.limit.saturating_sub(self.elapsed())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Stop the clock until the returned guard is dropped
+    pub fn pause(&self) -> DeadlinePause<'_> {
+        let mut waiting = self.waiting.lock().unwrap();
+        if waiting.depth == 0 {
+            waiting.since = Some(Instant::now());
+        }
+        waiting.depth += 1;
+        DeadlinePause { deadline: self }
+    }
+}
+
+/// Keeps a [`Deadline`] paused while alive
+pub struct DeadlinePause<'a> {
+    deadline: &'a Deadline,
+}
+
+impl Drop for DeadlinePause<'_> {
+    fn drop(&mut self) {
+        let mut waiting = self.deadline.waiting.lock().unwrap();
+        waiting.depth -= 1;
+        if waiting.depth == 0 {
+            if let Some(since) = waiting.since.take() {
+                waiting.total += since.elapsed();
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Named sets of tools a session offers the model, selected by tool name or
+//! registry category. Custom profiles are read from `tool_profiles.toml`:
+//!
+//! ```toml
+//! [profiles.review]
+//! include = ["file_ops", "search", "todo_list"]
+//! exclude = ["write_file"]
+//! read_only = true
+//! ```
+//!
+//! The user-global file (`~/.okaychat/tool_profiles.toml`) and the project
+//! file (`.apchat/tool_profiles.toml`) are merged; a project profile replaces
+//! a global or built-in profile of the same name.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use super::tool::SideEffects;
+use super::tool_registry::ToolRegistry;
+
+/// Profile that enables every tool
+pub const FULL_PROFILE: &str = "full";
+
+/// Which tools a profile enables
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolProfile {
+    /// Tools and categories to enable; empty enables every tool
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Tools and categories to leave out
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Only enable tools that change nothing
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl ToolProfile {
+    pub fn with_include(mut self, selectors: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.include = selectors.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_exclude(mut self, selectors: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.exclude = selectors.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Names of the registered tools this profile enables
+    pub fn select(&self, registry: &ToolRegistry) -> BTreeSet<String> {
+        let mut selected: BTreeSet<String> = if self.include.is_empty() {
+            registry.get_tool_names().into_iter().collect()
+        } else {
+            self.include.iter().flat_map(|selector| registry.resolve_tools(selector)).collect()
+        };
+        for selector in &self.exclude {
+            for name in registry.resolve_tools(selector) {
+                selected.remove(&name);
+            }
+        }
+        if self.read_only {
+            selected.retain(|name| registry.side_effects(name) == SideEffects::ReadOnly);
+        }
+        selected
+    }
+}
+
+/// Profiles by name: the built-in ones plus those from config files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolProfiles {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ToolProfile>,
+}
+
+impl Default for ToolProfiles {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl ToolProfiles {
+    /// `full`, `readonly` (look around without changing anything), `edit`
+    /// (edit files but run no commands: no terminal, system, subagent,
+    /// custom or MCP tools) and `no-terminal` (everything but the PTY tools;
+    /// subagents are started with the same profile)
+    pub fn builtin() -> Self {
+        let profiles = BTreeMap::from([
+            (FULL_PROFILE.to_string(), ToolProfile::default()),
+            (
+                "readonly".to_string(),
+                ToolProfile::default()
+                    .with_include(["file_ops", "search", "skills", "task_tracking"])
+                    .with_read_only(true),
+            ),
+            (
+                "edit".to_string(),
+                ToolProfile::default().with_exclude(["terminal", "system", "agent_control", "custom", "mcp"]),
+            ),
+            ("no-terminal".to_string(), ToolProfile::default().with_exclude(["terminal"])),
+        ]);
+        Self { profiles }
+    }
+
+    /// Custom profiles in `text`, on top of the built-in ones
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut profiles = Self::builtin();
+        profiles.profiles.extend(toml::from_str::<Self>(text)?.profiles);
+        Ok(profiles)
+    }
+
+    /// Read a profiles file; a missing file adds no profiles
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut profiles = Self::builtin();
+        profiles.profiles.extend(Self::custom(path)?);
+        Ok(profiles)
+    }
+
+    /// Global profiles overlaid with the project profiles
+    pub fn layered(global: Option<&Path>, project: &Path) -> Result<Self> {
+        let mut profiles = match global {
+            Some(path) => Self::load(path)?,
+            None => Self::builtin(),
+        };
+        profiles.profiles.extend(Self::custom(project)?);
+        Ok(profiles)
+    }
+
+    fn custom(path: &Path) -> Result<BTreeMap<String, ToolProfile>> {
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let custom: Self = toml::from_str(&text).with_context(|| format!("Invalid tool profiles {}", path.display()))?;
+        Ok(custom.profiles)
+    }
+
+    /// Project profiles file (`.apchat/tool_profiles.toml`)
+    pub fn project_config_path(work_dir: &Path) -> PathBuf {
+        work_dir.join(".apchat").join("tool_profiles.toml")
+    }
+
+    pub fn get(&self, name: &str) -> Result<&ToolProfile> {
+        self.profiles.get(name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown tool profile '{}' (available: {})",
+                name,
+                self.names().join(", ")
+            )
+        })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+}
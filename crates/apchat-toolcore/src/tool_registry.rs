@@ -1,13 +1,44 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
-use super::tool::{Tool, ToolParameters, ToolResult};
-use super::tool_context::ToolContext;
+use std::time::Duration;
+use futures::stream::{self, StreamExt};
+use super::tool::{SideEffects, Tool, ToolParameters, ToolResult};
+use super::tool_context::{Deadline, ToolContext};
+use super::middleware::{MiddlewareAction, ToolInvocation, ToolMiddleware};
+use super::tool_profiles::ToolProfile;
+use super::tool_search::{ToolMatch, ToolSearch, SEARCH_TOOLS};
+use super::validation::{validate_arguments, ParameterPatterns};
 
 /// Registry for managing and discovering tools
 #[derive(Clone)]
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn Tool>>,
     categories: HashMap<String, Vec<String>>,
+    /// Parameter patterns of each tool, compiled when it is registered
+    patterns: HashMap<String, ParameterPatterns>,
+    /// Registered tools the model is not offered and cannot call
+    disabled: BTreeSet<String>,
+    /// When set, only the core tools and those found by searching are sent
+    tool_search: Option<Arc<ToolSearch>>,
+    middleware: Vec<Arc<dyn ToolMiddleware>>,
+    /// Timeouts overriding the tools' own defaults; `None` disables the limit
+    timeouts: HashMap<String, Option<Duration>>,
+    /// Timeout for tools that declare none
+    default_timeout: Option<Duration>,
+    /// How many read-only calls may run at the same time
+    parallelism: usize,
+}
+
+/// Read-only calls run at the same time unless configured otherwise
+const DEFAULT_PARALLELISM: usize = 4;
+
+/// How long a cancelled tool gets to stop and report partial results before
+/// it is abandoned
+const STOP_GRACE: Duration = Duration::from_secs(2);
+
+enum Stopped {
+    TimedOut(Duration),
+    Cancelled,
 }
 
 impl std::fmt::Debug for ToolRegistry {
@@ -15,6 +46,12 @@ impl std::fmt::Debug for ToolRegistry {
         f.debug_struct("ToolRegistry")
             .field("tool_count", &self.tools.len())
             .field("categories", &self.categories)
+            .field("disabled", &self.disabled)
+            .field("tool_search", &self.tool_search.as_ref().map(|search| search.core()))
+            .field("middleware", &self.middleware_names())
+            .field("timeouts", &self.timeouts)
+            .field("default_timeout", &self.default_timeout)
+            .field("parallelism", &self.parallelism)
             .finish()
     }
 }
@@ -24,14 +61,132 @@ impl ToolRegistry {
         Self {
             tools: HashMap::new(),
             categories: HashMap::new(),
+            patterns: HashMap::new(),
+            disabled: BTreeSet::new(),
+            tool_search: None,
+            middleware: Vec::new(),
+            timeouts: HashMap::new(),
+            default_timeout: None,
+            parallelism: DEFAULT_PARALLELISM,
+        }
+    }
+
+    /// Allow up to `limit` read-only calls at the same time; 1 runs every
+    /// call on its own
+    pub fn set_parallelism(&mut self, limit: usize) {
+        self.parallelism = limit.max(1);
+    }
+
+    pub fn parallelism(&self) -> usize {
+        self.parallelism
+    }
+
+    /// What calls of a tool can change; unknown tools count as external
+    pub fn side_effects(&self, name: &str) -> SideEffects {
+        self.get_tool(name)
+            .map(|tool| tool.side_effects())
+            .unwrap_or(SideEffects::External)
+    }
+
+    /// How many calls at the start of `names` are read-only and can run
+    /// together with [`execute_concurrently`](Self::execute_concurrently).
+    /// Returns 0 or 1 when concurrency would not help.
+    pub fn read_only_run<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> usize {
+        if self.parallelism == 1 {
+            return 0;
+        }
+        names
+            .into_iter()
+            .take_while(|name| self.side_effects(name) == SideEffects::ReadOnly)
+            .count()
+    }
+
+    /// Execute calls concurrently, at most [`parallelism`](Self::parallelism)
+    /// at a time. Results come back in call order. Only read-only calls
+    /// should be passed here; use [`read_only_run`](Self::read_only_run) to
+    /// find them.
+    pub async fn execute_concurrently(
+        &self,
+        calls: Vec<ToolInvocation>,
+        context: &ToolContext,
+    ) -> Vec<ToolResult> {
+        stream::iter(calls)
+            .map(|call| async move { self.execute(call, context).await })
+            .buffered(self.parallelism)
+            .collect()
+            .await
+    }
+
+    /// Limit a tool's calls to `timeout`, or lift its limit with `None`
+    pub fn set_timeout(&mut self, tool: &str, timeout: Option<Duration>) {
+        self.timeouts.insert(tool.to_string(), timeout);
+    }
+
+    /// Timeout for tools that do not declare their own
+    pub fn set_default_timeout(&mut self, timeout: Option<Duration>) {
+        self.default_timeout = timeout;
+    }
+
+    /// Apply a timeout spec such as `run_command=600,pty_get_screen=10,default=120`.
+    /// Values are seconds; `none` or `0` removes the limit.
+    pub fn configure_timeouts(&mut self, spec: &str) -> anyhow::Result<()> {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (tool, value) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid timeout '{}': expected tool=seconds", entry))?;
+            let timeout = match value.trim() {
+                "none" | "0" => None,
+                seconds => Some(Duration::from_secs(seconds.parse().map_err(|_| {
+                    anyhow::anyhow!("Invalid timeout '{}': '{}' is not a number of seconds", entry, seconds)
+                })?)),
+            };
+            match tool.trim() {
+                "default" => self.set_default_timeout(timeout),
+                tool => self.set_timeout(tool, timeout),
+            }
         }
+        Ok(())
+    }
+
+    /// Timeout that applies to calls of a tool
+    pub fn timeout_for(&self, name: &str) -> Option<Duration> {
+        match self.timeouts.get(name) {
+            Some(timeout) => *timeout,
+            None => self
+                .get_tool(name)
+                .and_then(|tool| tool.default_timeout())
+                .or(self.default_timeout),
+        }
+    }
+
+    /// Add a middleware to the end of the chain run around every tool call
+    pub fn add_middleware<M: ToolMiddleware + 'static>(&mut self, middleware: M) {
+        self.middleware.push(Arc::new(middleware));
     }
 
-    /// Register a new tool
+    /// Add a middleware to the start of the chain, so it sees each call
+    /// first and its final result last
+    pub fn prepend_middleware<M: ToolMiddleware + 'static>(&mut self, middleware: M) {
+        self.middleware.insert(0, Arc::new(middleware));
+    }
+
+    /// Names of the registered middleware, in call order
+    pub fn middleware_names(&self) -> Vec<String> {
+        self.middleware.iter().map(|m| m.name().to_string()).collect()
+    }
+
+    /// Register a new tool. Parameter patterns that are not valid regular
+    /// expressions are reported and not checked.
     pub fn register<T: Tool + 'static>(&mut self, tool: T) {
         let name = tool.name().to_string();
+        let patterns = ParameterPatterns::compile(&tool.parameters());
+        for error in patterns.invalid() {
+            eprintln!("⚠️  Tool '{}', parameter {}: {}", name, error.path, error.message);
+        }
+        self.patterns.insert(name.clone(), patterns);
         let tool_arc = Arc::new(tool);
         self.tools.insert(name.clone(), tool_arc);
+        self.refresh_tool_search();
     }
 
     /// Register a tool with categories
@@ -42,6 +197,7 @@ impl ToolRegistry {
         for category in categories {
             self.categories.entry(category).or_insert_with(Vec::new).push(name.clone());
         }
+        self.refresh_tool_search();
     }
 
     /// Get a tool by name
@@ -76,26 +232,198 @@ impl ToolRegistry {
         self.tools.keys().cloned().collect()
     }
 
-    /// Execute a tool by name
+    /// Offer a tool to the model or take it away. Returns false for unknown tools.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        if !self.has_tool(name) {
+            return false;
+        }
+        if enabled {
+            self.disabled.remove(name);
+        } else {
+            self.disabled.insert(name.to_string());
+        }
+        self.refresh_tool_search();
+        true
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.has_tool(name) && !self.disabled.contains(name)
+    }
+
+    /// Names of the enabled tools, sorted
+    pub fn enabled_tool_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.tools.keys().filter(|name| !self.disabled.contains(*name)).cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Enable exactly the tools `profile` selects. The tool search tool
+    /// stays enabled while tool search is on.
+    pub fn apply_profile(&mut self, profile: &ToolProfile) {
+        let mut selected = profile.select(self);
+        if self.tool_search.is_some() {
+            selected.insert(SEARCH_TOOLS.to_string());
+        }
+        self.disabled = self.tools.keys().filter(|name| !selected.contains(*name)).cloned().collect();
+        self.refresh_tool_search();
+    }
+
+    /// Send only the `core` tools and the search tool, which must be
+    /// registered, and let the model find the others
+    pub fn enable_tool_search(&mut self, core: impl IntoIterator<Item = impl Into<String>>) {
+        self.tool_search = Some(Arc::new(ToolSearch::new(core)));
+        self.disabled.remove(SEARCH_TOOLS);
+        self.refresh_tool_search();
+    }
+
+    pub fn tool_search(&self) -> Option<&Arc<ToolSearch>> {
+        self.tool_search.as_ref()
+    }
+
+    /// Let the search find exactly the enabled tools outside the core set
+    fn refresh_tool_search(&self) {
+        let Some(ref search) = self.tool_search else {
+            return;
+        };
+        let catalog = self
+            .enabled_tool_names()
+            .into_iter()
+            .filter(|name| !search.is_core(name))
+            .filter_map(|name| {
+                let tool = self.tools.get(&name)?;
+                let definition = tool.to_openai_definition();
+                Some(ToolMatch {
+                    description: tool.description().to_string(),
+                    categories: self.categories_of(&name),
+                    parameters: definition["function"]["parameters"].clone(),
+                    name,
+                })
+            })
+            .collect();
+        search.set_catalog(catalog);
+    }
+
+    /// The tools a selector names: a tool of that name, or else the tools in
+    /// the category of that name. Sorted; empty when it matches neither.
+    pub fn resolve_tools(&self, selector: &str) -> Vec<String> {
+        if self.has_tool(selector) {
+            return vec![selector.to_string()];
+        }
+        let mut names = self.categories.get(selector).cloned().unwrap_or_default();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Categories a tool is registered in, sorted
+    pub fn categories_of(&self, name: &str) -> Vec<String> {
+        let mut categories: Vec<String> = self
+            .categories
+            .iter()
+            .filter(|(_, tools)| tools.iter().any(|tool| tool == name))
+            .map(|(category, _)| category.clone())
+            .collect();
+        categories.sort();
+        categories
+    }
+
+    /// Execute a tool by name through the middleware chain, after validating
+    /// its arguments against the tool's parameter definitions
     pub async fn execute_tool(
         &self,
         name: &str,
         params: ToolParameters,
         context: &ToolContext,
     ) -> ToolResult {
-        match self.get_tool(name) {
-            Some(tool) => tool.execute(params, context).await,
-            None => ToolResult::error(format!("Tool '{}' not found", name)),
+        self.execute(ToolInvocation::new(name, params), context).await
+    }
+
+    /// Execute a call through the middleware chain; see [`execute_tool`](Self::execute_tool)
+    pub async fn execute(&self, mut call: ToolInvocation, context: &ToolContext) -> ToolResult {
+        call.restart();
+        let mut entered = 0;
+        let mut vetoed = None;
+        for middleware in &self.middleware {
+            entered += 1;
+            if let MiddlewareAction::Veto(result) = middleware.before(&mut call, context).await {
+                vetoed = Some(*result);
+                break;
+            }
+        }
+
+        let mut result = match vetoed {
+            Some(result) => result,
+            None => self.run(&mut call, context).await,
+        };
+        result.metadata.duration_ms = Some(call.elapsed().as_millis() as u64);
+
+        for middleware in self.middleware[..entered].iter().rev() {
+            middleware.after(&call, context, &mut result).await;
+        }
+        result
+    }
+
+    async fn run(&self, call: &mut ToolInvocation, context: &ToolContext) -> ToolResult {
+        let Some(tool) = self.get_tool(&call.tool) else {
+            return ToolResult::error(format!("Tool '{}' not found", call.tool));
+        };
+        if self.disabled.contains(&call.tool) {
+            return ToolResult::error(format!("Tool '{}' is disabled in this session", call.tool));
+        }
+        let no_patterns = ParameterPatterns::default();
+        let patterns = self.patterns.get(&call.tool).unwrap_or(&no_patterns);
+        if let Err(errors) = validate_arguments(&call.tool, &tool.parameters(), patterns, &mut call.params) {
+            return ToolResult::error(errors.to_string());
+        }
+
+        // The call gets its own token, so stopping it leaves the caller's alone
+        let mut tool_context = context.clone();
+        tool_context.cancellation = context.cancellation.child_token();
+        tool_context.deadline = self.timeout_for(&call.tool).map(|limit| Arc::new(Deadline::new(limit)));
+        tool_context.tool_search = self.tool_search.clone();
+
+        let execution = tool.execute(call.params.clone(), &tool_context);
+        tokio::pin!(execution);
+
+        let stopped = loop {
+            let remaining = tool_context.deadline.as_ref().map(|deadline| deadline.remaining());
+            tokio::select! {
+                result = &mut execution => return result,
+                _ = context.cancellation.cancelled() => break Stopped::Cancelled,
+                _ = sleep_for(remaining) => {
+                    // The deadline may have been paused while we slept
+                    if let Some(deadline) = tool_context.deadline.as_ref().filter(|d| d.is_expired()) {
+                        break Stopped::TimedOut(deadline.limit());
+                    }
+                }
+            }
+        };
+
+        // Ask the tool to stop; a cooperative tool cleans up and reports what
+        // it has done so far
+        tool_context.cancellation.cancel();
+        match tokio::time::timeout(STOP_GRACE, &mut execution).await {
+            Ok(result) => result,
+            Err(_) => ToolResult::error(match stopped {
+                Stopped::TimedOut(limit) => format!("Tool '{}' timed out after {:?}", call.tool, limit),
+                Stopped::Cancelled => format!("Tool '{}' was cancelled", call.tool),
+            }),
         }
     }
 
-    /// Get all tool definitions in OpenAI format
+    /// Get the enabled tools' definitions in OpenAI format. With tool search
+    /// on, these are the core tools followed by the activated ones.
     pub fn get_openai_tool_definitions(&self) -> Vec<serde_json::Value> {
-        let mut tools: Vec<_> = self.tools.iter().collect();
         // Sort by tool name to ensure consistent ordering (critical for prompt caching)
-        tools.sort_by_key(|(name, _)| name.as_str());
-        tools.into_iter()
-            .map(|(_, tool)| tool.to_openai_definition())
+        let mut names = self.enabled_tool_names();
+        if let Some(ref search) = self.tool_search {
+            names.retain(|name| search.is_core(name));
+            // Activated tools go last in activation order, so earlier lists stay a prefix
+            names.extend(search.activated().into_iter().filter(|name| self.is_enabled(name)));
+        }
+        names.iter()
+            .filter_map(|name| self.tools.get(name))
+            .map(|tool| tool.to_openai_definition())
             .collect()
     }
 
@@ -105,6 +433,13 @@ impl ToolRegistry {
     }
 }
 
+async fn sleep_for(duration: Option<Duration>) {
+    match duration {
+        Some(duration) => tokio::time::sleep(duration).await,
+        None => std::future::pending().await,
+    }
+}
+
 impl Default for ToolRegistry {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,122 @@
+//! On-demand tool discovery. With tool search on, the model is sent a small
+//! core set of tools plus [`SEARCH_TOOLS`]; the tools a search finds are
+//! activated and sent from then on. Activated tools are appended after the
+//! core tools, in the order they were found, so the tool list of earlier
+//! requests stays a prefix of later ones and prompt caches keep working.
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::sync::{Mutex, RwLock};
+
+/// Name of the tool the model searches for other tools with
+pub const SEARCH_TOOLS: &str = "search_tools";
+
+/// A tool that can be found by searching
+#[derive(Debug, Clone)]
+pub struct ToolMatch {
+    pub name: String,
+    pub description: String,
+    pub categories: Vec<String>,
+    /// JSON Schema of the tool's arguments
+    pub parameters: Value,
+}
+
+/// Which tools are always sent, which can be found and which have been
+/// activated. Shared by a registry and its clones, so a tool found in one
+/// part of a session stays available in the others.
+#[derive(Debug)]
+pub struct ToolSearch {
+    core: BTreeSet<String>,
+    /// Enabled tools outside the core set, kept up to date by the registry
+    catalog: RwLock<Vec<ToolMatch>>,
+    activated: Mutex<Vec<String>>,
+}
+
+impl ToolSearch {
+    pub fn new(core: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut core: BTreeSet<String> = core.into_iter().map(Into::into).collect();
+        core.insert(SEARCH_TOOLS.to_string());
+        Self {
+            core,
+            catalog: RwLock::new(Vec::new()),
+            activated: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn core(&self) -> &BTreeSet<String> {
+        &self.core
+    }
+
+    pub fn is_core(&self, name: &str) -> bool {
+        self.core.contains(name)
+    }
+
+    pub(crate) fn set_catalog(&self, catalog: Vec<ToolMatch>) {
+        *self.catalog.write().unwrap() = catalog;
+    }
+
+    /// Activated tools, in the order they were activated
+    pub fn activated(&self) -> Vec<String> {
+        self.activated.lock().unwrap().clone()
+    }
+
+    pub fn is_activated(&self, name: &str) -> bool {
+        self.activated.lock().unwrap().iter().any(|activated| activated == name)
+    }
+
+    /// Send these tools from now on. Returns the names that were not active yet.
+    pub fn activate<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut activated = self.activated.lock().unwrap();
+        let mut added = Vec::new();
+        for name in names {
+            if !self.is_core(name) && !activated.iter().any(|active| active == name) {
+                activated.push(name.to_string());
+                added.push(name.to_string());
+            }
+        }
+        added
+    }
+
+    /// Tools matching the words of `query` in their name, categories or
+    /// description, best first; with `category`, only tools in it. An empty
+    /// query matches every tool of the category.
+    pub fn search(&self, query: &str, category: Option<&str>, limit: usize) -> Vec<ToolMatch> {
+        let terms: Vec<String> = query
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|term| !term.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+
+        let catalog = self.catalog.read().unwrap();
+        let mut scored: Vec<(usize, &ToolMatch)> = catalog
+            .iter()
+            .filter(|tool| category.is_none_or(|category| tool.categories.iter().any(|c| c == category)))
+            .filter_map(|tool| {
+                if terms.is_empty() {
+                    return Some((0, tool));
+                }
+                let name = tool.name.to_lowercase();
+                let description = tool.description.to_lowercase();
+                let score: usize = terms
+                    .iter()
+                    .map(|term| {
+                        let mut score = 0;
+                        if name.contains(term.as_str()) {
+                            score += 3;
+                        }
+                        if tool.categories.iter().any(|c| c.to_lowercase().contains(term.as_str())) {
+                            score += 2;
+                        }
+                        if description.contains(term.as_str()) {
+                            score += 1;
+                        }
+                        score
+                    })
+                    .sum();
+                (score > 0).then_some((score, tool))
+            })
+            .collect();
+        scored.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then_with(|| a.name.cmp(&b.name)));
+        scored.into_iter().take(limit).map(|(_, tool)| tool.clone()).collect()
+    }
+}
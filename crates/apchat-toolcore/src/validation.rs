@@ -0,0 +1,278 @@
+//! Validation of tool arguments against the tool's declared parameters.
+//!
+//! [`ToolRegistry::execute_tool`](crate::ToolRegistry::execute_tool) runs
+//! this before a tool executes, so every problem with a call is reported at
+//! once and the model can fix them all in a single retry. Values that are
+//! mistyped but unambiguous (`"42"` for an integer, `"true"` for a boolean,
+//! an array encoded as a JSON string) are coerced instead of rejected.
+//! Parameter patterns are compiled once, when the tool is registered, into
+//! [`ParameterPatterns`].
+
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::tool::{ParameterDefinition, ToolParameters};
+
+/// One problem with one argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgumentError {
+    /// Location of the argument, e.g. `edits[2].file_path`
+    pub path: String,
+    pub message: String,
+}
+
+/// Every problem found in the arguments of a tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgumentErrors {
+    pub tool: String,
+    pub errors: Vec<ArgumentError>,
+}
+
+impl std::fmt::Display for ArgumentErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid arguments for tool '{}' ({} problem(s)). Fix all of them and call the tool again:",
+            self.tool,
+            self.errors.len()
+        )?;
+        for error in &self.errors {
+            write!(f, "\n- {}: {}", error.path, error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ArgumentErrors {}
+
+/// The compiled `pattern`s of a tool's parameters, including those of array
+/// items and nested objects
+#[derive(Debug, Clone, Default)]
+pub struct ParameterPatterns {
+    regexes: HashMap<String, Regex>,
+    invalid: Vec<ArgumentError>,
+}
+
+impl ParameterPatterns {
+    pub fn compile(parameters: &HashMap<String, ParameterDefinition>) -> Self {
+        let mut patterns = Self::default();
+        patterns.add_all(parameters, "");
+        patterns
+    }
+
+    /// Patterns that are not valid regular expressions; arguments are not
+    /// checked against them
+    pub fn invalid(&self) -> &[ArgumentError] {
+        &self.invalid
+    }
+
+    fn add_all(&mut self, properties: &HashMap<String, ParameterDefinition>, prefix: &str) {
+        for (name, definition) in properties {
+            let path = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            self.add(definition, &path);
+        }
+    }
+
+    fn add(&mut self, definition: &ParameterDefinition, path: &str) {
+        if let Some(ref pattern) = definition.pattern {
+            if !self.regexes.contains_key(pattern) {
+                match Regex::new(pattern) {
+                    Ok(regex) => {
+                        self.regexes.insert(pattern.clone(), regex);
+                    }
+                    Err(e) => self.invalid.push(ArgumentError {
+                        path: path.to_string(),
+                        message: format!("invalid pattern {}: {}", pattern, e),
+                    }),
+                }
+            }
+        }
+        if let Some(ref items) = definition.items {
+            self.add(items, &format!("{}[]", path));
+        }
+        self.add_all(&definition.properties, path);
+    }
+
+    fn get(&self, pattern: &str) -> Option<&Regex> {
+        self.regexes.get(pattern)
+    }
+}
+
+/// Check `params` against a tool's parameter definitions, coercing values
+/// where that is safe. Arguments the tool does not declare are left alone.
+pub fn validate_arguments(
+    tool: &str,
+    parameters: &HashMap<String, ParameterDefinition>,
+    patterns: &ParameterPatterns,
+    params: &mut ToolParameters,
+) -> Result<(), ArgumentErrors> {
+    let mut object: Map<String, Value> = params.data.drain().collect();
+    let mut errors = Vec::new();
+    check_object(parameters, patterns, &mut object, "", &mut errors);
+    params.data = object.into_iter().collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ArgumentErrors {
+            tool: tool.to_string(),
+            errors,
+        })
+    }
+}
+
+fn check_object(
+    properties: &HashMap<String, ParameterDefinition>,
+    patterns: &ParameterPatterns,
+    object: &mut Map<String, Value>,
+    prefix: &str,
+    errors: &mut Vec<ArgumentError>,
+) {
+    let mut names: Vec<&String> = properties.keys().collect();
+    names.sort();
+
+    for name in names {
+        let definition = &properties[name];
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        // An explicit null for an optional argument means "not given"
+        if object.get(name).is_some_and(Value::is_null) && !definition.required {
+            object.remove(name);
+        }
+
+        match object.get_mut(name) {
+            Some(value) if !value.is_null() => check_value(definition, patterns, value, &path, errors),
+            _ if definition.required => {
+                let mut message = format!("missing required {} argument", definition.param_type);
+                if !definition.description.is_empty() {
+                    message.push_str(&format!(" ({})", definition.description));
+                }
+                errors.push(ArgumentError { path, message });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_value(
+    definition: &ParameterDefinition,
+    patterns: &ParameterPatterns,
+    value: &mut Value,
+    path: &str,
+    errors: &mut Vec<ArgumentError>,
+) {
+    let mut error = |message: String| {
+        errors.push(ArgumentError {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    coerce(&definition.param_type, value);
+    if !has_type(&definition.param_type, value) {
+        error(format!("expected {}, got {}", definition.param_type, describe(value)));
+        return;
+    }
+
+    if let Some(ref allowed) = definition.enum_values {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            error(format!("{} is not one of {}", describe(value), allowed.join(", ")));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = definition.minimum.filter(|minimum| number < *minimum) {
+            error(format!("{} is less than the minimum {}", number, minimum));
+        }
+        if let Some(maximum) = definition.maximum.filter(|maximum| number > *maximum) {
+            error(format!("{} is greater than the maximum {}", number, maximum));
+        }
+    }
+
+    if let (Some(ref pattern), Some(text)) = (&definition.pattern, value.as_str()) {
+        if patterns.get(pattern).is_some_and(|regex| !regex.is_match(text)) {
+            error(format!("{} does not match the pattern {}", describe(value), pattern));
+        }
+    }
+
+    match value {
+        Value::Array(items) => {
+            if let Some(min_items) = definition.min_items.filter(|min| items.len() < *min) {
+                error(format!("has {} item(s), at least {} required", items.len(), min_items));
+            }
+            if let Some(max_items) = definition.max_items.filter(|max| items.len() > *max) {
+                error(format!("has {} item(s), at most {} allowed", items.len(), max_items));
+            }
+            if let Some(ref item_definition) = definition.items {
+                for (index, item) in items.iter_mut().enumerate() {
+                    check_value(item_definition, patterns, item, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        Value::Object(object) => check_object(&definition.properties, patterns, object, path, errors),
+        _ => {}
+    }
+}
+
+/// Convert values whose intended type is unambiguous
+fn coerce(param_type: &str, value: &mut Value) {
+    let coerced = match (param_type, &*value) {
+        ("integer", Value::String(s)) => s.trim().parse::<i64>().ok().map(Value::from),
+        ("integer", Value::Number(n)) if n.as_i64().is_none() => n
+            .as_f64()
+            .filter(|f| f.fract() == 0.0 && f.abs() < i64::MAX as f64)
+            .map(|f| Value::from(f as i64)),
+        ("number", Value::String(s)) => s.trim().parse::<f64>().ok().filter(|f| f.is_finite()).map(Value::from),
+        ("boolean", Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ("string", Value::Number(n)) => Some(Value::String(n.to_string())),
+        ("string", Value::Bool(b)) => Some(Value::String(b.to_string())),
+        ("array" | "object", Value::String(s)) => serde_json::from_str::<Value>(s)
+            .ok()
+            .filter(|parsed| has_type(param_type, parsed)),
+        _ => None,
+    };
+    if let Some(coerced) = coerced {
+        *value = coerced;
+    }
+}
+
+fn has_type(param_type: &str, value: &Value) -> bool {
+    match param_type {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        // Types we do not know about are not checked
+        _ => true,
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::String(s) if s.chars().count() > 40 => {
+            format!("string \"{}...\"", s.chars().take(40).collect::<String>())
+        }
+        Value::String(s) => format!("string \"{}\"", s),
+        Value::Number(n) => format!("number {}", n),
+        Value::Bool(b) => format!("boolean {}", b),
+        Value::Array(_) => "an array".to_string(),
+        Value::Object(_) => "an object".to_string(),
+        Value::Null => "null".to_string(),
+    }
+}
@@ -0,0 +1,109 @@
+//! Resolution of tool-supplied paths inside the workspace.
+//!
+//! File tools never join a model-supplied path onto the work directory
+//! themselves. They go through [`resolve_path`], which resolves `..`
+//! components and symlinks, rejects anything that ends up outside the
+//! allowed roots, and gives policy rules one normalized target to match.
+
+use anyhow::{Context, Result};
+use std::path::{Component, Path, PathBuf};
+
+/// A path that has been checked to lie inside the workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPath {
+    /// Absolute path with symlinks resolved; this is what tools should access
+    pub absolute: PathBuf,
+    /// Target for policy rules: relative to the work directory with `/`
+    /// separators, or absolute for paths under an extra allowed root
+    pub policy_target: String,
+}
+
+/// Resolve `path` against `work_dir` and make sure it stays inside the work
+/// directory or one of `allowed_roots` (relative roots are taken from
+/// `work_dir`). The path itself does not have to exist yet.
+pub fn resolve_path(work_dir: &Path, allowed_roots: &[PathBuf], path: &str) -> Result<ResolvedPath> {
+    let root = work_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to canonicalize workspace dir: {}", work_dir.display()))?;
+
+    let joined = root.join(path);
+    let absolute = canonicalize_lenient(&joined)
+        .with_context(|| format!("Failed to resolve path: {}", path))?;
+
+    if let Ok(relative) = absolute.strip_prefix(&root) {
+        return Ok(ResolvedPath {
+            policy_target: to_policy_target(relative),
+            absolute,
+        });
+    }
+
+    for allowed in allowed_roots {
+        // Roots that do not exist cannot contain anything
+        let Ok(allowed) = root.join(allowed).canonicalize() else {
+            continue;
+        };
+        if absolute.starts_with(&allowed) {
+            return Ok(ResolvedPath {
+                policy_target: to_policy_target(&absolute),
+                absolute,
+            });
+        }
+    }
+
+    anyhow::bail!(
+        "Path '{}' resolves to {}, which is outside the workspace {}",
+        path,
+        absolute.display(),
+        root.display()
+    )
+}
+
+/// Resolve `path` one component at a time: every prefix that exists is
+/// canonicalized before the next component is applied, so a `..` always
+/// leaves the real directory and a symlink is followed wherever it appears.
+/// Components that do not exist yet are appended lexically, so paths of
+/// files that are about to be created can be checked.
+fn canonicalize_lenient(path: &Path) -> Result<PathBuf> {
+    let mut resolved = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => {
+                resolved.push(other);
+                let Ok(metadata) = resolved.symlink_metadata() else {
+                    continue;
+                };
+                match resolved.canonicalize() {
+                    Ok(canonical) => resolved = canonical,
+                    // A dangling symlink does not "exist", but writing through
+                    // it would create its target wherever it points
+                    Err(_) if metadata.file_type().is_symlink() => {
+                        anyhow::bail!("'{}' is a symlink to a missing target", resolved.display());
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
+fn to_policy_target(path: &Path) -> String {
+    let target = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    if path.is_absolute() {
+        format!("/{}", target.trim_start_matches('/'))
+    } else if target.is_empty() {
+        ".".to_string()
+    } else {
+        target
+    }
+}
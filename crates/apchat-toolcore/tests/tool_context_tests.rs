@@ -1,5 +1,7 @@
 use apchat_toolcore::tool_context::ToolContext;
-use apchat_policy::PolicyManager;
+use apchat_toolcore::confirmation::{ConfirmationResponse, EscalatingConfirmation, ScriptedConfirmation};
+use apchat_policy::{GrantScope, PolicyManager};
+use std::sync::Arc;
 use tempfile::TempDir;
 
 #[cfg(test)]
@@ -94,7 +96,7 @@ mod tool_context_tests {
         let target = "/tmp/test_file.txt";
         let prompt = "Allow reading test file?";
         
-        let result = context.check_permission(action, target, prompt);
+        let result = context.check_permission(action, target, prompt).await;
         assert!(result.is_ok());
         
         let (_approved, reason) = result.unwrap();
@@ -118,7 +120,7 @@ mod tool_context_tests {
         let target = "rm -rf /";
         let prompt = "Allow destructive system command?";
         
-        let result = context.check_permission(action, target, prompt);
+        let result = context.check_permission(action, target, prompt).await;
         assert!(result.is_ok());
         
         let (_approved, reason) = result.unwrap();
@@ -140,12 +142,13 @@ mod tool_context_tests {
         let target = "/tmp/test.txt";
         let prompt = "Allow file write?";
         
-        let result = context.check_permission(action, target, prompt);
+        let result = context.check_permission(action, target, prompt).await;
         assert!(result.is_ok());
         
-        let (_approved, reason) = result.unwrap();
-        // In non-interactive mode, decisions should be deterministic
-        assert!(reason.is_none());
+        let (approved, reason) = result.unwrap();
+        // In non-interactive mode nobody can answer, so Ask decisions are denied
+        assert!(!approved);
+        assert!(reason.is_some());
     }
 
     #[test]
@@ -208,7 +211,7 @@ mod tool_context_tests {
         let target = "";
         let prompt = "Test prompt";
         
-        let result = context.check_permission(action, target, prompt);
+        let result = context.check_permission(action, target, prompt).await;
         assert!(result.is_ok());
     }
 
@@ -227,7 +230,7 @@ mod tool_context_tests {
         let prompt = "Test prompt";
         
         // This should not panic even if learning is enabled
-        let result = context.check_permission(action, target, prompt);
+        let result = context.check_permission(action, target, prompt).await;
         assert!(result.is_ok());
     }
 
@@ -300,7 +303,7 @@ mod tool_context_tests {
             let target = format!("/tmp/test_{}.txt", i);
             let prompt = format!("Test prompt {}", i);
             
-            let result = context.check_permission(action, &target, &prompt);
+            let result = context.check_permission(action, &target, &prompt).await;
             assert!(result.is_ok(), "Permission check {} failed", i);
             
             let (_approved, reason) = result.unwrap();
@@ -342,4 +345,229 @@ mod tool_context_tests {
             assert!(result.contains("_completed"));
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_check_permission_uses_confirmation_provider() {
+        let (context, _temp_dir) = create_test_context();
+        let provider = Arc::new(ScriptedConfirmation::new([
+            ConfirmationResponse::approve(),
+            ConfirmationResponse::deny(Some("not now".to_string())),
+        ]));
+        let context = context
+            .with_non_interactive(true)
+            .with_confirmation_provider(provider.clone());
+
+        let (approved, reason) = context
+            .check_permission(apchat_policy::ActionType::CommandExecution, "cargo test", "Execute?")
+            .await
+            .unwrap();
+        assert!(approved);
+        assert!(reason.is_none());
+
+        let (approved, reason) = context
+            .check_permission(apchat_policy::ActionType::FileWrite, "notes.md", "Write?")
+            .await
+            .unwrap();
+        assert!(!approved);
+        assert_eq!(reason.as_deref(), Some("not now"));
+
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].target, "cargo test");
+        assert_eq!(requests[1].prompt, "Write?");
+        assert_eq!(requests[1].session_id, "test_session");
+    }
+
+    #[tokio::test]
+    async fn test_learn_mode_learns_chosen_generalization() {
+        let temp_dir = TempDir::new().unwrap();
+        let policy_manager = PolicyManager::layered(None, None, true).unwrap();
+        let provider = Arc::new(
+            ScriptedConfirmation::new([ConfirmationResponse::approve()]).with_rule_choices([Some(1)]),
+        );
+        let context = ToolContext::new(temp_dir.path().to_path_buf(), "test_session".to_string(), policy_manager.clone())
+            .with_confirmation_provider(provider.clone());
+
+        let (approved, _) = context
+            .check_permission(apchat_policy::ActionType::CommandExecution, "cargo test --all", "Execute?")
+            .await
+            .unwrap();
+        assert!(approved);
+
+        let rules = policy_manager.session_rules();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].pattern, "cargo test *");
+
+        // The generalized rule answers without asking again
+        let (approved, _) = context
+            .check_permission(apchat_policy::ActionType::CommandExecution, "cargo test -p foo", "Execute?")
+            .await
+            .unwrap();
+        assert!(approved);
+        assert_eq!(provider.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_content_rules_deny_with_reason() {
+        let temp_dir = TempDir::new().unwrap();
+        let context = ToolContext::new(temp_dir.path().to_path_buf(), "test_session".to_string(), PolicyManager::allow_all());
+        let secret = format!("OPENAI_API_KEY={}", "sk-proj-abcdefghijklmnopqrstuvwxyz012345");
+
+        let (approved, reason) = context
+            .check_permission_with_content(apchat_policy::ActionType::FileEdit, ".env", &secret, "Apply?")
+            .await
+            .unwrap();
+        assert!(!approved);
+        let reason = reason.unwrap();
+        assert!(reason.contains("API key"), "{}", reason);
+        assert!(!reason.contains("sk-proj"), "the secret must not be echoed back: {}", reason);
+
+        let (approved, reason) = context
+            .check_content(apchat_policy::ActionType::FileWrite, "README.md", "# Hello", "Write?")
+            .await
+            .unwrap();
+        assert!(approved);
+        assert!(reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_permission_skips_provider_when_policy_decides() {
+        let temp_dir = TempDir::new().unwrap();
+        let provider = Arc::new(ScriptedConfirmation::new([]));
+        let context = ToolContext::new(temp_dir.path().to_path_buf(), "test_session".to_string(), PolicyManager::allow_all())
+            .with_confirmation_provider(provider.clone());
+
+        let (approved, _) = context
+            .check_permission(apchat_policy::ActionType::FileWrite, "notes.md", "Write?")
+            .await
+            .unwrap();
+        assert!(approved);
+
+        let (approved, reason) = context
+            .check_permission(apchat_policy::ActionType::CommandExecution, "sudo rm -rf /", "Execute?")
+            .await
+            .unwrap();
+        assert!(!approved);
+        assert!(reason.unwrap().contains("builtin"));
+        assert!(provider.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_escalating_confirmation_tags_origin() {
+        let (context, _temp_dir) = create_test_context();
+        let parent = Arc::new(ScriptedConfirmation::new([ConfirmationResponse::approve()]));
+        let context = context.with_confirmation_provider(Arc::new(EscalatingConfirmation::new(
+            "subagent 'reviewer'".to_string(),
+            parent.clone(),
+        )));
+
+        let (approved, _) = context
+            .check_permission(apchat_policy::ActionType::FileEdit, "src/lib.rs", "Apply?")
+            .await
+            .unwrap();
+        assert!(approved);
+        assert_eq!(parent.requests()[0].origin.as_deref(), Some("subagent 'reviewer'"));
+    }
+
+    #[tokio::test]
+    async fn test_check_permission_applies_grant_scope() {
+        let (context, _temp_dir) = create_test_context();
+        let provider = Arc::new(ScriptedConfirmation::new([
+            ConfirmationResponse::approve().with_scope(GrantScope::Once),
+            ConfirmationResponse::approve().with_scope(GrantScope::Minutes(10)),
+        ]));
+        let context = context.with_confirmation_provider(provider.clone());
+        let check = || context.check_permission(apchat_policy::ActionType::CommandExecution, "make", "Execute?");
+
+        assert!(check().await.unwrap().0);
+        assert!(check().await.unwrap().0);
+        // The timed grant answers the third request without asking
+        assert!(check().await.unwrap().0);
+        assert_eq!(provider.requests().len(), 2);
+
+        let grants = context.policy_manager.session_rules();
+        assert_eq!(grants.len(), 1);
+        assert!(grants[0].remaining().is_some());
+    }
+
+    #[test]
+    fn test_parse_confirmation_answer() {
+        let parse = |answer: &str| ConfirmationResponse::parse_answer(answer);
+
+        assert_eq!(parse(""), Some(ConfirmationResponse::approve()));
+        assert_eq!(parse("Yes"), Some(ConfirmationResponse::approve()));
+        assert_eq!(parse("n"), Some(ConfirmationResponse::deny(None)));
+        assert_eq!(parse("once").unwrap().scope, Some(GrantScope::Once));
+        assert_eq!(parse("s").unwrap().scope, Some(GrantScope::Session));
+        assert_eq!(parse("m 15").unwrap().scope, Some(GrantScope::Minutes(15)));
+        assert_eq!(
+            parse("p  cargo *").unwrap().scope,
+            Some(GrantScope::Pattern("cargo *".to_string()))
+        );
+        let deny_always = parse("deny always").unwrap();
+        assert!(!deny_always.approved);
+        assert_eq!(deny_always.scope, Some(GrantScope::Always));
+
+        assert!(parse("m").is_none());
+        assert!(parse("minutes soon").is_none());
+        assert!(parse("p").is_none());
+        assert!(parse("maybe").is_none());
+    }
+
+    #[test]
+    fn test_confirmation_response_scope_serialization() {
+        let plain = serde_json::to_string(&ConfirmationResponse::approve()).unwrap();
+        assert!(!plain.contains("scope"));
+
+        let scoped = ConfirmationResponse::approve().with_scope(GrantScope::Minutes(5));
+        let json = serde_json::to_string(&scoped).unwrap();
+        assert_eq!(serde_json::from_str::<ConfirmationResponse>(&json).unwrap(), scoped);
+    }
+
+    #[test]
+    fn test_resolve_path_normalizes_targets() {
+        let (context, temp_dir) = create_test_context();
+        std::fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        std::fs::write(temp_dir.path().join("src/lib.rs"), "").unwrap();
+
+        let resolved = context.resolve_path("./src/../src/lib.rs").unwrap();
+        assert_eq!(resolved.policy_target, "src/lib.rs");
+        assert_eq!(resolved.absolute, temp_dir.path().canonicalize().unwrap().join("src/lib.rs"));
+
+        // Files that do not exist yet resolve too
+        assert_eq!(context.resolve_path("new/dir/notes.md").unwrap().policy_target, "new/dir/notes.md");
+        assert_eq!(context.resolve_path("new/../notes.md").unwrap().policy_target, "notes.md");
+    }
+
+    #[test]
+    fn test_resolve_path_blocks_escapes() {
+        let (context, temp_dir) = create_test_context();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+
+        assert!(context.resolve_path("../../etc/passwd").is_err());
+        assert!(context.resolve_path("/etc/passwd").is_err());
+        assert!(context.resolve_path(outside.path().join("secret.txt").to_str().unwrap()).is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("link")).unwrap();
+            assert!(context.resolve_path("link/secret.txt").is_err());
+            assert!(context.resolve_path("link/new.txt").is_err());
+            // A `..` after a missing directory must not hide the symlink
+            assert!(context.resolve_path("x/../link/secret.txt").is_err());
+            assert!(context.resolve_path("x/y/../../link/secret.txt").is_err());
+            assert!(context.resolve_path("src/../x/../link/new.txt").is_err());
+
+            std::os::unix::fs::symlink(outside.path().join("missing.txt"), temp_dir.path().join("dangling")).unwrap();
+            assert!(context.resolve_path("dangling").is_err());
+        }
+
+        // Unless the directory is explicitly allowed
+        let context = context.with_allowed_roots(vec![outside.path().to_path_buf()]);
+        let resolved = context.resolve_path(outside.path().join("secret.txt").to_str().unwrap()).unwrap();
+        assert_eq!(resolved.absolute, outside.path().canonicalize().unwrap().join("secret.txt"));
+        assert!(resolved.policy_target.starts_with('/'));
+        assert!(context.resolve_path("/etc/passwd").is_err());
+    }
+}
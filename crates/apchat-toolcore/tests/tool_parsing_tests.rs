@@ -0,0 +1,165 @@
+use apchat_models::{FunctionCall, ToolCallDialect};
+use apchat_toolcore::validation::{validate_arguments, ParameterPatterns};
+use apchat_toolcore::{
+    parse_tool_calls, parse_xml_tool_calls, ParameterDefinition, ToolCallParser, ToolCallParsers, ToolParameters,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+fn arguments(call: &FunctionCall) -> Value {
+    serde_json::from_str(&call.arguments).unwrap()
+}
+
+#[test]
+fn test_glm_calls_keep_text_and_convert_values() {
+    let content = "Let me look.\n<tool_call>search_files\n<arg_key>query</arg_key>\n<arg_value>fn main</arg_value>\n\
+        <arg_key>max_results</arg_key>\n<arg_value>5</arg_value>\n<arg_key>threshold</arg_key>\n<arg_value>0.5</arg_value>\n\
+        <arg_key>filter</arg_key>\n<arg_value>{\"kind\":[\"rs\",\"toml\"]}</arg_value>\n\
+        <arg_key>id</arg_key>\n<arg_value>007</arg_value>\n</tool_call>";
+
+    let parsed = parse_tool_calls(content, ToolCallDialect::Auto).unwrap();
+    assert_eq!(parsed.dialect, ToolCallDialect::Glm);
+    assert_eq!(parsed.text, "Let me look.");
+    assert_eq!(parsed.calls.len(), 1);
+    assert_eq!(parsed.calls[0].id, "call_0");
+    assert_eq!(parsed.calls[0].function.name, "search_files");
+    assert_eq!(
+        arguments(&parsed.calls[0].function),
+        json!({"query": "fn main", "max_results": 5, "threshold": 0.5, "filter": "{\"kind\":[\"rs\",\"toml\"]}", "id": "007"})
+    );
+
+    // Pretty-printed JSON is text the model meant literally
+    let content = "<tool_call>write_file\n<arg_key>content</arg_key>\n<arg_value>{\n  \"a\": 1\n}</arg_value>\n</tool_call>";
+    let calls = parse_xml_tool_calls(content).unwrap();
+    assert_eq!(arguments(&calls[0].function), json!({"content": "{\n  \"a\": 1\n}"}));
+}
+
+#[test]
+fn test_glm_json_text_is_parsed_only_for_non_string_parameters() {
+    let content = "<tool_call>write_file\n<arg_key>path</arg_key>\n<arg_value>data.json</arg_value>\n\
+        <arg_key>content</arg_key>\n<arg_value>{\"a\":[1]}</arg_value>\n\
+        <arg_key>tags</arg_key>\n<arg_value>[\"x\",\"y\"]</arg_value>\n</tool_call>";
+    let calls = parse_xml_tool_calls(content).unwrap();
+    let mut params = ToolParameters::from_json(&calls[0].function.arguments).unwrap();
+    assert_eq!(params.data["content"], json!(r#"{"a":[1]}"#));
+
+    let parameters = HashMap::from([
+        ("path".to_string(), ParameterDefinition::new("string", "File to write").required()),
+        ("content".to_string(), ParameterDefinition::new("string", "Text to write").required()),
+        ("tags".to_string(), ParameterDefinition::array("Tags", ParameterDefinition::new("string", "Tag"))),
+    ]);
+    validate_arguments("write_file", &parameters, &ParameterPatterns::default(), &mut params).unwrap();
+    assert_eq!(params.data["content"], json!(r#"{"a":[1]}"#));
+    assert_eq!(params.data["tags"], json!(["x", "y"]));
+}
+
+#[test]
+fn test_hermes_calls() {
+    let content = "I'll read both files.\n<tool_call>\n{\"name\": \"read_file\", \"arguments\": {\"path\": \"a.rs\"}}\n</tool_call>\n\
+        <tool_call>\n{\"name\": \"read_file\", \"arguments\": \"{\\\"path\\\": \\\"b.rs\\\"}\"}\n</tool_call>";
+
+    let parsed = parse_tool_calls(content, ToolCallDialect::Auto).unwrap();
+    assert_eq!(parsed.dialect, ToolCallDialect::Hermes);
+    assert_eq!(parsed.text, "I'll read both files.");
+    let ids: Vec<&str> = parsed.calls.iter().map(|call| call.id.as_str()).collect();
+    assert_eq!(ids, vec!["call_0", "call_1"]);
+    assert_eq!(arguments(&parsed.calls[1].function), json!({"path": "b.rs"}));
+
+    // Generation stopped before the closing tag
+    let parsed = parse_tool_calls("<tool_call>{\"name\": \"list_files\"}", ToolCallDialect::Hermes).unwrap();
+    assert_eq!(parsed.calls[0].function.name, "list_files");
+    assert_eq!(arguments(&parsed.calls[0].function), json!({}));
+}
+
+#[test]
+fn test_llama3_calls() {
+    let content = "<|python_tag|>{\"name\": \"open_file\", \"parameters\": {\"file_path\": \"README.md\", \"start_line\": 1}}; \
+        {\"name\": \"list_files\", \"parameters\": {}}<|eom_id|>";
+
+    let parsed = parse_tool_calls(content, ToolCallDialect::Auto).unwrap();
+    assert_eq!(parsed.dialect, ToolCallDialect::Llama3);
+    assert_eq!(parsed.text, "");
+    assert_eq!(parsed.calls.len(), 2);
+    assert_eq!(arguments(&parsed.calls[0].function), json!({"file_path": "README.md", "start_line": 1}));
+    assert_eq!(parsed.calls[1].function.name, "list_files");
+}
+
+#[test]
+fn test_mistral_calls() {
+    let content = "Checking.[TOOL_CALLS][{\"name\": \"run_command\", \"arguments\": {\"command\": \"cargo test\", \"env\": {\"RUST_LOG\": \"debug\"}}}]</s>";
+    let parsed = parse_tool_calls(content, ToolCallDialect::Auto).unwrap();
+    assert_eq!(parsed.dialect, ToolCallDialect::Mistral);
+    assert_eq!(parsed.text, "Checking.");
+    assert_eq!(
+        arguments(&parsed.calls[0].function),
+        json!({"command": "cargo test", "env": {"RUST_LOG": "debug"}})
+    );
+
+    // Newer templates put the name before [ARGS]
+    let content = "[TOOL_CALLS]read_file[ARGS]{\"path\": \"a.rs\"}[TOOL_CALLS]read_file[ARGS]{\"path\": \"b.rs\"}";
+    let parsed = parse_tool_calls(content, ToolCallDialect::Mistral).unwrap();
+    assert_eq!(parsed.calls.len(), 2);
+    assert_eq!(arguments(&parsed.calls[1].function), json!({"path": "b.rs"}));
+}
+
+#[test]
+fn test_fenced_json_calls() {
+    let content = "Here is the config:\n```json\n{\"name\": \"demo\", \"version\": 2}\n```\n\
+        Now reading it.\n```json\n{\"name\": \"read_file\", \"arguments\": {\"path\": \"config.json\"}}\n```";
+
+    // Fenced JSON is only parsed for a slot that asks for it
+    assert!(parse_tool_calls(content, ToolCallDialect::Auto).is_none());
+
+    let parsed = parse_tool_calls(content, ToolCallDialect::Json).unwrap();
+    assert_eq!(parsed.dialect, ToolCallDialect::Json);
+    assert_eq!(parsed.calls.len(), 1);
+    assert_eq!(parsed.calls[0].function.name, "read_file");
+    // The block that is not a call stays visible
+    assert!(parsed.text.contains("\"version\": 2"));
+    assert!(!parsed.text.contains("read_file"));
+
+    assert!(parse_tool_calls("```rust\nfn main() {}\n```", ToolCallDialect::Json).is_none());
+}
+
+#[test]
+fn test_dialect_selection() {
+    let hermes = "<tool_call>{\"name\": \"list_files\", \"arguments\": {}}</tool_call>";
+    assert!(parse_tool_calls(hermes, ToolCallDialect::Glm).is_none());
+    assert!(parse_tool_calls(hermes, ToolCallDialect::None).is_none());
+    assert!(parse_tool_calls("No tools needed.", ToolCallDialect::Auto).is_none());
+
+    assert_eq!("qwen".parse::<ToolCallDialect>(), Ok(ToolCallDialect::Hermes));
+    assert_eq!("LLAMA3".parse::<ToolCallDialect>().unwrap().as_str(), "llama3");
+    assert!("yaml".parse::<ToolCallDialect>().unwrap_err().contains("Unknown tool parser 'yaml'"));
+}
+
+/// `CALL name` lines
+struct CallLineParser;
+
+impl ToolCallParser for CallLineParser {
+    fn dialect(&self) -> ToolCallDialect {
+        ToolCallDialect::Auto
+    }
+
+    fn detect(&self, content: &str) -> bool {
+        content.contains("CALL ")
+    }
+
+    fn parse(&self, content: &str) -> Option<(Vec<FunctionCall>, String)> {
+        let name = content.split("CALL ").nth(1)?.trim();
+        Some((
+            vec![FunctionCall {
+                name: name.to_string(),
+                arguments: "{}".to_string(),
+            }],
+            String::new(),
+        ))
+    }
+}
+
+#[test]
+fn test_custom_parser() {
+    let parsers = ToolCallParsers::for_dialect(ToolCallDialect::None).with_parser(CallLineParser);
+    let parsed = parsers.parse("CALL list_files").unwrap();
+    assert_eq!(parsed.calls[0].function.name, "list_files");
+}
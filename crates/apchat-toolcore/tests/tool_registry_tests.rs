@@ -1,8 +1,15 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use apchat_toolcore::tool_registry::ToolRegistry;
-use apchat_toolcore::tool::{Tool, ToolParameters, ToolResult, ParameterDefinition};
-use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::tool::{SideEffects, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::tool_context::{Deadline, ToolContext};
+use apchat_toolcore::validation::{validate_arguments, ParameterPatterns};
+use apchat_toolcore::middleware::{
+    MiddlewareAction, OutputLimitMiddleware, RedactionMiddleware, SpillMiddleware, ToolInvocation, ToolMiddleware,
+};
+use apchat_toolcore::artifacts::{excerpt, ArtifactStore};
+use apchat_toolcore::tool_profiles::{ToolProfile, ToolProfiles};
 use tempfile::TempDir;
 
 // Mock tool implementations for testing
@@ -75,6 +82,7 @@ fn create_parameter_definition(name: &str, param_type: &str, required: bool) ->
         description: format!("Test parameter {}", name),
         required,
         default: None,
+        ..Default::default()
     }
 }
 
@@ -245,6 +253,93 @@ async fn test_get_tools_by_category_with_tools() {
     assert_eq!(cat3_tools.len(), 0);
 }
 
+#[tokio::test]
+async fn test_disabled_tools_are_hidden_and_refused() {
+    let mut registry = ToolRegistry::new();
+    registry.register_with_categories(TestTool::new("tool_b", "B"), vec!["cat1".to_string()]);
+    registry.register_with_categories(TestTool::new("tool_a", "A"), vec!["cat1".to_string(), "cat2".to_string()]);
+    registry.register(TestTool::new("tool_c", "C"));
+
+    assert!(registry.set_enabled("tool_b", false));
+    assert!(!registry.set_enabled("missing", false));
+    assert!(!registry.is_enabled("tool_b"));
+    assert!(registry.has_tool("tool_b"));
+    assert_eq!(registry.enabled_tool_names(), vec!["tool_a", "tool_c"]);
+
+    let names: Vec<_> = registry
+        .get_openai_tool_definitions()
+        .iter()
+        .map(|definition| definition["function"]["name"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(names, vec!["tool_a", "tool_c"]);
+
+    let params = ToolParameters { data: HashMap::new() };
+    let result = registry.execute_tool("tool_b", params, &create_test_context()).await;
+    assert!(!result.success);
+    assert!(result.error.unwrap().contains("disabled"));
+
+    assert_eq!(registry.resolve_tools("cat1"), vec!["tool_a", "tool_b"]);
+    assert_eq!(registry.resolve_tools("tool_c"), vec!["tool_c"]);
+    assert!(registry.resolve_tools("cat3").is_empty());
+    assert_eq!(registry.categories_of("tool_a"), vec!["cat1", "cat2"]);
+}
+
+#[tokio::test]
+async fn test_tool_profiles() {
+    let mut registry = ToolRegistry::new();
+    registry.register_with_categories(TestTool::new("read", "Read"), vec!["file_ops".to_string()]);
+    registry.register_with_categories(TestTool::new("write", "Write"), vec!["file_ops".to_string()]);
+    registry.register_with_categories(TestTool::new("pty", "PTY"), vec!["terminal".to_string()]);
+    registry.register_with_categories(TestTool::new("run", "Run"), vec!["system".to_string()]);
+    registry.register_with_categories(TestTool::new("subagent", "Subagent"), vec!["agent_control".to_string()]);
+    registry.register_with_categories(TestTool::new("lint", "Lint"), vec!["custom".to_string()]);
+    registry.register_with_categories(TestTool::new("mcp__x__y", "Remote"), vec!["mcp".to_string()]);
+
+    let profiles = ToolProfiles::builtin();
+    registry.apply_profile(profiles.get("no-terminal").unwrap());
+    assert_eq!(registry.enabled_tool_names(), vec!["lint", "mcp__x__y", "read", "run", "subagent", "write"]);
+    // Every tool that can run a command is left out
+    registry.apply_profile(profiles.get("edit").unwrap());
+    assert_eq!(registry.enabled_tool_names(), vec!["read", "write"]);
+    // Test tools declare no side effects, so none of them is read-only
+    registry.apply_profile(profiles.get("readonly").unwrap());
+    assert!(registry.enabled_tool_names().is_empty());
+    registry.apply_profile(profiles.get("full").unwrap());
+    assert_eq!(registry.enabled_tool_names().len(), 7);
+
+    let custom = ToolProfiles::parse(
+        "[profiles.review]\ninclude = [\"file_ops\", \"pty\"]\nexclude = [\"write\"]\n\n[profiles.full]\nexclude = [\"system\"]\n",
+    )
+    .unwrap();
+    registry.apply_profile(custom.get("review").unwrap());
+    assert_eq!(registry.enabled_tool_names(), vec!["pty", "read"]);
+    // Custom profiles replace built-in ones of the same name
+    assert_eq!(custom.get("full").unwrap(), &ToolProfile::default().with_exclude(["system"]));
+    assert!(custom.names().contains(&"readonly"));
+
+    let error = custom.get("nope").unwrap_err().to_string();
+    assert!(error.contains("Unknown tool profile 'nope'") && error.contains("review"));
+    assert!(ToolProfiles::parse("[profiles.bad]\ninclude = \"file_ops\"\n").is_err());
+}
+
+#[tokio::test]
+async fn test_tool_profiles_layering() {
+    let temp_dir = TempDir::new().unwrap();
+    let global = temp_dir.path().join("global.toml");
+    std::fs::write(&global, "[profiles.edit]\nexclude = [\"terminal\"]\n[profiles.mine]\ninclude = [\"search\"]\n").unwrap();
+    let project = ToolProfiles::project_config_path(temp_dir.path());
+    std::fs::create_dir_all(project.parent().unwrap()).unwrap();
+    std::fs::write(&project, "[profiles.mine]\ninclude = [\"file_ops\"]\n").unwrap();
+
+    let profiles = ToolProfiles::layered(Some(&global), &project).unwrap();
+    // The project file does not reset the global change to a built-in profile
+    assert_eq!(profiles.get("edit").unwrap().exclude, vec!["terminal"]);
+    assert_eq!(profiles.get("mine").unwrap().include, vec!["file_ops"]);
+
+    let missing = ToolProfiles::layered(None, &temp_dir.path().join("missing.toml")).unwrap();
+    assert_eq!(missing.names(), ToolProfiles::builtin().names());
+}
+
 #[tokio::test]
 async fn test_openai_tool_definitions() {
     let mut registry = ToolRegistry::new();
@@ -276,6 +371,164 @@ async fn test_openai_tool_definitions() {
     assert!(function.get("parameters").is_some());
 }
 
+#[tokio::test]
+async fn test_openai_definition_rich_schema() {
+    let mut registry = ToolRegistry::new();
+
+    let parameters = HashMap::from([
+        (
+            "edits".to_string(),
+            ParameterDefinition::array(
+                "Edits to apply",
+                ParameterDefinition::object("", [
+                    ("file_path".to_string(), ParameterDefinition::new("string", "File to edit").required()),
+                    ("mode".to_string(), ParameterDefinition::new("string", "How to edit").with_enum(["replace", "append"])),
+                ]),
+            )
+            .with_item_count(Some(1), Some(20))
+            .required(),
+        ),
+        (
+            "count".to_string(),
+            ParameterDefinition::new("integer", "How many").with_range(Some(1.0), Some(10.0)).with_default(3),
+        ),
+        (
+            "name".to_string(),
+            ParameterDefinition::new("string", "Identifier").with_pattern("^[a-z_]+$"),
+        ),
+    ]);
+    registry.register(TestTool::new("schema_tool", "Tool with a rich schema").with_parameters(parameters));
+
+    let definitions = registry.get_openai_tool_definitions();
+    assert_eq!(
+        definitions[0]["function"]["parameters"],
+        serde_json::json!({
+            "type": "object",
+            "required": ["edits"],
+            "properties": {
+                "count": {"type": "integer", "description": "How many", "default": 3, "minimum": 1, "maximum": 10},
+                "edits": {
+                    "type": "array",
+                    "description": "Edits to apply",
+                    "minItems": 1,
+                    "maxItems": 20,
+                    "items": {
+                        "type": "object",
+                        "required": ["file_path"],
+                        "properties": {
+                            "file_path": {"type": "string", "description": "File to edit"},
+                            "mode": {"type": "string", "description": "How to edit", "enum": ["replace", "append"]}
+                        }
+                    }
+                },
+                "name": {"type": "string", "description": "Identifier", "pattern": "^[a-z_]+$"}
+            }
+        })
+    );
+}
+
+#[test]
+fn test_get_required_json_accepts_encoded_values() {
+    let mut params = ToolParameters::new();
+    params.set("structured", serde_json::json!([1, 2, 3]));
+    params.set("encoded", "[4, 5]");
+    params.set("broken", "[4,");
+
+    assert_eq!(params.get_required_json::<Vec<i32>>("structured").unwrap(), vec![1, 2, 3]);
+    assert_eq!(params.get_required_json::<Vec<i32>>("encoded").unwrap(), vec![4, 5]);
+    assert!(params.get_required_json::<Vec<i32>>("broken").is_err());
+    assert!(params.get_required_json::<Vec<i32>>("missing").is_err());
+}
+
+fn validated_parameters() -> HashMap<String, ParameterDefinition> {
+    HashMap::from([
+        ("path".to_string(), ParameterDefinition::new("string", "File to read").required()),
+        ("count".to_string(), ParameterDefinition::new("integer", "How many").with_range(Some(1.0), None)),
+        ("verbose".to_string(), ParameterDefinition::new("boolean", "Print more")),
+        ("mode".to_string(), ParameterDefinition::new("string", "Mode").with_enum(["fast", "slow"])),
+        (
+            "items".to_string(),
+            ParameterDefinition::array(
+                "Items",
+                ParameterDefinition::object("", [("name".to_string(), ParameterDefinition::new("string", "Item name").required())]),
+            ),
+        ),
+    ])
+}
+
+#[test]
+fn test_validate_arguments_coerces_safe_values() {
+    let mut params = ToolParameters::new();
+    params.set("path", "src/main.rs");
+    params.set("count", "42");
+    params.set("verbose", "true");
+    params.set("mode", serde_json::Value::Null);
+    params.set("items", r#"[{"name": "a"}]"#);
+    params.set("extra", "ignored");
+
+    validate_arguments("tool", &validated_parameters(), &ParameterPatterns::default(), &mut params).unwrap();
+    assert_eq!(params.data["count"], serde_json::json!(42));
+    assert_eq!(params.data["verbose"], serde_json::json!(true));
+    assert_eq!(params.data["items"], serde_json::json!([{"name": "a"}]));
+    assert!(!params.data.contains_key("mode"));
+    assert_eq!(params.data["extra"], serde_json::json!("ignored"));
+}
+
+#[tokio::test]
+async fn test_execute_tool_reports_every_invalid_argument() {
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("strict_tool", "Tool with a strict schema").with_parameters(validated_parameters()));
+    let context = create_test_context();
+
+    let mut params = ToolParameters::new();
+    params.set("count", "many");
+    params.set("mode", "medium");
+    params.set("items", serde_json::json!([{"name": "a"}, {}]));
+    let result = registry.execute_tool("strict_tool", params, &context).await;
+
+    assert!(!result.success);
+    let error = result.error.unwrap();
+    assert!(error.starts_with("Invalid arguments for tool 'strict_tool' (4 problem(s))"), "{}", error);
+    assert!(error.contains("- count: expected integer, got string \"many\""), "{}", error);
+    assert!(error.contains("- items[1].name: missing required string argument (Item name)"), "{}", error);
+    assert!(error.contains("- mode: string \"medium\" is not one of \"fast\", \"slow\""), "{}", error);
+    assert!(error.contains("- path: missing required string argument (File to read)"), "{}", error);
+
+    let mut params = ToolParameters::new();
+    params.set("path", "README.md");
+    params.set("count", 0);
+    let errors = validate_arguments("strict_tool", &validated_parameters(), &ParameterPatterns::default(), &mut params).unwrap_err();
+    assert_eq!(errors.errors.len(), 1);
+    assert_eq!(errors.errors[0].message, "0 is less than the minimum 1");
+}
+
+#[tokio::test]
+async fn test_parameter_patterns_are_compiled_once() {
+    let parameters = HashMap::from([
+        ("name".to_string(), ParameterDefinition::new("string", "Identifier").with_pattern("^[a-z_]+$")),
+        (
+            "tags".to_string(),
+            ParameterDefinition::array("Tags", ParameterDefinition::new("string", "").with_pattern("([a-z")),
+        ),
+    ]);
+    let patterns = ParameterPatterns::compile(&parameters);
+    assert_eq!(patterns.invalid().len(), 1);
+    assert_eq!(patterns.invalid()[0].path, "tags[]");
+    assert!(patterns.invalid()[0].message.starts_with("invalid pattern ([a-z"));
+
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("pattern_tool", "Tool with patterns").with_parameters(parameters));
+    let context = create_test_context();
+
+    let mut params = ToolParameters::new();
+    params.set("name", "Not An Identifier");
+    params.set("tags", serde_json::json!(["anything"]));
+    let result = registry.execute_tool("pattern_tool", params, &context).await;
+    let error = result.error.unwrap();
+    assert!(error.contains("- name: string \"Not An Identifier\" does not match the pattern ^[a-z_]+$"), "{}", error);
+    assert!(error.contains("(1 problem(s))"), "{}", error);
+}
+
 #[tokio::test]
 async fn test_registry_debug_formatting() {
     let mut registry = ToolRegistry::new();
@@ -334,4 +587,433 @@ async fn test_concurrent_access() {
     for handle in handles {
         handle.await.unwrap();
     }
-}
\ No newline at end of file
+}
+struct RecordingMiddleware {
+    name: String,
+    log: Arc<std::sync::Mutex<Vec<String>>>,
+    veto: bool,
+}
+
+impl RecordingMiddleware {
+    fn new(name: &str, log: &Arc<std::sync::Mutex<Vec<String>>>) -> Self {
+        Self {
+            name: name.to_string(),
+            log: Arc::clone(log),
+            veto: false,
+        }
+    }
+
+    fn vetoing(mut self) -> Self {
+        self.veto = true;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolMiddleware for RecordingMiddleware {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn before(&self, call: &mut ToolInvocation, _context: &ToolContext) -> MiddlewareAction {
+        self.log.lock().unwrap().push(format!("before {}", self.name));
+        if self.veto {
+            return MiddlewareAction::Veto(Box::new(ToolResult::error(format!("vetoed by {}", self.name))));
+        }
+        call.params.set(&self.name, true);
+        MiddlewareAction::Continue
+    }
+
+    async fn after(&self, call: &ToolInvocation, _context: &ToolContext, result: &mut ToolResult) {
+        let id = call.id.as_deref().map(|id| format!(" {}", id)).unwrap_or_default();
+        self.log.lock().unwrap().push(format!("after {} ({}{})", self.name, call.tool, id));
+        result.content.push_str(&format!(" +{}", self.name));
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_wraps_tool_calls_in_order() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("exec_tool", "Tool for execution testing"));
+    registry.add_middleware(RecordingMiddleware::new("outer", &log));
+    registry.add_middleware(RecordingMiddleware::new("inner", &log));
+    assert_eq!(registry.middleware_names(), vec!["outer", "inner"]);
+
+    let context = create_test_context();
+    let result = registry.execute_tool("exec_tool", ToolParameters::new(), &context).await;
+
+    // Both middleware added a parameter before the tool ran, and the
+    // result passed back through them innermost first
+    assert_eq!(result.content, "Executed exec_tool with 2 parameters +inner +outer");
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["before outer", "before inner", "after inner (exec_tool)", "after outer (exec_tool)"]
+    );
+}
+
+#[tokio::test]
+async fn test_middleware_veto_skips_tool_and_later_middleware() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("exec_tool", "Tool for execution testing"));
+    registry.add_middleware(RecordingMiddleware::new("outer", &log));
+    registry.add_middleware(RecordingMiddleware::new("guard", &log).vetoing());
+    registry.add_middleware(RecordingMiddleware::new("inner", &log));
+
+    let context = create_test_context();
+    let result = registry.execute_tool("exec_tool", ToolParameters::new(), &context).await;
+
+    assert!(!result.success);
+    assert_eq!(result.error.as_deref(), Some("vetoed by guard"));
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["before outer", "before guard", "after guard (exec_tool)", "after outer (exec_tool)"]
+    );
+}
+
+#[tokio::test]
+async fn test_prepended_middleware_runs_first_and_sees_call_id() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("exec_tool", "Tool for execution testing"));
+    registry.add_middleware(RecordingMiddleware::new("inner", &log));
+    registry.prepend_middleware(RecordingMiddleware::new("outer", &log));
+    assert_eq!(registry.middleware_names(), vec!["outer", "inner"]);
+
+    let context = create_test_context();
+    let call = ToolInvocation::new("exec_tool", ToolParameters::new()).with_id("call_7");
+    let result = registry.execute(call, &context).await;
+
+    assert_eq!(result.content, "Executed exec_tool with 2 parameters +inner +outer");
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["before outer", "before inner", "after inner (exec_tool call_7)", "after outer (exec_tool call_7)"]
+    );
+}
+
+#[tokio::test]
+async fn test_redaction_and_output_limit_middleware() {
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("exec_tool", "Tool for execution testing"));
+    registry.add_middleware(OutputLimitMiddleware::new(30));
+    registry.add_middleware(RedactionMiddleware::new(vec![(
+        "tool-name".to_string(),
+        regex::Regex::new(r"exec_\w+").unwrap(),
+    )]));
+
+    let context = create_test_context();
+    let result = registry.execute_tool("exec_tool", ToolParameters::new(), &context).await;
+
+    // Redaction runs first on the way out, then the limit applies
+    assert_eq!(
+        result.content,
+        "Executed [REDACTED tool-name] \n[output truncated: 30 of 47 characters shown]"
+    );
+    assert!(result.metadata.truncated);
+}
+
+#[tokio::test]
+async fn test_redaction_leaves_exempt_tools_alone() {
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("exec_tool", "Tool for execution testing"));
+    registry.register(TestTool::new("read_tool", "Tool whose output is file content"));
+    registry.add_middleware(
+        RedactionMiddleware::new(vec![("tool-name".to_string(), regex::Regex::new(r"\w+_tool").unwrap())])
+            .with_exempt_tools(["read_tool"]),
+    );
+
+    let context = create_test_context();
+    let redacted = registry.execute_tool("exec_tool", ToolParameters::new(), &context).await;
+    let exempt = registry.execute_tool("read_tool", ToolParameters::new(), &context).await;
+
+    assert_eq!(redacted.content, "Executed [REDACTED tool-name] with 0 parameters");
+    assert_eq!(exempt.content, "Executed read_tool with 0 parameters");
+}
+
+/// Runs for a long time; a cooperative instance stops when cancelled and
+/// reports how far it got
+struct SlowTool {
+    cooperative: bool,
+}
+
+#[async_trait::async_trait]
+impl Tool for SlowTool {
+    fn name(&self) -> &str {
+        if self.cooperative { "slow_cooperative" } else { "slow_stubborn" }
+    }
+
+    fn description(&self) -> &str {
+        "Takes a long time"
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        HashMap::new()
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_secs(60))
+    }
+
+    async fn execute(&self, _params: ToolParameters, context: &ToolContext) -> ToolResult {
+        if self.cooperative {
+            context.cancellation.cancelled().await;
+            ToolResult::error("stopped after step 1 of 3".to_string())
+        } else {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            ToolResult::success("done".to_string())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_configure_timeouts_precedence() {
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("plain_tool", "No timeout of its own"));
+    registry.register(SlowTool { cooperative: true });
+
+    assert_eq!(registry.timeout_for("plain_tool"), None);
+    assert_eq!(registry.timeout_for("slow_cooperative"), Some(Duration::from_secs(60)));
+
+    registry.configure_timeouts("default=120, slow_cooperative=none").unwrap();
+    assert_eq!(registry.timeout_for("plain_tool"), Some(Duration::from_secs(120)));
+    assert_eq!(registry.timeout_for("slow_cooperative"), None);
+
+    registry.configure_timeouts("plain_tool=5").unwrap();
+    assert_eq!(registry.timeout_for("plain_tool"), Some(Duration::from_secs(5)));
+
+    assert!(registry.configure_timeouts("plain_tool").is_err());
+    assert!(registry.configure_timeouts("plain_tool=soon").is_err());
+}
+
+#[tokio::test]
+async fn test_timeout_lets_cooperative_tool_report_partial_result() {
+    let mut registry = ToolRegistry::new();
+    registry.register(SlowTool { cooperative: true });
+    registry.set_timeout("slow_cooperative", Some(Duration::from_millis(50)));
+
+    let context = create_test_context();
+    let result = registry.execute_tool("slow_cooperative", ToolParameters::new(), &context).await;
+
+    assert!(!result.success);
+    assert_eq!(result.error.as_deref(), Some("stopped after step 1 of 3"));
+    assert!(!context.is_cancelled());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeout_abandons_uncooperative_tool() {
+    let mut registry = ToolRegistry::new();
+    registry.register(SlowTool { cooperative: false });
+    registry.set_timeout("slow_stubborn", Some(Duration::from_millis(50)));
+
+    let context = create_test_context();
+    let result = registry.execute_tool("slow_stubborn", ToolParameters::new(), &context).await;
+
+    assert!(!result.success);
+    assert_eq!(result.error.as_deref(), Some("Tool 'slow_stubborn' timed out after 50ms"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancelling_context_stops_tool() {
+    let mut registry = ToolRegistry::new();
+    registry.register(SlowTool { cooperative: false });
+
+    let context = create_test_context();
+    let token = context.cancellation.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        token.cancel();
+    });
+    let result = registry.execute_tool("slow_stubborn", ToolParameters::new(), &context).await;
+
+    assert!(!result.success);
+    assert_eq!(result.error.as_deref(), Some("Tool 'slow_stubborn' was cancelled"));
+}
+
+#[test]
+fn test_deadline_pause_excludes_waiting_time() {
+    let deadline = Deadline::new(Duration::from_millis(100));
+    {
+        let _outer = deadline.pause();
+        let _inner = deadline.pause();
+        std::thread::sleep(Duration::from_millis(150));
+    }
+    assert!(!deadline.is_expired());
+    assert!(deadline.elapsed() < Duration::from_millis(100));
+
+    std::thread::sleep(Duration::from_millis(120));
+    assert!(deadline.is_expired());
+}
+
+#[tokio::test]
+async fn test_execute_tool_records_duration() {
+    let mut registry = ToolRegistry::new();
+    registry.register(TestTool::new("exec_tool", "Tool for execution testing"));
+
+    let context = create_test_context();
+    let result = registry.execute_tool("exec_tool", ToolParameters::new(), &context).await;
+
+    assert!(result.metadata.duration_ms.is_some());
+    assert!(!result.metadata.truncated);
+}
+
+#[test]
+fn test_tool_result_metadata() {
+    let result = ToolResult::success("full output".to_string())
+        .with_display("short".to_string())
+        .with_file_read("/work/a.rs", 10)
+        .with_file_written("/work/b.rs", 5)
+        .with_file_written("/work/c.rs", 7)
+        .with_file_deleted("/work/d.rs")
+        .with_exit_code(2);
+
+    assert_eq!(result.display_content(), "short");
+    assert_eq!(result.metadata.bytes_read, Some(10));
+    assert_eq!(result.metadata.bytes_written, Some(12));
+    let changed: Vec<_> = result.metadata.files_changed().map(|p| p.display().to_string()).collect();
+    assert_eq!(changed, vec!["/work/b.rs", "/work/c.rs", "/work/d.rs"]);
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["metadata"]["exit_code"], 2);
+    assert!(json["metadata"].get("artifacts").is_none());
+
+    // Results serialized before metadata existed still load
+    let old: ToolResult = serde_json::from_str(r#"{"success":false,"content":"","error":"boom"}"#).unwrap();
+    assert_eq!(old.metadata, Default::default());
+    assert_eq!(old.display_content(), "boom");
+}
+
+/// Read-only tool that records how many of its calls overlap
+struct ProbeTool {
+    running: Arc<std::sync::atomic::AtomicUsize>,
+    peak: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl Tool for ProbeTool {
+    fn name(&self) -> &str {
+        "probe"
+    }
+
+    fn description(&self) -> &str {
+        "Reads nothing, slowly"
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        HashMap::new()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
+    async fn execute(&self, params: ToolParameters, _context: &ToolContext) -> ToolResult {
+        use std::sync::atomic::Ordering;
+        let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        // Later calls finish first, so ordering comes from the registry
+        let id = params.get_optional::<u64>("id").ok().flatten().unwrap_or(0);
+        tokio::time::sleep(Duration::from_millis(50 - id * 10)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        ToolResult::success(format!("probe {}", id))
+    }
+}
+
+#[tokio::test]
+async fn test_read_only_run_stops_at_side_effects() {
+    let mut registry = ToolRegistry::new();
+    registry.register(ProbeTool { running: Default::default(), peak: Default::default() });
+    registry.register(TestTool::new("writer", "Has side effects"));
+
+    assert_eq!(registry.side_effects("probe"), SideEffects::ReadOnly);
+    assert_eq!(registry.side_effects("writer"), SideEffects::External);
+    assert_eq!(registry.side_effects("missing"), SideEffects::External);
+
+    assert_eq!(registry.read_only_run(["probe", "probe", "writer", "probe"]), 2);
+    assert_eq!(registry.read_only_run(["writer", "probe"]), 0);
+
+    registry.set_parallelism(1);
+    assert_eq!(registry.read_only_run(["probe", "probe"]), 0);
+}
+
+#[tokio::test]
+async fn test_execute_concurrently_keeps_call_order_and_limit() {
+    let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut registry = ToolRegistry::new();
+    registry.register(ProbeTool { running: Default::default(), peak: peak.clone() });
+    registry.set_parallelism(3);
+
+    let calls = (0..5)
+        .map(|id| {
+            let mut params = ToolParameters::new();
+            params.set("id", id);
+            ToolInvocation::new("probe", params)
+        })
+        .collect();
+    let context = create_test_context();
+    let results = registry.execute_concurrently(calls, &context).await;
+
+    let contents: Vec<_> = results.iter().map(|r| r.content.as_str()).collect();
+    assert_eq!(contents, vec!["probe 0", "probe 1", "probe 2", "probe 3", "probe 4"]);
+    assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_artifact_store_save_and_read() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = ArtifactStore::new(temp_dir.path().join("artifacts"));
+
+    let first = store.save("run_command", "first output").unwrap();
+    let second = store.save("pty:screen", "second output").unwrap();
+    assert_eq!(first, "run_command-1");
+    assert_eq!(second, "pty_screen-2");
+    assert_eq!(store.read(&first).unwrap(), "first output");
+    assert_eq!(store.read(&second).unwrap(), "second output");
+
+    assert!(store.read("run_command-9").is_err());
+    assert!(store.read("../secrets").is_err());
+    assert!(store.read("").is_err());
+}
+
+#[test]
+fn test_excerpt_keeps_head_and_tail() {
+    let content: String = (1..=100).map(|n| format!("line {:03}\n", n)).collect();
+    let text = excerpt(&content, 40);
+    assert_eq!(text, "line 001\nline 002\n[... 96 line(s) omitted ...]\nline 099\nline 100");
+
+    // Content that fits is left alone
+    assert_eq!(excerpt("short", 40), "short");
+
+    // A single long line still shows its start
+    let long = format!("{}\nend", "x".repeat(100));
+    assert!(excerpt(&long, 20).starts_with("xxxxxxxxxx...\n[... 1 line(s) omitted ...]"));
+}
+
+#[tokio::test]
+async fn test_spill_middleware_saves_large_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = Arc::new(ArtifactStore::new(temp_dir.path()));
+    let context = create_test_context().with_artifact_store(store.clone());
+    let spill = SpillMiddleware::new(100);
+    let content: String = (1..=50).map(|n| format!("row {}\n", n)).collect();
+
+    let call = ToolInvocation::new("run_command", ToolParameters::new());
+    let mut result = ToolResult::success(content.clone());
+    spill.after(&call, &context, &mut result).await;
+
+    assert!(result.content.starts_with("row 1\n"));
+    assert!(result.content.contains("handle 'run_command-1'"));
+    assert!(result.content.len() < content.len());
+    assert!(result.metadata.truncated);
+    assert_eq!(result.metadata.artifacts, vec![store.path("run_command-1").unwrap()]);
+    assert_eq!(store.read("run_command-1").unwrap(), content);
+
+    // Small output and sessions without a store are untouched
+    let mut small = ToolResult::success("ok".to_string());
+    spill.after(&call, &context, &mut small).await;
+    assert_eq!(small.content, "ok");
+
+    let mut unstored = ToolResult::success(content.clone());
+    spill.after(&call, &create_test_context(), &mut unstored).await;
+    assert_eq!(unstored.content, content);
+}
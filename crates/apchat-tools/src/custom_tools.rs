@@ -0,0 +1,323 @@
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects};
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_policy::{ActionType, Decision};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use colored::Colorize;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::system::run_shell_command;
+
+/// Timeout of custom tools that do not set `timeout_secs`, as for `run_command`
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Parameter types a command template can take
+const PARAMETER_TYPES: [&str; 4] = ["string", "integer", "number", "boolean"];
+
+/// A project-local tool described by a TOML manifest in `.apchat/tools/`:
+///
+/// ```toml
+/// name = "run_migrations"
+/// description = "Apply pending database migrations"
+/// command = "diesel migration run --database-url {{database_url}}"
+/// working_dir = "backend"
+/// timeout_secs = 600
+/// action = "command_execution"
+///
+/// [parameters.database_url]
+/// type = "string"
+/// description = "Database to migrate"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolManifest {
+    pub name: String,
+    pub description: String,
+    /// Shell command line; `{{param}}` is replaced by the shell-quoted argument
+    pub command: String,
+    /// Directory to run in, relative to the workspace
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Policy action the rendered command is checked as
+    #[serde(default = "default_action")]
+    pub action: ActionType,
+    #[serde(default = "default_side_effects")]
+    pub side_effects: SideEffects,
+    #[serde(default)]
+    pub parameters: BTreeMap<String, ManifestParameter>,
+}
+
+/// One typed parameter of a custom tool
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestParameter {
+    #[serde(rename = "type", default = "default_parameter_type")]
+    pub param_type: String,
+    #[serde(default)]
+    pub description: String,
+    /// Defaults to true unless the parameter has a default
+    #[serde(default)]
+    pub required: Option<bool>,
+    #[serde(default)]
+    pub default: Option<Value>,
+    #[serde(default, rename = "enum")]
+    pub enum_values: Option<Vec<Value>>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub minimum: Option<f64>,
+    #[serde(default)]
+    pub maximum: Option<f64>,
+}
+
+fn default_action() -> ActionType {
+    ActionType::CommandExecution
+}
+
+fn default_side_effects() -> SideEffects {
+    SideEffects::External
+}
+
+fn default_parameter_type() -> String {
+    "string".to_string()
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap())
+}
+
+/// Directory holding a project's custom tool manifests
+pub fn custom_tools_dir(work_dir: &Path) -> PathBuf {
+    work_dir.join(".apchat").join("tools")
+}
+
+/// Load every `*.toml` manifest in `dir`, in file name order. A missing
+/// directory simply has no tools.
+pub fn load_custom_tools(dir: &Path) -> Vec<Result<CustomTool>> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+    paths.iter().map(|path| CustomTool::from_file(path)).collect()
+}
+
+/// A [`Tool`] that runs the command of a [`ToolManifest`]
+#[derive(Debug, Clone)]
+pub struct CustomTool {
+    manifest: ToolManifest,
+}
+
+impl CustomTool {
+    /// Check a manifest and build its tool
+    pub fn new(manifest: ToolManifest) -> Result<Self> {
+        let valid_name = |name: &str| {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        };
+        if !valid_name(&manifest.name) {
+            anyhow::bail!("Tool name '{}' may only contain letters, digits, '_' and '-'", manifest.name);
+        }
+        if manifest.command.trim().is_empty() {
+            anyhow::bail!("Tool '{}' has an empty command", manifest.name);
+        }
+
+        for (name, parameter) in &manifest.parameters {
+            let valid_parameter = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_parameter {
+                anyhow::bail!("Parameter name '{}' must be a letter or '_' followed by letters, digits or '_'", name);
+            }
+            if !PARAMETER_TYPES.contains(&parameter.param_type.as_str()) {
+                anyhow::bail!(
+                    "Parameter '{}' has type '{}'; expected one of {}",
+                    name,
+                    parameter.param_type,
+                    PARAMETER_TYPES.join(", ")
+                );
+            }
+            if let Some(ref pattern) = parameter.pattern {
+                Regex::new(pattern).with_context(|| format!("Parameter '{}' has an invalid pattern", name))?;
+            }
+        }
+
+        for placeholder in placeholder_regex().captures_iter(&manifest.command) {
+            if !manifest.parameters.contains_key(&placeholder[1]) {
+                anyhow::bail!("Command uses '{}', which is not a declared parameter", &placeholder[0]);
+            }
+            // Arguments are quoted when rendered; inside quotes that quoting
+            // would end the surrounding string instead of protecting the value
+            if is_quoted_at(&manifest.command, placeholder.get(0).unwrap().start()) {
+                anyhow::bail!(
+                    "Command uses '{}' inside quotes; placeholders are quoted for you, so write it unquoted",
+                    &placeholder[0]
+                );
+            }
+        }
+
+        if let Some(ref dir) = manifest.working_dir {
+            if !dir.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+                anyhow::bail!("working_dir '{}' must be a path inside the workspace", dir.display());
+            }
+        }
+
+        Ok(Self { manifest })
+    }
+
+    /// Parse and check a manifest
+    pub fn parse(text: &str) -> Result<Self> {
+        let manifest: ToolManifest = toml::from_str(text)?;
+        Self::new(manifest)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid tool manifest {}", path.display()))
+    }
+
+    pub fn manifest(&self) -> &ToolManifest {
+        &self.manifest
+    }
+
+    /// The command line for a call, with every placeholder replaced by its
+    /// shell-quoted argument. Optional arguments that were not given and have
+    /// no default are left out.
+    pub fn render(&self, params: &ToolParameters) -> String {
+        placeholder_regex()
+            .replace_all(&self.manifest.command, |captures: &regex::Captures| {
+                let name = &captures[1];
+                let value = params
+                    .data
+                    .get(name)
+                    .filter(|value| !value.is_null())
+                    .or_else(|| self.manifest.parameters.get(name).and_then(|p| p.default.as_ref()));
+                match value {
+                    Some(Value::String(text)) => shell_quote(text),
+                    Some(value) => shell_quote(&value.to_string()),
+                    None => String::new(),
+                }
+            })
+            .into_owned()
+    }
+}
+
+/// Whether byte `index` of `command` is inside a single- or double-quoted span
+fn is_quoted_at(command: &str, index: usize) -> bool {
+    let mut quote = None;
+    let mut escaped = false;
+    for (position, c) in command.char_indices() {
+        if position >= index {
+            break;
+        }
+        match (quote, c) {
+            _ if escaped => escaped = false,
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => escaped = true,
+            (None, '\'' | '"') => quote = Some(c),
+            (Some('"'), '"') => quote = None,
+            _ => {}
+        }
+    }
+    quote.is_some()
+}
+
+/// Quote `text` as a single shell word
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[async_trait]
+impl Tool for CustomTool {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn description(&self) -> &str {
+        &self.manifest.description
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        self.manifest
+            .parameters
+            .iter()
+            .map(|(name, parameter)| {
+                let definition = ParameterDefinition {
+                    param_type: parameter.param_type.clone(),
+                    description: parameter.description.clone(),
+                    required: parameter.required.unwrap_or(parameter.default.is_none()),
+                    default: parameter.default.clone(),
+                    enum_values: parameter.enum_values.clone(),
+                    pattern: parameter.pattern.clone(),
+                    minimum: parameter.minimum,
+                    maximum: parameter.maximum,
+                    ..Default::default()
+                };
+                (name.clone(), definition)
+            })
+            .collect()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        self.manifest.side_effects
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_secs(self.manifest.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)))
+    }
+
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let command = self.render(&params);
+        let action = &self.manifest.action;
+
+        // The builtin safety rules for shell commands apply whatever action
+        // class the manifest declares
+        let policy_decision = context.policy_manager.decide_command_line(action, &command);
+        if policy_decision.decision == Decision::Deny {
+            return ToolResult::error(match policy_decision.reason() {
+                Some(reason) => format!("Command blocked by policy: {} ({})", reason, policy_decision.source()),
+                None => format!("Command blocked by policy: {}", policy_decision.source()),
+            });
+        }
+
+        print!("{} {} ", format!("Run {}:", self.manifest.name).yellow(), command.cyan());
+        std::io::stdout().flush().ok();
+
+        let (approved, rejection_reason) = match context
+            .resolve_decision(action.clone(), &command, "Execute? (y/N):", policy_decision)
+            .await
+        {
+            Ok((approved, reason)) => (approved, reason),
+            Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
+        };
+
+        if !approved {
+            let error_msg = if let Some(reason) = rejection_reason {
+                format!("Command cancelled by user: {}", reason)
+            } else {
+                "Command cancelled by user or policy".to_string()
+            };
+            return ToolResult::error(error_msg);
+        }
+
+        println!("{} {}", "Running:".green(), command.cyan());
+
+        let dir = match self.manifest.working_dir {
+            Some(ref dir) => context.work_dir.join(dir),
+            None => context.work_dir.clone(),
+        };
+        run_shell_command(&command, &dir, context).await
+    }
+}
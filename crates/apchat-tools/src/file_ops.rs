@@ -1,5 +1,7 @@
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::{EditFileArgs, ListFilesArgs, OpenFileArgs, ReadFileArgs, WriteFileArgs};
+use apchat_models::ToolArgs;
 use apchat_logging::get_logs_dir;
 use crate::open_file;
 use async_trait::async_trait;
@@ -22,25 +24,22 @@ impl Tool for OpenFileTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("file_path", "string", "Path to the file relative to the work directory", required),
-            param!("start_line", "integer", "Starting line number (1-based)", optional),
-            param!("end_line", "integer", "Ending line number (1-based)", optional),
-        ])
+        OpenFileArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let file_path = match params.get_required::<String>("file_path") {
-            Ok(path) => path,
+        let OpenFileArgs { file_path, start_line, end_line } = match OpenFileArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        let start_line = params.get_optional::<i32>("start_line").unwrap_or(None);
-        let end_line = params.get_optional::<i32>("end_line").unwrap_or(None);
-
         let line_range = if let (Some(start), Some(end)) = (start_line, end_line) {
             if start > 0 && end > 0 {
-                Some((start as usize)..=(end as usize))
+                Some(start..=end)
             } else {
                 None
             }
@@ -48,8 +47,28 @@ impl Tool for OpenFileTool {
             None
         };
 
-        match open_file::open_file(&context.work_dir, &file_path, line_range).await {
-            Ok(content) => ToolResult::success(content),
+        let resolved = match context.resolve_path(&file_path) {
+            Ok(resolved) => resolved,
+            Err(e) => return ToolResult::error(format!("Failed to open file: {:#}", e)),
+        };
+        if let Err(result) = check_read(context, &resolved.policy_target, &file_path).await {
+            return result;
+        }
+
+        match open_file::open_file(&context.work_dir, &context.allowed_roots, &file_path, line_range).await {
+            Ok(content) => {
+                let path = resolved.absolute;
+                let bytes = content.len() as u64;
+                let lines: Vec<&str> = content.lines().collect();
+                // The user sees the first lines only; the model gets everything
+                let display = (lines.len() > 10)
+                    .then(|| format!("{}\n\n...and {} more lines", lines[..10].join("\n"), lines.len() - 10));
+                let result = ToolResult::success(content.clone()).with_file_read(path, bytes);
+                match display {
+                    Some(display) => result.with_display(display),
+                    None => result,
+                }
+            }
             Err(e) => ToolResult::error(format!("Failed to open file: {}", e)),
         }
     }
@@ -69,19 +88,27 @@ impl Tool for ReadFileTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("file_path", "string", "Path to the file relative to the work directory", required),
-        ])
+        ReadFileArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let file_path = match params.get_required::<String>("file_path") {
-            Ok(path) => path,
+        let ReadFileArgs { file_path } = match ReadFileArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        // Use the same logic as the original read_file method
-        let full_path = context.work_dir.join(&file_path);
+        let resolved = match context.resolve_path(&file_path) {
+            Ok(resolved) => resolved,
+            Err(e) => return ToolResult::error(format!("{:#}", e)),
+        };
+        if let Err(result) = check_read(context, &resolved.policy_target, &file_path).await {
+            return result;
+        }
+        let full_path = resolved.absolute;
         if !full_path.exists() {
             // Check for directory with similar name
             if let Some(stem) = full_path.file_stem().and_then(|s| s.to_str()) {
@@ -109,6 +136,7 @@ impl Tool for ReadFileTool {
 
         match fs::read_to_string(&full_path) {
             Ok(content) => {
+                let bytes = content.len() as u64;
                 let lines: Vec<&str> = content.lines().collect();
                 let total_lines = lines.len();
 
@@ -119,13 +147,25 @@ impl Tool for ReadFileTool {
                     format!("{}\n[{} more lines]", preview_lines, total_lines - 10)
                 };
 
-                ToolResult::success(preview)
+                ToolResult::success(preview).with_file_read(full_path, bytes)
             }
             Err(e) => ToolResult::error(format!("Failed to read file: {}", e)),
         }
     }
 }
 
+/// Refuse a read that a `file_read` rule denies or the user declines
+async fn check_read(context: &ToolContext, policy_target: &str, file_path: &str) -> Result<(), ToolResult> {
+    match context.check_read(policy_target, &format!("Read {}? [Y/n]", file_path)).await {
+        Ok((true, _)) => Ok(()),
+        Ok((false, reason)) => Err(ToolResult::error(format!(
+            "Read cancelled: {}",
+            reason.unwrap_or_else(|| "rejected by user or policy".to_string())
+        ))),
+        Err(e) => Err(ToolResult::error(format!("Permission check failed: {}", e))),
+    }
+}
+
 /// Tool for writing content to files
 pub struct WriteFileTool;
 
@@ -140,24 +180,41 @@ impl Tool for WriteFileTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("file_path", "string", "Path to the file relative to the work directory", required),
-            param!("content", "string", "Content to write to the file", required),
-        ])
+        WriteFileArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::MutatesWorkspace
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let file_path = match params.get_required::<String>("file_path") {
-            Ok(path) => path,
+        let WriteFileArgs { file_path, content } = match WriteFileArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        let content = match params.get_required::<String>("content") {
-            Ok(content) => content,
-            Err(e) => return ToolResult::error(e.to_string()),
+        let resolved = match context.resolve_path(&file_path) {
+            Ok(resolved) => resolved,
+            Err(e) => return ToolResult::error(format!("{:#}", e)),
         };
 
-        let full_path = context.work_dir.join(&file_path);
+        // The target and, through content rules, the content must be allowed
+        match context.check_permission_with_content(
+            apchat_policy::ActionType::FileWrite,
+            &resolved.policy_target,
+            &content,
+            &format!("Write {}? [Y/n]", file_path),
+        ).await {
+            Ok((true, _)) => {}
+            Ok((false, reason)) => {
+                return ToolResult::error(format!(
+                    "Write cancelled: {}",
+                    reason.unwrap_or_else(|| "rejected by user or policy".to_string())
+                ))
+            }
+            Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
+        }
+        let full_path = resolved.absolute;
 
         // Create parent directories if they don't exist
         if let Some(parent) = full_path.parent() {
@@ -166,8 +223,9 @@ impl Tool for WriteFileTool {
             }
         }
 
-        match fs::write(&full_path, content) {
-            Ok(_) => ToolResult::success(format!("Successfully wrote to file: {}", file_path)),
+        match fs::write(&full_path, &content) {
+            Ok(_) => ToolResult::success(format!("Successfully wrote to file: {}", file_path))
+                .with_file_written(full_path, content.len() as u64),
             Err(e) => ToolResult::error(format!("Failed to write file: {}", e)),
         }
     }
@@ -187,26 +245,16 @@ impl Tool for EditFileTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("file_path", "string", "Path to the file relative to the work directory", required),
-            param!("old_content", "string", "Old content to find and replace (must not be empty)", required),
-            param!("new_content", "string", "New content to replace with", required),
-        ])
+        EditFileArgs::parameters()
     }
 
-    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let file_path = match params.get_required::<String>("file_path") {
-            Ok(path) => path,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
-
-        let old_content = match params.get_required::<String>("old_content") {
-            Ok(content) => content,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::MutatesWorkspace
+    }
 
-        let new_content = match params.get_required::<String>("new_content") {
-            Ok(content) => content,
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let EditFileArgs { file_path, old_content, new_content } = match EditFileArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
@@ -214,14 +262,18 @@ impl Tool for EditFileTool {
             return ToolResult::error("old_content must not be empty".to_string());
         }
 
-        let full_path = context.work_dir.join(&file_path);
+        let resolved = match context.resolve_path(&file_path) {
+            Ok(resolved) => resolved,
+            Err(e) => return ToolResult::error(format!("{:#}", e)),
+        };
+        let full_path = &resolved.absolute;
 
         if !full_path.exists() {
             return ToolResult::error(format!("File not found: {}", file_path));
         }
 
         // Read current content
-        let current_content = match fs::read_to_string(&full_path) {
+        let current_content = match fs::read_to_string(full_path) {
             Ok(content) => content,
             Err(e) => return ToolResult::error(format!("Failed to read file: {}", e)),
         };
@@ -295,19 +347,21 @@ impl Tool for EditFileTool {
         }
 
         // Check permission using policy system
-        let (approved, rejection_reason) = match context.check_permission(
+        let (approved, rejection_reason) = match context.check_permission_with_content(
             apchat_policy::ActionType::FileEdit,
-            &file_path,
+            &resolved.policy_target,
+            &new_content,
             "Apply these changes? [Y/n]"
-        ) {
+        ).await {
             Ok((approved, reason)) => (approved, reason),
             Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
         };
 
         if approved {
             // Write back to file
-            match fs::write(&full_path, new_content_full) {
-                Ok(_) => ToolResult::success(format!("✅ Successfully edited {} ({} replacement(s))", file_path, occurrences)),
+            match fs::write(full_path, &new_content_full) {
+                Ok(_) => ToolResult::success(format!("✅ Successfully edited {} ({} replacement(s))", file_path, occurrences))
+                    .with_file_written(full_path.clone(), new_content_full.len() as u64),
                 Err(e) => ToolResult::error(format!("Failed to write file: {}", e)),
             }
         } else {
@@ -335,15 +389,18 @@ impl Tool for ListFilesTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("pattern", "string", "Glob pattern (e.g., 'src/**/*.rs', '**/*.json'). Use ** for recursive search. Defaults to '*' (files in current directory). Respects .gitignore and limits to 1000 results.", optional, "*"),
-        ])
+        ListFilesArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let pattern = params.get_optional::<String>("pattern")
-            .unwrap_or(Some("*".to_string()))
-            .unwrap_or_else(|| "*".to_string());
+        let ListFilesArgs { pattern } = match ListFilesArgs::parse(&params) {
+            Ok(args) => args,
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
 
         eprintln!("[DEBUG] list_files with pattern: '{}' in work_dir: {:?}", pattern, context.work_dir);
 
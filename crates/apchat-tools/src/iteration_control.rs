@@ -1,5 +1,7 @@
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::RequestMoreIterationsArgs;
+use apchat_models::ToolArgs;
 use async_trait::async_trait;
 use std::collections::HashMap;
 
@@ -18,31 +20,18 @@ impl Tool for RequestMoreIterationsTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("additional_iterations", "integer", "Number of additional iterations requested (1-10)", required),
-            param!("justification", "string", "Detailed explanation of why more iterations are needed and what will be accomplished", required),
-            param!("progress_summary", "string", "Summary of progress made so far and current findings", required),
-        ])
+        RequestMoreIterationsArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, _context: &ToolContext) -> ToolResult {
-        let additional = match params.get_required::<i32>("additional_iterations") {
-            Ok(val) => val,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
-
-        let justification = match params.get_required::<String>("justification") {
-            Ok(val) => val,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
-
-        let progress_summary = match params.get_required::<String>("progress_summary") {
-            Ok(val) => val,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
+        let RequestMoreIterationsArgs { additional_iterations: additional, justification, progress_summary } =
+            match RequestMoreIterationsArgs::parse(&params) {
+                Ok(args) => args,
+                Err(e) => return ToolResult::error(e.to_string()),
+            };
 
         // Validate request
-        if additional < 1 || additional > 10 {
+        if !(1..=10).contains(&additional) {
             return ToolResult::error("Additional iterations must be between 1 and 10".to_string());
         }
 
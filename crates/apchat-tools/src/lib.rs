@@ -1,7 +1,8 @@
 //! Tool modules for APChat
 //!
 //! This module contains all available tools that can be used by AI models,
-//! organized by functionality (file operations, search, system, model management, project tools).
+//! organized by functionality (file operations, search, system, model management, project tools,
+//! paging through large tool output, finding tools on demand), plus project-local tools declared in TOML manifests.
 
 pub mod file_ops;
 pub mod search;
@@ -15,6 +16,9 @@ pub mod todo_tools;
 pub mod terminal_tools;
 pub mod open_file;
 pub mod subagent_tools;
+pub mod output_tools;
+pub mod tool_search;
+pub mod custom_tools;
 
 pub use file_ops::*;
 pub use search::*;
@@ -25,3 +29,6 @@ pub use skill_tools::*;
 pub use todo_tools::*;
 pub use terminal_tools::*;
 pub use subagent_tools::*;
+pub use output_tools::*;
+pub use tool_search::*;
+pub use custom_tools::*;
@@ -1,5 +1,9 @@
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{
+    ConfirmationRequest, MiddlewareAction, ParameterDefinition, SideEffects, Tool, ToolInvocation, ToolMiddleware,
+    ToolParameters, ToolResult,
+};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::{SwitchModelArgs, ToolArgs};
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::fs;
@@ -7,22 +11,30 @@ use std::path::PathBuf;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use similar::{ChangeTag, TextDiff};
-use rustyline::DefaultEditor;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToolArgs)]
 struct EditOperation {
+    /// Path to the file relative to the work directory
     file_path: String,
+    /// Exact content to replace
     old_content: String,
+    /// Content to replace it with
     new_content: String,
+    /// What the edit does
     description: String,
 }
 
-/// Tool for switching between AI models
-pub struct SwitchModelTool {
-    // This will need to be connected to the main application state
-    // For now, it's a placeholder that returns information about the switch
+#[derive(Debug, Deserialize, ToolArgs)]
+struct PlanEditsArgs {
+    /// Edit operations to apply, in order
+    #[tool(min_items = 1)]
+    edits: Vec<EditOperation>,
 }
 
+/// Tool for switching between AI models. It checks the policy; the session
+/// running the call applies the switch once the tool succeeds.
+pub struct SwitchModelTool {}
+
 impl SwitchModelTool {
     pub fn new() -> Self {
         Self { }
@@ -40,34 +52,34 @@ impl Tool for SwitchModelTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("model", "string", "Model to switch to (kimi or gpt_oss)", required),
-            param!("reason", "string", "Reason for switching models", required),
-        ])
+        SwitchModelArgs::parameters()
     }
 
-    async fn execute(&self, params: ToolParameters, _context: &ToolContext) -> ToolResult {
-        let model = match params.get_required::<String>("model") {
-            Ok(model) => model,
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let SwitchModelArgs { model, reason } = match SwitchModelArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        let reason = match params.get_required::<String>("reason") {
-            Ok(reason) => reason,
-            Err(e) => return ToolResult::error(e.to_string()),
+        let (approved, rejection_reason) = match context.check_permission(
+            apchat_policy::ActionType::ModelSwitch,
+            &model,
+            &format!("Switch to model '{}'? (y/N):", model)
+        ).await {
+            Ok((approved, reason)) => (approved, reason),
+            Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
         };
 
-        // Validate model name
-        if !["kimi", "gpt_oss", "blu_model", "grn_model", "anthropic"].contains(&model.as_str()) {
-            return ToolResult::error("Invalid model. Available models: kimi, gpt_oss, blu_model, grn_model, anthropic".to_string());
+        if !approved {
+            let error_msg = if let Some(reason) = rejection_reason {
+                format!("Model switch cancelled by user: {}", reason)
+            } else {
+                "Model switch cancelled by user or policy".to_string()
+            };
+            return ToolResult::error(error_msg);
         }
 
-        // For now, return information about the requested switch
-        // In the full implementation, this would trigger an actual model switch
-        let message = format!(
-            "Model switch requested:\n  Target model: {}\n  Reason: {}\n  Note: Actual model switching will be implemented in the main application layer",
-            model, reason
-        );
+        let message = format!("Model switch to '{}' approved - Reason: {}", model, reason);
 
         ToolResult::success(message)
     }
@@ -144,28 +156,19 @@ impl Tool for PlanEditsTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("edits", "string", "JSON array of edit operations with file_path, old_content, new_content, and description fields", required),
-        ])
+        PlanEditsArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::MutatesWorkspace
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        // Parse edits from parameters
-        let edits_str = match params.get_required::<String>("edits") {
-            Ok(edits) => edits,
+        let PlanEditsArgs { edits } = match PlanEditsArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        let edits_value: serde_json::Value = match serde_json::from_str(&edits_str) {
-            Ok(value) => value,
-            Err(e) => return ToolResult::error(format!("Failed to parse edits JSON: {}", e)),
-        };
-
-        let edits: Vec<EditOperation> = match serde_json::from_value(edits_value) {
-            Ok(edits) => edits,
-            Err(e) => return ToolResult::error(format!("Failed to parse edits: {}", e)),
-        };
-
         if edits.is_empty() {
             return ToolResult::error("Cannot create empty edit plan. Provide at least one edit operation.".to_string());
         }
@@ -183,8 +186,11 @@ impl Tool for PlanEditsTool {
             );
 
             // Read current file to validate old_content exists
-            let full_path = context.work_dir.join(&edit.file_path);
-            let current_content = match fs::read_to_string(&full_path) {
+            let resolved = match context.resolve_path(&edit.file_path) {
+                Ok(resolved) => resolved,
+                Err(e) => return ToolResult::error(format!("Edit #{}: {:#}", idx + 1, e)),
+            };
+            let current_content = match fs::read_to_string(&resolved.absolute) {
                 Ok(content) => content,
                 Err(_) => return ToolResult::error(format!("Edit #{}: File not found: {}", idx + 1, edit.file_path)),
             };
@@ -207,6 +213,24 @@ impl Tool for PlanEditsTool {
                 ));
             }
 
+            // Content rules keep secrets out of the workspace
+            match context.check_content(
+                apchat_policy::ActionType::FileEdit,
+                &resolved.policy_target,
+                &edit.new_content,
+                &format!("Plan edit #{} to {}? [Y/n]", idx + 1, edit.file_path),
+            ).await {
+                Ok((true, _)) => {}
+                Ok((false, reason)) => {
+                    return ToolResult::error(format!(
+                        "Edit #{}: rejected: {}",
+                        idx + 1,
+                        reason.unwrap_or_else(|| "cancelled by user or policy".to_string())
+                    ))
+                }
+                Err(e) => return ToolResult::error(format!("Edit #{}: Permission check failed: {}", idx + 1, e)),
+            }
+
             // Show unified diff preview
             let diff_output = show_unified_diff(&edit.old_content, &edit.new_content);
             if !diff_output.is_empty() {
@@ -256,6 +280,10 @@ impl Tool for ApplyEditPlanTool {
         HashMap::new()
     }
 
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::MutatesWorkspace
+    }
+
     async fn execute(&self, _params: ToolParameters, context: &ToolContext) -> ToolResult {
         // Load the plan
         let plan = match load_edit_plan(&context.work_dir) {
@@ -265,91 +293,46 @@ impl Tool for ApplyEditPlanTool {
 
         println!("\n{}", "🚀 Applying Edit Plan".bright_cyan().bold());
         println!("{}", "═".repeat(60).bright_black());
-        println!("{} {} edit(s) will be applied:", "📋".cyan(), plan.len());
-
-        // Show summary of all edits
-        for (idx, edit) in plan.iter().enumerate() {
-            println!("  {}. {} - {}",
-                idx + 1,
-                edit.file_path.bright_white(),
-                edit.description.cyan()
-            );
-        }
-
-        println!("{}", "═".repeat(60).bright_black());
-
-        // Check if we need to ask for confirmation
-        // In non-interactive mode (web/API), skip prompt - already confirmed via web UI
-        if !context.non_interactive {
-            // Ask for confirmation in interactive mode
-            println!("\n{}", "Apply all these changes? [Y/n]".bright_green().bold());
-
-            let mut rl = match DefaultEditor::new() {
-                Ok(rl) => rl,
-                Err(e) => return ToolResult::error(format!("Failed to create readline editor: {}", e)),
-            };
-
-            let response = match rl.readline(">>> ") {
-                Ok(resp) => resp,
-                Err(_) => {
-                    clear_edit_plan(&context.work_dir);
-                    return ToolResult::error("Edit plan application cancelled by user".to_string());
-                }
-            };
-
-            let response = response.trim().to_lowercase();
-
-            match response.as_str() {
-                "" | "y" | "yes" => {
-                    // Continue with applying edits
-                    println!("\n{}", "Applying edits...".green());
-                }
-                _ => {
-                    // Cancelled - ask for optional feedback
-                    println!("{}", "Would you like to provide feedback to the model about why you rejected this? (optional)".bright_yellow());
-                    println!("{}", "Press Enter to skip, or type your feedback:".bright_black());
-
-                    let feedback = match rl.readline("") {
-                        Ok(fb) if !fb.trim().is_empty() => format!(" - {}", fb.trim()),
-                        _ => String::new(),
-                    };
-
-                    clear_edit_plan(&context.work_dir);
-                    return ToolResult::error(format!("Edit plan application cancelled by user{}", feedback));
-                }
-            }
-        } else {
-            // Non-interactive mode - auto-confirm (already confirmed via web UI)
-            println!("\n{}", "✓ Confirmed via web UI - Applying edits...".green());
-        }
 
         // Apply all edits sequentially
         let mut results = Vec::new();
+        // Files already changed, reported even if a later edit fails
+        let mut written = Vec::new();
         for (idx, edit) in plan.iter().enumerate() {
             println!("\n{} {}", format!("Applying edit #{}", idx + 1).yellow(), edit.file_path.cyan());
 
-            // Re-read file to get current state (in case previous edits affected it)
-            let full_path = context.work_dir.join(&edit.file_path);
+            // Re-read file to get current state (in case previous edits affected it).
+            // The plan file may have been edited since it was validated, so check again.
+            let full_path = match context.resolve_path(&edit.file_path) {
+                Ok(resolved) => resolved.absolute,
+                Err(e) => {
+                    clear_edit_plan(&context.work_dir);
+                    return with_files_written(ToolResult::error(format!(
+                        "Edit #{} failed: {:#}. Edit plan aborted and cleared.",
+                        idx + 1, e
+                    )), written);
+                }
+            };
             let current_content = match fs::read_to_string(&full_path) {
                 Ok(content) => content,
                 Err(_) => {
                     clear_edit_plan(&context.work_dir);
-                    return ToolResult::error(format!(
+                    return with_files_written(ToolResult::error(format!(
                         "Edit #{} failed: File not found: {}. Edit plan aborted and cleared.",
                         idx + 1, edit.file_path
-                    ));
+                    )), written);
                 }
             };
 
             // Check if content still exists (might have changed due to previous edits)
             if !current_content.contains(&edit.old_content) {
                 clear_edit_plan(&context.work_dir);
-                return ToolResult::error(format!(
+                return with_files_written(ToolResult::error(format!(
                     "Edit #{} failed: old_content no longer found in {}. \
                     A previous edit in this plan may have affected this file. \
                     Edit plan aborted at step {}. No further edits applied. Plan has been cleared.",
                     idx + 1, edit.file_path, idx + 1
-                ));
+                )), written);
             }
 
             // Apply the edit
@@ -358,12 +341,13 @@ impl Tool for ApplyEditPlanTool {
             // Write the updated content
             if let Err(e) = fs::write(&full_path, &updated_content) {
                 clear_edit_plan(&context.work_dir);
-                return ToolResult::error(format!(
+                return with_files_written(ToolResult::error(format!(
                     "Edit #{} failed: Failed to write file {}: {}. Edit plan aborted and cleared.",
                     idx + 1, edit.file_path, e
-                ));
+                )), written);
             }
 
+            written.push((full_path, updated_content.len() as u64));
             results.push(format!("✓ {}", edit.file_path));
             println!("  {} {}", "✓".green(), edit.description);
         }
@@ -374,10 +358,87 @@ impl Tool for ApplyEditPlanTool {
         // Clear the plan after successful application
         clear_edit_plan(&context.work_dir);
 
-        ToolResult::success(format!(
-            "Successfully applied {} edit(s):\n{}",
-            plan.len(),
-            results.join("\n")
-        ))
+        with_files_written(
+            ToolResult::success(format!(
+                "Successfully applied {} edit(s):\n{}",
+                plan.len(),
+                results.join("\n")
+            )),
+            written,
+        )
     }
-}
\ No newline at end of file
+}
+
+fn with_files_written(result: ToolResult, written: Vec<(PathBuf, u64)>) -> ToolResult {
+    written
+        .into_iter()
+        .fold(result, |result, (path, bytes)| result.with_file_written(path, bytes))
+}
+
+/// Asks the user before `apply_edit_plan` runs, showing the planned edits.
+/// The question goes through the context's confirmation provider, so the
+/// terminal, the web UI and a subagent's parent all see it. Sessions without
+/// anyone to ask apply the plan unconfirmed.
+pub struct EditPlanConfirmation;
+
+#[async_trait]
+impl ToolMiddleware for EditPlanConfirmation {
+    fn name(&self) -> &str {
+        "edit_plan_confirmation"
+    }
+
+    async fn before(&self, call: &mut ToolInvocation, context: &ToolContext) -> MiddlewareAction {
+        if call.tool != "apply_edit_plan" || (context.non_interactive && context.confirmation_provider.is_none()) {
+            return MiddlewareAction::Continue;
+        }
+        // Without a plan the tool reports the problem itself
+        let Ok(plan) = load_edit_plan(&context.work_dir) else {
+            return MiddlewareAction::Continue;
+        };
+
+        let mut prompt = format!("{} edit(s) will be applied:\n", plan.len());
+        for (idx, edit) in plan.iter().enumerate() {
+            prompt.push_str(&format!("  {}. {} - {}\n", idx + 1, edit.file_path, edit.description));
+        }
+        prompt.push_str("Apply all these changes? [Y/n]");
+
+        let request = ConfirmationRequest {
+            action: apchat_policy::ActionType::ApplyEditPlan,
+            target: plan.iter().map(|edit| edit.file_path.as_str()).collect::<Vec<_>>().join(", "),
+            prompt,
+            preview: Some(plan_preview(&plan)),
+            session_id: context.session_id.clone(),
+            origin: None,
+        };
+        let response = match context.confirmation_provider().confirm(&request).await {
+            Ok(response) => response,
+            Err(e) => return MiddlewareAction::Veto(Box::new(ToolResult::error(format!("Confirmation failed: {}", e)))),
+        };
+        if response.approved {
+            return MiddlewareAction::Continue;
+        }
+
+        clear_edit_plan(&context.work_dir);
+        let feedback = response.reason.map(|reason| format!(" - {}", reason)).unwrap_or_default();
+        MiddlewareAction::Veto(Box::new(ToolResult::error(format!(
+            "Edit plan application cancelled by user{}",
+            feedback
+        ))))
+    }
+}
+
+/// The planned edits as plain `-`/`+` lines, for confirmation prompts
+fn plan_preview(plan: &[EditOperation]) -> String {
+    let mut preview = String::new();
+    for (idx, edit) in plan.iter().enumerate() {
+        preview.push_str(&format!("Edit #{} {} - {}\n", idx + 1, edit.file_path, edit.description));
+        for line in edit.old_content.lines() {
+            preview.push_str(&format!("  -{}\n", line));
+        }
+        for line in edit.new_content.lines() {
+            preview.push_str(&format!("  +{}\n", line));
+        }
+        preview.push('\n');
+    }
+    preview
+}
@@ -1,5 +1,6 @@
-use anyhow::{Context, Result};
-use std::path::Path;
+use anyhow::Result;
+use apchat_toolcore::workspace::resolve_path;
+use std::path::{Path, PathBuf};
 use std::ops::RangeInclusive;
 use std::fs;
 use thiserror::Error;
@@ -24,18 +25,22 @@ const MAX_FILE_SIZE: usize = 1024 * 1024; // 1 MiB
 
 /// Open a file within the given workspace, optionally returning only a line range.
 ///
-/// * `work_dir` – The root workspace directory. The function ensures the resolved file stays inside this directory.
+/// * `work_dir` – The root workspace directory. The function ensures the resolved file stays inside this directory
+///   or one of `allowed_roots`.
 /// * `file_path` – Path relative to the workspace.
 /// * `line_range` – Optional inclusive 1‑based line range. If `None`, the whole file is returned.
 pub async fn open_file(
     work_dir: &Path,
-    file_path: impl AsRef<Path>,
+    allowed_roots: &[PathBuf],
+    file_path: &str,
     line_range: Option<RangeInclusive<usize>>,
 ) -> Result<String> {
-    // Resolve the absolute path
-    let abs_path = work_dir.join(file_path.as_ref());
+    // Resolve the absolute path, with `..` and symlinks followed
+    let abs_path = resolve_path(work_dir, allowed_roots, file_path)
+        .map_err(|e| OpenFileError::PermissionDenied(format!("{:#}", e)))?
+        .absolute;
 
-    // Check if the exact path exists before trying to canonicalize
+    // Check if the path exists
     if !abs_path.exists() {
         // Provide helpful error message if there's a similar directory
         let file_name = abs_path.file_name()
@@ -70,35 +75,22 @@ pub async fn open_file(
         return Err(OpenFileError::FileNotFound(abs_path.display().to_string()).into());
     }
 
-    // Now canonicalize the existing path
-    let canonical = abs_path.canonicalize().with_context(|| {
-        format!("Failed to canonicalize path: {}", abs_path.display())
-    })?;
-
-    let work_canonical = work_dir.canonicalize().with_context(|| {
-        format!("Failed to canonicalize workspace dir: {}", work_dir.display())
-    })?;
-
-    if !canonical.starts_with(&work_canonical) {
-        anyhow::bail!(OpenFileError::PermissionDenied(canonical.display().to_string()));
-    }
-
     // Check if it's a directory instead of a file
-    if canonical.is_dir() {
+    if abs_path.is_dir() {
         return Err(OpenFileError::FileNotFound(format!(
             "{} is a directory, not a file. Use list_files to see its contents.",
-            canonical.display()
+            abs_path.display()
         )).into());
     }
 
     // Size check
-    let metadata = fs::metadata(&canonical)?;
+    let metadata = fs::metadata(&abs_path)?;
     if metadata.len() > MAX_FILE_SIZE as u64 {
         return Err(OpenFileError::FileTooLarge(metadata.len() as usize).into());
     }
 
     // Read file content as UTF-8
-    let raw_bytes = fs::read(&canonical)?;
+    let raw_bytes = fs::read(&abs_path)?;
     let content = String::from_utf8(raw_bytes).map_err(|_| OpenFileError::BinaryFileNotSupported)?;
 
     // If a line range is requested, slice the lines
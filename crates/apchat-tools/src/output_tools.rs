@@ -0,0 +1,141 @@
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects, READ_TOOL_OUTPUT};
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::ReadToolOutputArgs;
+use apchat_models::ToolArgs;
+use async_trait::async_trait;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Lines longer than this are cut when shown
+const MAX_LINE_CHARS: usize = 2000;
+
+/// Room kept free at the end of a page for the continuation hint
+const CONTINUATION_RESERVE: usize = 64;
+
+/// Tool for paging through tool output that was too large for the conversation
+pub struct ReadToolOutputTool {
+    max_bytes: usize,
+}
+
+impl ReadToolOutputTool {
+    /// Pages are kept within `max_bytes`, the limit above which output is
+    /// spilled, so a page never has to be spilled itself
+    pub fn new(max_bytes: usize) -> Self {
+        Self { max_bytes }
+    }
+}
+
+#[async_trait]
+impl Tool for ReadToolOutputTool {
+    fn name(&self) -> &str {
+        READ_TOOL_OUTPUT
+    }
+
+    fn description(&self) -> &str {
+        "Read tool output that was too large to show in full. Pass the handle from the excerpted result; page with start_line and lines, filter with grep (a regex), or use tail to see the last lines."
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        ReadToolOutputArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let ReadToolOutputArgs { handle, start_line, lines: max_lines, grep, tail } =
+            match ReadToolOutputArgs::parse(&params) {
+                Ok(args) => args,
+                Err(e) => return ToolResult::error(e.to_string()),
+            };
+
+        let Some(ref store) = context.artifacts else {
+            return ToolResult::error("No saved tool output in this session".to_string());
+        };
+        let output = match store.read(&handle) {
+            Ok(output) => output,
+            Err(e) => return ToolResult::error(format!("{:#}", e)),
+        };
+
+        let pattern = match grep.as_deref().map(Regex::new).transpose() {
+            Ok(pattern) => pattern,
+            Err(e) => return ToolResult::error(format!("Invalid grep pattern: {}", e)),
+        };
+
+        let all: Vec<&str> = output.lines().collect();
+        let total = all.len();
+        let candidates: Vec<(usize, &str)> = all
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+            .filter(|(_, line)| pattern.as_ref().is_none_or(|pattern| pattern.is_match(line)))
+            .collect();
+        let matched = candidates.len();
+
+        let (shown, more) = match tail {
+            Some(count) => (candidates[matched.saturating_sub(count.min(max_lines))..].to_vec(), false),
+            None => {
+                let from: Vec<(usize, &str)> = candidates
+                    .into_iter()
+                    .filter(|(number, _)| *number >= start_line)
+                    .collect();
+                let more = from.len() > max_lines;
+                (from.into_iter().take(max_lines).collect(), more)
+            }
+        };
+
+        let header = |last: Option<usize>| {
+            let mut header = match (shown.first(), last) {
+                (Some((first, _)), Some(last)) => {
+                    format!("Output '{}': lines {}-{} of {}", handle, first, last, total)
+                }
+                _ => format!("Output '{}': no lines to show ({} lines in total)", handle, total),
+            };
+            if let Some(ref grep) = grep {
+                header.push_str(&format!(", {} line(s) match '{}'", matched, grep));
+            }
+            header
+        };
+
+        // Keep the page within the byte budget. It always shows at least one
+        // line, so continuing from the hint makes progress.
+        let widest_header = header(shown.last().map(|(number, _)| *number)).len();
+        let budget = self.max_bytes.saturating_sub(widest_header + CONTINUATION_RESERVE);
+        let mut body = String::new();
+        let mut last_shown = None;
+        let mut next = None;
+        for (number, line) in &shown {
+            let mut entry = if line.chars().count() > MAX_LINE_CHARS {
+                format!("\n{:>6}  {}...", number, line.chars().take(MAX_LINE_CHARS).collect::<String>())
+            } else {
+                format!("\n{:>6}  {}", number, line)
+            };
+            if body.len() + entry.len() > budget {
+                if last_shown.is_some() {
+                    next = Some(*number);
+                    break;
+                }
+                let mut end = budget.max(12).min(entry.len());
+                while !entry.is_char_boundary(end) {
+                    end -= 1;
+                }
+                entry.truncate(end);
+                entry.push_str("...");
+            }
+            body.push_str(&entry);
+            last_shown = Some(*number);
+        }
+        if more {
+            next = next.or(last_shown.map(|last| last + 1));
+        }
+
+        let mut content = header(last_shown);
+        content.push_str(&body);
+        if let Some(next) = next {
+            content.push_str(&format!("\n[More lines follow; continue with start_line={}]", next));
+        }
+
+        ToolResult::success(content)
+    }
+}
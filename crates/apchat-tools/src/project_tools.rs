@@ -1,5 +1,7 @@
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::ProjectAnalysisArgs;
+use apchat_models::ToolArgs;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use tokio::process::Command as AsyncCommand;
@@ -18,19 +20,18 @@ impl Tool for ProjectAnalysisTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("analysis_type", "string", "Type of analysis to perform (structure, dependencies, file_types)", required),
-            param!("target_path", "string", "Target path for analysis (optional)", optional),
-        ])
+        ProjectAnalysisArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let analysis_type = match params.get_required::<String>("analysis_type") {
-            Ok(t) => t,
+        let ProjectAnalysisArgs { analysis_type, target_path } = match ProjectAnalysisArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
-
-        let target_path = params.get_optional::<String>("target_path").unwrap_or(None);
         let path = target_path.as_deref().unwrap_or(".");
 
         match analysis_type.as_str() {
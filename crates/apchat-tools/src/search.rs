@@ -1,5 +1,7 @@
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::SearchFilesArgs;
+use apchat_models::ToolArgs;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use regex::Regex;
@@ -19,37 +21,19 @@ impl Tool for SearchFilesTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("query", "string", "Text or pattern to search for", required),
-            param!("pattern", "string", "File pattern to search in (e.g., 'src/**/*.rs', '**/*.py'). Use ** for recursive search. Defaults to '**/*.rs' (all Rust files)", optional, "**/*.rs"),
-            param!("regex", "boolean", "Use regex search instead of plain text", optional, false),
-            param!("case_insensitive", "boolean", "Case insensitive search", optional, false),
-            param!("max_results", "integer", "Maximum number of results to return", optional, 50),
-        ])
+        SearchFilesArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let query = match params.get_required::<String>("query") {
-            Ok(query) => query,
+        let SearchFilesArgs { query, pattern, regex: use_regex, case_insensitive, max_results } = match SearchFilesArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        let pattern = params.get_optional::<String>("pattern")
-            .unwrap_or(Some("**/*.rs".to_string()))
-            .unwrap_or_else(|| "**/*.rs".to_string());
-
-        let use_regex = params.get_optional::<bool>("regex")
-            .unwrap_or(Some(false))
-            .unwrap_or(false);
-
-        let case_insensitive = params.get_optional::<bool>("case_insensitive")
-            .unwrap_or(Some(false))
-            .unwrap_or(false);
-
-        let max_results = params.get_optional::<i32>("max_results")
-            .unwrap_or(Some(50))
-            .unwrap_or(50) as usize;
-
         // Build search pattern
         let search_regex = if use_regex {
             match Regex::new(&query) {
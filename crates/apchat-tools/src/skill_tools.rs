@@ -1,5 +1,7 @@
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::{FindRelevantSkillsArgs, LoadSkillArgs};
+use apchat_models::ToolArgs;
 use async_trait::async_trait;
 use std::collections::HashMap;
 
@@ -17,14 +19,16 @@ impl Tool for LoadSkillTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("skill_name", "string", "Name of the skill to load (e.g., 'test-driven-development', 'systematic-debugging')", required),
-        ])
+        LoadSkillArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let skill_name = match params.get_required::<String>("skill_name") {
-            Ok(name) => name,
+        let LoadSkillArgs { skill_name } = match LoadSkillArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
@@ -75,6 +79,10 @@ impl Tool for ListSkillsTool {
         HashMap::new()
     }
 
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
     async fn execute(&self, _params: ToolParameters, context: &ToolContext) -> ToolResult {
         // Get skill registry from context
         let skill_registry = match &context.skill_registry {
@@ -115,14 +123,16 @@ impl Tool for FindRelevantSkillsTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("task_description", "string", "Description of the task you're about to perform", required),
-        ])
+        FindRelevantSkillsArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let task_description = match params.get_required::<String>("task_description") {
-            Ok(desc) => desc,
+        let FindRelevantSkillsArgs { task_description } = match FindRelevantSkillsArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
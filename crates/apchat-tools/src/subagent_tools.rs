@@ -1,10 +1,66 @@
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::LaunchSubagentArgs;
+use apchat_models::ToolArgs;
+use apchat_toolcore::{ConfirmationProvider, ConfirmationRequest, ConfirmationResponse, EscalatingConfirmation, ESCALATION_PREFIX};
 use async_trait::async_trait;
 use std::collections::HashMap;
-use std::process::Command;
+use std::process::{Output, Stdio};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
 use serde_json;
 
+/// Run a subagent process, answering the confirmation requests it escalates
+/// (see `--escalate-confirmations`) through the parent's confirmation provider
+async fn run_subagent_process(mut cmd: Command, context: &ToolContext) -> std::io::Result<Output> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take();
+    let mut stdout_pipe = child.stdout.take().expect("stdout is piped");
+    let stderr_pipe = child.stderr.take().expect("stderr is piped");
+
+    let stdout_task = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        stdout_pipe.read_to_end(&mut buf).await.map(|_| buf)
+    });
+
+    let provider = EscalatingConfirmation::new("subagent".to_string(), context.confirmation_provider());
+    let mut stderr = Vec::new();
+    let mut lines = BufReader::new(stderr_pipe).lines();
+    while let Some(line) = lines.next_line().await? {
+        let Some(payload) = line.strip_prefix(ESCALATION_PREFIX) else {
+            stderr.extend_from_slice(line.as_bytes());
+            stderr.push(b'\n');
+            continue;
+        };
+
+        let response = match serde_json::from_str::<ConfirmationRequest>(payload) {
+            Ok(request) => provider
+                .confirm(&request)
+                .await
+                .unwrap_or_else(|e| ConfirmationResponse::deny(Some(format!("Confirmation failed: {}", e)))),
+            Err(e) => ConfirmationResponse::deny(Some(format!("Malformed confirmation request: {}", e))),
+        };
+
+        if let Some(ref mut stdin) = stdin {
+            let answer = serde_json::to_string(&response).unwrap_or_default();
+            stdin.write_all(format!("{}\n", answer).as_bytes()).await?;
+            stdin.flush().await?;
+        }
+    }
+
+    // Close stdin so a subagent still waiting for an answer sees EOF
+    drop(stdin);
+    let status = child.wait().await?;
+    let stdout = stdout_task.await.map_err(std::io::Error::other)??;
+
+    Ok(Output { status, stdout, stderr })
+}
+
 /// Tool for launching a subagent to execute a task independently
 pub struct LaunchSubagentTool;
 
@@ -19,42 +75,61 @@ impl Tool for LaunchSubagentTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("task", "string", "The task description for the subagent to execute", required),
-            param!("working_directory", "string", "Optional working directory for the subagent (defaults to current directory)", optional),
-            param!("timeout_seconds", "integer", "Optional timeout in seconds (defaults to 300)", optional),
-            param!("auto_confirm", "boolean", "Whether to auto-confirm all actions without prompting (defaults to true)", optional),
-        ])
+        LaunchSubagentArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let task = match params.get_required::<String>("task") {
-            Ok(task) => task,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
+        let LaunchSubagentArgs { task, working_directory, timeout_seconds, auto_confirm } =
+            match LaunchSubagentArgs::parse(&params) {
+                Ok(args) => args,
+                Err(e) => return ToolResult::error(e.to_string()),
+            };
+        let working_directory =
+            working_directory.unwrap_or_else(|| context.work_dir.to_string_lossy().to_string());
 
-        let working_directory = params.get_optional::<String>("working_directory")
-            .unwrap_or_else(|_| Some(context.work_dir.to_string_lossy().to_string()))
-            .unwrap_or_else(|| context.work_dir.to_string_lossy().to_string());
+        let (approved, rejection_reason) = match context.check_permission(
+            apchat_policy::ActionType::SubagentLaunch,
+            &task,
+            "Launch subagent for this task? (y/N):"
+        ).await {
+            Ok((approved, reason)) => (approved, reason),
+            Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
+        };
 
-        let timeout_seconds = params.get_optional::<i64>("timeout_seconds")
-            .unwrap_or_else(|_| Some(300))
-            .unwrap_or(300);
-        let auto_confirm = params.get_optional::<bool>("auto_confirm")
-            .unwrap_or_else(|_| Some(true))
-            .unwrap_or(true);
+        if !approved {
+            let error_msg = if let Some(reason) = rejection_reason {
+                format!("Subagent launch cancelled by user: {}", reason)
+            } else {
+                "Subagent launch cancelled by user or policy".to_string()
+            };
+            return ToolResult::error(error_msg);
+        }
 
         // Build the apchat command
         let mut cmd = Command::new("apchat");
-        cmd.arg("--task")
-           .arg(&task)
-           .arg("--auto-confirm"); // Always use auto-confirm for subagent calls
+        cmd.arg("--task").arg(&task);
+        if auto_confirm {
+            cmd.arg("--auto-confirm");
+        } else {
+            // Policy questions go back to this session
+            cmd.arg("--escalate-confirmations");
+        }
+
+        // The subagent may reach the same directories as this session
+        for root in &context.allowed_roots {
+            cmd.arg("--allow-dir").arg(context.work_dir.join(root));
+        }
 
         // Add model parameter if current model string is available
         if let Some(model_string) = &context.current_model_string {
             cmd.arg("--model").arg(model_string);
         }
 
+        // The subagent gets no tools this session was not offered
+        if let Some(profile) = &context.tool_profile {
+            cmd.arg("--tool-profile").arg(profile);
+        }
+
         if working_directory != context.work_dir.to_string_lossy() {
             cmd.current_dir(&working_directory);
         }
@@ -65,12 +140,9 @@ impl Tool for LaunchSubagentTool {
         }
 
         // Execute the command
-        let output = match tokio::task::spawn_blocking(move || {
-            cmd.output()
-        }).await {
-            Ok(Ok(output)) => output,
-            Ok(Err(e)) => return ToolResult::error(format!("Failed to execute subagent: {}", e)),
-            Err(e) => return ToolResult::error(format!("Task join error: {}", e)),
+        let output = match run_subagent_process(cmd, context).await {
+            Ok(output) => output,
+            Err(e) => return ToolResult::error(format!("Failed to execute subagent: {}", e)),
         };
 
         // Parse the JSON output
@@ -128,43 +200,63 @@ impl Tool for LaunchSubagentPrettyTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("task", "string", "The task description for the subagent to execute", required),
-            param!("working_directory", "string", "Optional working directory for the subagent (defaults to current directory)", optional),
-            param!("timeout_seconds", "integer", "Optional timeout in seconds (defaults to 300)", optional),
-            param!("auto_confirm", "boolean", "Whether to auto-confirm all actions without prompting (defaults to true)", optional),
-        ])
+        LaunchSubagentArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let task = match params.get_required::<String>("task") {
-            Ok(task) => task,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
+        let LaunchSubagentArgs { task, working_directory, timeout_seconds, auto_confirm } =
+            match LaunchSubagentArgs::parse(&params) {
+                Ok(args) => args,
+                Err(e) => return ToolResult::error(e.to_string()),
+            };
+        let working_directory =
+            working_directory.unwrap_or_else(|| context.work_dir.to_string_lossy().to_string());
 
-        let working_directory = params.get_optional::<String>("working_directory")
-            .unwrap_or_else(|_| Some(context.work_dir.to_string_lossy().to_string()))
-            .unwrap_or_else(|| context.work_dir.to_string_lossy().to_string());
+        let (approved, rejection_reason) = match context.check_permission(
+            apchat_policy::ActionType::SubagentLaunch,
+            &task,
+            "Launch subagent for this task? (y/N):"
+        ).await {
+            Ok((approved, reason)) => (approved, reason),
+            Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
+        };
 
-        let timeout_seconds = params.get_optional::<i64>("timeout_seconds")
-            .unwrap_or_else(|_| Some(300))
-            .unwrap_or(300);
-        let auto_confirm = params.get_optional::<bool>("auto_confirm")
-            .unwrap_or_else(|_| Some(true))
-            .unwrap_or(true);
+        if !approved {
+            let error_msg = if let Some(reason) = rejection_reason {
+                format!("Subagent launch cancelled by user: {}", reason)
+            } else {
+                "Subagent launch cancelled by user or policy".to_string()
+            };
+            return ToolResult::error(error_msg);
+        }
 
         // Build the apchat command with --pretty flag
         let mut cmd = Command::new("apchat");
         cmd.arg("--task")
            .arg(&task)
-           .arg("--pretty")
-           .arg("--auto-confirm");
+           .arg("--pretty");
+        if auto_confirm {
+            cmd.arg("--auto-confirm");
+        } else {
+            // Policy questions go back to this session
+            cmd.arg("--escalate-confirmations");
+        }
+
+        // The subagent may reach the same directories as this session
+        for root in &context.allowed_roots {
+            cmd.arg("--allow-dir").arg(context.work_dir.join(root));
+        }
 
         // Add model parameter if current model string is available
         if let Some(model_string) = &context.current_model_string {
             cmd.arg("--model").arg(model_string);
         }
 
+        // The subagent gets no tools this session was not offered
+        if let Some(profile) = &context.tool_profile {
+            cmd.arg("--tool-profile").arg(profile);
+        }
+
         if working_directory != context.work_dir.to_string_lossy() {
             cmd.current_dir(&working_directory);
         }
@@ -175,12 +267,9 @@ impl Tool for LaunchSubagentPrettyTool {
         }
 
         // Execute the command
-        let output = match tokio::task::spawn_blocking(move || {
-            cmd.output()
-        }).await {
-            Ok(Ok(output)) => output,
-            Ok(Err(e)) => return ToolResult::error(format!("Failed to execute subagent: {}", e)),
-            Err(e) => return ToolResult::error(format!("Task join error: {}", e)),
+        let output = match run_subagent_process(cmd, context).await {
+            Ok(output) => output,
+            Err(e) => return ToolResult::error(format!("Failed to execute subagent: {}", e)),
         };
 
         // Parse the JSON output
@@ -1,10 +1,16 @@
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::RunCommandArgs;
+use apchat_models::ToolArgs;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use tokio::io::AsyncReadExt;
 use tokio::process::Command as AsyncCommand;
 use colored::Colorize;
 use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
 
 /// Tool for running shell commands
 pub struct RunCommandTool;
@@ -20,41 +26,39 @@ impl Tool for RunCommandTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("command", "string", "Shell command to execute", required),
-        ])
+        RunCommandArgs::parameters()
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_secs(300))
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let command = match params.get_required::<String>("command") {
-            Ok(command) => command,
+        let RunCommandArgs { command } = match RunCommandArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        // Basic security checks - prevent dangerous commands
-        let dangerous_patterns = [
-            "rm -rf /",
-            "sudo rm",
-            ":(){ :|:& };:",
-            "chmod -R 777 /",
-            "dd if=",
-        ];
-
-        for pattern in &dangerous_patterns {
-            if command.contains(pattern) {
-                return ToolResult::error(format!("Command blocked for security reasons: contains dangerous pattern '{}'", pattern));
-            }
+        // Dangerous commands are caught by the builtin deny rules; every
+        // simple command in a compound command line is checked separately
+        let policy_decision = context.policy_manager.decide(&apchat_policy::ActionType::CommandExecution, &command);
+        if policy_decision.decision == apchat_policy::Decision::Deny {
+            return ToolResult::error(match policy_decision.reason() {
+                Some(reason) => format!("Command blocked by policy: {} ({})", reason, policy_decision.source()),
+                None => format!("Command blocked by policy: {}", policy_decision.source()),
+            });
         }
 
         // Check permission using policy system
         print!("{} {} ", "Run command:".yellow(), command.cyan());
         std::io::stdout().flush().ok();
 
-        let (approved, rejection_reason) = match context.check_permission(
+        let (approved, rejection_reason) = match context.resolve_decision(
             apchat_policy::ActionType::CommandExecution,
             &command,
-            "Execute? (y/N):"
-        ) {
+            "Execute? (y/N):",
+            policy_decision,
+        ).await {
             Ok((approved, reason)) => (approved, reason),
             Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
         };
@@ -70,48 +74,103 @@ impl Tool for RunCommandTool {
 
         println!("{} {}", "Running:".green(), command.cyan());
 
-        // Parse command and arguments
-        let orig_command = command.clone();
-        let parts: Vec<&str> = command.trim().split_whitespace().collect();
-        if parts.is_empty() {
+        if command.trim().is_empty() {
             return ToolResult::error("Empty command".to_string());
         }
 
-        let (cmd, args) = parts.split_first().unwrap();
-
-        // Execute command in work directory
-        let output = match AsyncCommand::new("bash")
-            .args(["-c", &orig_command])
-            .current_dir(&context.work_dir)
-            .output()
-            .await
-        {
-            Ok(output) => output,
-            Err(e) => {
-                return ToolResult::error(format!("Failed to execute command: {}", e));
-            }
-        };
+        run_shell_command(&command, &context.work_dir, context).await
+    }
+}
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-
-        let result = if !stderr.is_empty() {
-            format!(
-                "Command: {}\nExit code: {}\nSTDOUT:\n{}\nSTDERR:\n{}",
-                command,
-                output.status.code().unwrap_or(-1),
-                stdout,
-                stderr
-            )
-        } else {
-            format!(
-                "Command: {}\nExit code: {}\nSTDOUT:\n{}",
-                command,
-                output.status.code().unwrap_or(-1),
-                stdout
-            )
-        };
+/// Run a command line with bash in `dir`, stopping it when the call is
+/// cancelled or times out
+pub(crate) async fn run_shell_command(command: &str, dir: &Path, context: &ToolContext) -> ToolResult {
+    // Execute command in its own process group so everything it starts can
+    // be killed together
+    let mut shell = AsyncCommand::new("bash");
+    shell
+        .args(["-c", command])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    shell.process_group(0);
+
+    let mut child = match shell.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            return ToolResult::error(format!("Failed to execute command: {}", e));
+        }
+    };
+
+    // Read output as it arrives, so it is still there if the command is stopped
+    let stdout_reader = tokio::spawn(read_pipe(child.stdout.take()));
+    let stderr_reader = tokio::spawn(read_pipe(child.stderr.take()));
+
+    let status = tokio::select! {
+        status = child.wait() => Some(status),
+        _ = context.cancellation.cancelled() => None,
+    };
+    if status.is_none() {
+        kill_process_group(&mut child);
+        let _ = child.wait().await;
+    }
+
+    let stdout = String::from_utf8_lossy(&stdout_reader.await.unwrap_or_default()).into_owned();
+    let stderr = String::from_utf8_lossy(&stderr_reader.await.unwrap_or_default()).into_owned();
+
+    let status = match status {
+        Some(Ok(status)) => status,
+        Some(Err(e)) => return ToolResult::error(format!("Failed to execute command: {}", e)),
+        None => {
+            let stopped = match context.deadline {
+                Some(ref deadline) if deadline.is_expired() => {
+                    format!("Command timed out after {:?}", deadline.limit())
+                }
+                _ => "Command cancelled".to_string(),
+            };
+            return ToolResult::error(format!(
+                "{}, partial output:\n{}",
+                stopped,
+                format_output(command, None, &stdout, &stderr)
+            ));
+        }
+    };
 
-        ToolResult::success(result)
+    let exit_code = status.code().unwrap_or(-1);
+    ToolResult::success(format_output(command, Some(exit_code), &stdout, &stderr)).with_exit_code(exit_code)
+}
+
+fn format_output(command: &str, exit_code: Option<i32>, stdout: &str, stderr: &str) -> String {
+    let mut result = format!("Command: {}\n", command);
+    if let Some(code) = exit_code {
+        result.push_str(&format!("Exit code: {}\n", code));
+    }
+    result.push_str(&format!("STDOUT:\n{}", stdout));
+    if !stderr.is_empty() {
+        result.push_str(&format!("\nSTDERR:\n{}", stderr));
+    }
+    result
+}
+
+async fn read_pipe(pipe: Option<impl tokio::io::AsyncRead + Unpin>) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_end(&mut buffer).await;
+    }
+    buffer
+}
+
+/// Kill a command and every process it started
+fn kill_process_group(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // A negative pid signals the whole process group
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGKILL);
+        }
     }
+    let _ = child.start_kill();
 }
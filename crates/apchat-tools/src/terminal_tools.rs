@@ -1,11 +1,21 @@
 // LLM tool implementations for terminal session management
 
-use apchat_toolcore::{param, {Tool, ToolParameters, ToolResult, ParameterDefinition}};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::{
+    PtyGetScreenArgs, PtyLaunchArgs, PtyRequestUserInputArgs, PtyResizeArgs, PtySendKeysArgs, PtySessionArgs,
+    PtySetScrollbackArgs,
+};
+use apchat_models::ToolArgs;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::time::Duration;
 use serde_json::json;
 
+/// Default time limit for tools that talk to a PTY, which can block when the
+/// session stops responding
+const PTY_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Tool for launching a new PTY terminal session
 pub struct PtyLaunchTool;
 
@@ -20,19 +30,18 @@ impl Tool for PtyLaunchTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("command", "string", "Command to run in the terminal (default: shell)", optional),
-            param!("working_dir", "string", "Working directory for the session (default: current)", optional),
-            param!("cols", "integer", "Terminal width in columns (default: 80)", optional),
-            param!("rows", "integer", "Terminal height in rows (default: 24)", optional),
-        ])
+        PtyLaunchArgs::parameters()
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(PTY_TIMEOUT)
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let command = params.get_optional::<String>("command").unwrap_or(None);
-        let working_dir_str = params.get_optional::<String>("working_dir").unwrap_or(None);
-        let cols = params.get_optional::<i32>("cols").unwrap_or(None).map(|c| c as u16).unwrap_or(80);
-        let rows = params.get_optional::<i32>("rows").unwrap_or(None).map(|r| r as u16).unwrap_or(24);
+        let PtyLaunchArgs { command, working_dir: working_dir_str, cols, rows } = match PtyLaunchArgs::parse(&params) {
+            Ok(args) => args,
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
 
         // Resolve working directory
         let working_dir = if let Some(dir_str) = &working_dir_str {
@@ -53,6 +62,27 @@ impl Tool for PtyLaunchTool {
             None => return ToolResult::error("Terminal manager not available".to_string()),
         };
 
+        // Policy rules see the shell that will actually run
+        let launched = command.clone()
+            .unwrap_or_else(|| std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string()));
+        let (approved, rejection_reason) = match context.check_permission(
+            apchat_policy::ActionType::TerminalLaunch,
+            &launched,
+            &format!("Launch terminal running '{}'? (y/N):", launched)
+        ).await {
+            Ok((approved, reason)) => (approved, reason),
+            Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
+        };
+
+        if !approved {
+            let error_msg = if let Some(reason) = rejection_reason {
+                format!("Terminal launch cancelled by user: {}", reason)
+            } else {
+                "Terminal launch cancelled by user or policy".to_string()
+            };
+            return ToolResult::error(error_msg);
+        }
+
         // Create session
         let command_for_display = command.clone().unwrap_or_else(|| "default shell (user's SHELL or /bin/bash)".to_string());
         let mut manager = terminal_manager.lock().await;
@@ -93,26 +123,18 @@ impl Tool for PtySendKeysTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to send keys to", required),
-            param!("keys", "string", "Keys to send to the terminal. Will be auto-terminated with 'Enter' key unless raw=true is supplied.", required),
-            param!("raw", "boolean", "Do not add 'Enter' key at the end of the series of keystrokes", required),
-        ])
+        PtySendKeysArgs::parameters()
     }
 
-    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(PTY_TIMEOUT)
+    }
 
-        let mut keys = match params.get_required::<String>("keys") {
-            Ok(k) => k,
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let PtySendKeysArgs { session_id, mut keys, raw } = match PtySendKeysArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
-        let raw = params.get_optional::<bool>("raw")
-            .unwrap_or_else(|_| Some(false))
-            .unwrap_or(false);
 
         // Get terminal manager from context
         let terminal_manager = match &context.terminal_manager {
@@ -120,6 +142,25 @@ impl Tool for PtySendKeysTool {
             None => return ToolResult::error("Terminal manager not available".to_string()),
         };
 
+        // Whatever is typed into a shell runs as a command, so it is checked like one
+        let (approved, rejection_reason) = match context.check_permission(
+            apchat_policy::ActionType::TerminalInput,
+            &keys,
+            &format!("Send '{}' to terminal {}? (y/N):", keys, session_id)
+        ).await {
+            Ok((approved, reason)) => (approved, reason),
+            Err(e) => return ToolResult::error(format!("Permission check failed: {}", e)),
+        };
+
+        if !approved {
+            let error_msg = if let Some(reason) = rejection_reason {
+                format!("Terminal input cancelled by user: {}", reason)
+            } else {
+                "Terminal input cancelled by user or policy".to_string()
+            };
+            return ToolResult::error(error_msg);
+        }
+
         // Send keys
         let mut manager = terminal_manager.lock().await;
         if ! raw {
@@ -146,22 +187,23 @@ impl Tool for PtyGetScreenTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to get screen from", required),
-            param!("include_colors", "boolean", "Include ANSI color codes (default: false)", optional),
-            param!("include_cursor", "boolean", "Include cursor position (default: true)", optional),
-        ])
+        PtyGetScreenArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(PTY_TIMEOUT)
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
+        let PtyGetScreenArgs { session_id, include_colors, include_cursor } = match PtyGetScreenArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        let include_colors = params.get_optional::<bool>("include_colors").unwrap_or(Some(false)).unwrap_or(false);
-        let include_cursor = params.get_optional::<bool>("include_cursor").unwrap_or(Some(true)).unwrap_or(true);
-
         // Get terminal manager from context
         let terminal_manager = match &context.terminal_manager {
             Some(tm) => tm,
@@ -217,6 +259,10 @@ impl Tool for PtyListTool {
         HashMap::new()
     }
 
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
     async fn execute(&self, _params: ToolParameters, context: &ToolContext) -> ToolResult {
         // Get terminal manager from context
         let terminal_manager = match &context.terminal_manager {
@@ -266,14 +312,12 @@ impl Tool for PtyKillTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to kill", required),
-        ])
+        PtySessionArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
+        let PtySessionArgs { session_id } = match PtySessionArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
@@ -305,14 +349,16 @@ impl Tool for PtyGetCursorTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to get cursor from", required),
-        ])
+        PtySessionArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
+        let PtySessionArgs { session_id } = match PtySessionArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
@@ -350,26 +396,12 @@ impl Tool for PtyResizeTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to resize", required),
-            param!("cols", "integer", "New terminal width in columns", required),
-            param!("rows", "integer", "New terminal height in rows", required),
-        ])
+        PtyResizeArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
-
-        let cols = match params.get_required::<i32>("cols") {
-            Ok(c) => c as u16,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
-
-        let rows = match params.get_required::<i32>("rows") {
-            Ok(r) => r as u16,
+        let PtyResizeArgs { session_id, cols, rows } = match PtyResizeArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
@@ -408,20 +440,12 @@ impl Tool for PtySetScrollbackTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to configure", required),
-            param!("lines", "integer", "Number of scrollback lines to keep", required),
-        ])
+        PtySetScrollbackArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
-
-        let lines = match params.get_required::<i32>("lines") {
-            Ok(l) => l as usize,
+        let PtySetScrollbackArgs { session_id, lines } = match PtySetScrollbackArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
@@ -458,14 +482,12 @@ impl Tool for PtyStartCaptureTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to start capturing", required),
-        ])
+        PtySessionArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
+        let PtySessionArgs { session_id } = match PtySessionArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
@@ -505,14 +527,12 @@ impl Tool for PtyStopCaptureTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to stop capturing", required),
-        ])
+        PtySessionArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
+        let PtySessionArgs { session_id } = match PtySessionArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
@@ -551,28 +571,15 @@ impl Tool for PtyRequestUserInputTool {
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("session_id", "string", "Session ID to hand over to user", required),
-            param!("message", "string", "Message to display to the user explaining what's needed", required),
-            param!("timeout_seconds", "integer", "Timeout in seconds (default: 300/5 minutes)", optional),
-        ])
+        PtyRequestUserInputArgs::parameters()
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
-        let session_id = match params.get_required::<String>("session_id") {
-            Ok(id) => id,
+        let PtyRequestUserInputArgs { session_id, message, timeout_seconds } = match PtyRequestUserInputArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        let message = match params.get_required::<String>("message") {
-            Ok(msg) => msg,
-            Err(e) => return ToolResult::error(e.to_string()),
-        };
-
-        let timeout_seconds = params.get_optional::<i32>("timeout_seconds")
-            .unwrap_or(Some(300))
-            .unwrap_or(300) as u64;
-
         let terminal_manager = match &context.terminal_manager {
             Some(tm) => tm,
             None => return ToolResult::error("Terminal manager not available".to_string()),
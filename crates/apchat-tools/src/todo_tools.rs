@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
 
-use apchat_toolcore::{param, Tool, ToolParameters, ToolResult, ParameterDefinition};
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects};
 use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::{TodoItemArgs, TodoWriteArgs};
+use apchat_models::ToolArgs;
 use apchat_todo::{Task, TaskStatus};
 
 /// Tool for managing the todo list
@@ -43,9 +45,11 @@ Task Format:
     }
 
     fn parameters(&self) -> HashMap<String, ParameterDefinition> {
-        HashMap::from([
-            param!("todos", "string", "JSON array of tasks with content (imperative form), status (pending/in_progress/completed), and activeForm (present continuous form)", required),
-        ])
+        TodoWriteArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::MutatesWorkspace
     }
 
     async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
@@ -55,45 +59,19 @@ Task Format:
             None => return ToolResult::error("Todo manager not available".to_string()),
         };
 
-        // Parse todos JSON string
-        let todos_str = match params.get_required::<String>("todos") {
-            Ok(todos) => todos,
+        let TodoWriteArgs { todos } = match TodoWriteArgs::parse(&params) {
+            Ok(args) => args,
             Err(e) => return ToolResult::error(e.to_string()),
         };
 
-        let todos_value: serde_json::Value = match serde_json::from_str(&todos_str) {
-            Ok(value) => value,
-            Err(e) => return ToolResult::error(format!("Failed to parse todos JSON: {}", e)),
-        };
-
-        let todos_array = match todos_value.as_array() {
-            Some(arr) => arr,
-            None => return ToolResult::error("Todos parameter must be a JSON array".to_string()),
-        };
-
         // Parse tasks
         let mut tasks = Vec::new();
-        for (idx, todo_val) in todos_array.iter().enumerate() {
-            let content = match todo_val.get("content").and_then(|v| v.as_str()) {
-                Some(c) => c.to_string(),
-                None => return ToolResult::error(format!("Task {} missing 'content'", idx + 1)),
-            };
-
-            let status_str = match todo_val.get("status").and_then(|v| v.as_str()) {
-                Some(s) => s,
-                None => return ToolResult::error(format!("Task {} missing 'status'", idx + 1)),
-            };
-
-            let status = match status_str {
+        for (idx, TodoItemArgs { content, status, active_form }) in todos.into_iter().enumerate() {
+            let status = match status.as_str() {
                 "pending" => TaskStatus::Pending,
                 "in_progress" => TaskStatus::InProgress,
                 "completed" => TaskStatus::Completed,
-                _ => return ToolResult::error(format!("Task {} has invalid status: {}", idx + 1, status_str)),
-            };
-
-            let active_form = match todo_val.get("activeForm").and_then(|v| v.as_str()) {
-                Some(af) => af.to_string(),
-                None => return ToolResult::error(format!("Task {} missing 'activeForm'", idx + 1)),
+                _ => return ToolResult::error(format!("Task {} has invalid status: {}", idx + 1, status)),
             };
 
             let mut task = Task::new(content, active_form);
@@ -149,6 +127,10 @@ impl Tool for TodoListTool {
         HashMap::new()
     }
 
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
     async fn execute(&self, _params: ToolParameters, context: &ToolContext) -> ToolResult {
         let todo_manager = match context.todo_manager.as_ref() {
             Some(tm) => tm,
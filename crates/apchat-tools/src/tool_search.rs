@@ -0,0 +1,65 @@
+use apchat_toolcore::{Tool, ToolParameters, ToolResult, ParameterDefinition, SideEffects, SEARCH_TOOLS};
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_models::types::SearchToolsArgs;
+use apchat_models::ToolArgs;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Tool for finding tools that are not sent with every request
+pub struct SearchToolsTool;
+
+#[async_trait]
+impl Tool for SearchToolsTool {
+    fn name(&self) -> &str {
+        SEARCH_TOOLS
+    }
+
+    fn description(&self) -> &str {
+        "Find more tools. Only a few tools are available at first; search by keyword (e.g. 'terminal screen', 'database migrate') or category to find others. The tools found can be called from then on."
+    }
+
+    fn parameters(&self) -> HashMap<String, ParameterDefinition> {
+        SearchToolsArgs::parameters()
+    }
+
+    fn side_effects(&self) -> SideEffects {
+        SideEffects::ReadOnly
+    }
+
+    async fn execute(&self, params: ToolParameters, context: &ToolContext) -> ToolResult {
+        let SearchToolsArgs { query, category, limit } = match SearchToolsArgs::parse(&params) {
+            Ok(args) => args,
+            Err(e) => return ToolResult::error(e.to_string()),
+        };
+        if query.trim().is_empty() && category.is_none() {
+            return ToolResult::error("Give a query, a category, or both".to_string());
+        }
+
+        let Some(ref search) = context.tool_search else {
+            return ToolResult::error("Tool search is not enabled in this session; all tools are already available".to_string());
+        };
+        let matches = search.search(&query, category.as_deref(), limit);
+        if matches.is_empty() {
+            return ToolResult::success(format!("No tools match '{}'", query));
+        }
+
+        search.activate(matches.iter().map(|tool| tool.name.as_str()));
+        let tools: Vec<_> = matches
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "categories": tool.categories,
+                    "parameters": tool.parameters,
+                })
+            })
+            .collect();
+        ToolResult::success(format!(
+            "Found {} tool(s); they can be called from now on:\n{}",
+            matches.len(),
+            serde_json::to_string_pretty(&tools).unwrap_or_default()
+        ))
+    }
+}
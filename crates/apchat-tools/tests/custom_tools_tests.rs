@@ -0,0 +1,184 @@
+use apchat_policy::{AuditLog, PolicyManager};
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{SideEffects, Tool, ToolParameters};
+use apchat_tools::custom_tools::{custom_tools_dir, load_custom_tools, CustomTool};
+use std::time::Duration;
+use tempfile::TempDir;
+
+const LINT_PROTO: &str = r#"
+name = "lint_proto"
+description = "Lint a protobuf file"
+command = "echo linting {{file}} {{strict}}"
+timeout_secs = 60
+side_effects = "read_only"
+
+[parameters.file]
+type = "string"
+description = "Proto file to lint"
+
+[parameters.strict]
+type = "boolean"
+default = false
+"#;
+
+fn params(json: &str) -> ToolParameters {
+    ToolParameters::from_json(json).unwrap()
+}
+
+#[test]
+fn test_manifest_defines_tool() {
+    let tool = CustomTool::parse(LINT_PROTO).unwrap();
+
+    assert_eq!(tool.name(), "lint_proto");
+    assert_eq!(tool.side_effects(), SideEffects::ReadOnly);
+    assert_eq!(tool.default_timeout(), Some(Duration::from_secs(60)));
+
+    let parameters = tool.parameters();
+    assert!(parameters["file"].required);
+    assert_eq!(parameters["strict"].param_type, "boolean");
+    assert!(!parameters["strict"].required);
+}
+
+#[test]
+fn test_render_quotes_arguments() {
+    let tool = CustomTool::parse(LINT_PROTO).unwrap();
+
+    assert_eq!(
+        tool.render(&params(r#"{"file": "api/v1.proto"}"#)),
+        "echo linting 'api/v1.proto' 'false'"
+    );
+    assert_eq!(
+        tool.render(&params(r#"{"file": "x'; rm -rf / #", "strict": true}"#)),
+        r"echo linting 'x'\''; rm -rf / #' 'true'"
+    );
+}
+
+#[test]
+fn test_invalid_manifests_are_rejected() {
+    let undeclared = r#"
+name = "deploy_preview"
+description = "Deploy"
+command = "deploy {{branch}}"
+"#;
+    let error = CustomTool::parse(undeclared).unwrap_err();
+    assert!(error.to_string().contains("{{branch}}"));
+
+    let escaping = r#"
+name = "deploy_preview"
+description = "Deploy"
+command = "deploy"
+working_dir = "../elsewhere"
+"#;
+    assert!(CustomTool::parse(escaping).is_err());
+
+    let array = r#"
+name = "deploy_preview"
+description = "Deploy"
+command = "deploy {{targets}}"
+
+[parameters.targets]
+type = "array"
+"#;
+    assert!(CustomTool::parse(array).is_err());
+}
+
+#[test]
+fn test_quoted_placeholders_are_rejected() {
+    let manifest = |command: &str| {
+        format!(
+            "name = \"commit\"\ndescription = \"Commit\"\ncommand = {}\n\n[parameters.msg]\ntype = \"string\"\n",
+            toml::Value::String(command.to_string())
+        )
+    };
+
+    // Inside quotes the rendered quoting would let `$(...)` in the argument run
+    for command in [r#"git commit -m "{{msg}}""#, "git commit -m '{{msg}}'", r#"sh -c "echo {{msg}} done""#] {
+        let error = CustomTool::parse(&manifest(command)).unwrap_err();
+        assert!(error.to_string().contains("inside quotes"), "{}: {}", command, error);
+    }
+
+    // Quotes that are closed or escaped before the placeholder are fine
+    for command in [
+        "git commit -m {{msg}}",
+        r#"git commit --author "A B" -m {{msg}}"#,
+        r#"echo \" {{msg}}"#,
+        r#"echo "it's" {{msg}}"#,
+    ] {
+        let tool = CustomTool::parse(&manifest(command)).unwrap();
+        assert!(tool.render(&params(r#"{"msg": "$(id)"}"#)).ends_with("'$(id)'"));
+    }
+}
+
+#[test]
+fn test_load_custom_tools_reports_bad_manifests() {
+    let work_dir = TempDir::new().unwrap();
+    let dir = custom_tools_dir(work_dir.path());
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a_lint.toml"), LINT_PROTO).unwrap();
+    std::fs::write(dir.join("b_broken.toml"), "name = ").unwrap();
+    std::fs::write(dir.join("notes.md"), "not a manifest").unwrap();
+
+    let loaded = load_custom_tools(&dir);
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded[0].as_ref().unwrap().name(), "lint_proto");
+    assert!(format!("{:#}", loaded[1].as_ref().unwrap_err()).contains("b_broken.toml"));
+
+    assert!(load_custom_tools(&work_dir.path().join("missing")).is_empty());
+}
+
+#[tokio::test]
+async fn test_custom_tool_runs_in_working_dir() {
+    let work_dir = TempDir::new().unwrap();
+    std::fs::create_dir(work_dir.path().join("proto")).unwrap();
+    let tool = CustomTool::parse(
+        r#"
+name = "where"
+description = "Print the directory"
+command = "pwd && echo {{greeting}}"
+working_dir = "proto"
+
+[parameters.greeting]
+type = "string"
+"#,
+    )
+    .unwrap();
+    let audit = AuditLog::new(work_dir.path().join("policy_audit.jsonl"));
+    let context = ToolContext::new(
+        work_dir.path().to_path_buf(),
+        "test_session".to_string(),
+        PolicyManager::allow_all().with_audit_log(audit.clone()),
+    );
+
+    let result = tool.execute(params(r#"{"greeting": "$HOME"}"#), &context).await;
+
+    assert!(result.success, "{:?}", result.error);
+    assert!(result.content.contains("/proto\n"));
+    assert!(result.content.contains("$HOME"));
+    assert_eq!(result.metadata.exit_code, Some(0));
+    // One policy decision per call
+    assert_eq!(audit.read(None).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_builtin_deny_rules_apply_to_any_action() {
+    let work_dir = TempDir::new().unwrap();
+    let tool = CustomTool::parse(
+        r#"
+name = "cleanup"
+description = "Clean up"
+command = "rm -rf /"
+action = "file_delete"
+"#,
+    )
+    .unwrap();
+    let context = ToolContext::new(
+        work_dir.path().to_path_buf(),
+        "test_session".to_string(),
+        PolicyManager::new(),
+    );
+
+    let result = tool.execute(ToolParameters::new(), &context).await;
+
+    assert!(!result.success);
+    assert!(result.error.unwrap().contains("blocked by policy"));
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use apchat_policy::PolicyManager;
+use apchat_toolcore::confirmation::ScriptedConfirmation;
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{ConfirmationResponse, ToolParameters, ToolRegistry};
+use apchat_tools::{ApplyEditPlanTool, EditPlanConfirmation, PlanEditsTool};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_edit_plan_is_confirmed_through_the_provider() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("lib.rs");
+    std::fs::write(&file, "fn old() {}\n").unwrap();
+
+    let mut registry = ToolRegistry::new();
+    registry.register(PlanEditsTool);
+    registry.register(ApplyEditPlanTool);
+    registry.add_middleware(EditPlanConfirmation);
+    let provider = Arc::new(ScriptedConfirmation::new([
+        ConfirmationResponse::deny(Some("wrong name".to_string())),
+        ConfirmationResponse::approve(),
+    ]));
+    let context = ToolContext::new(temp_dir.path().to_path_buf(), "edit_plan_test".to_string(), PolicyManager::new())
+        .with_confirmation_provider(provider.clone());
+    let plan = r#"{"edits": [{"file_path": "lib.rs", "old_content": "fn old() {}", "new_content": "fn new() {}", "description": "Rename"}]}"#;
+
+    // A refusal keeps the tool from running, passes the reason on and drops the plan
+    let result = registry.execute_tool("plan_edits", ToolParameters::from_json(plan).unwrap(), &context).await;
+    assert!(result.success, "{:?}", result.error);
+    let result = registry.execute_tool("apply_edit_plan", ToolParameters::new(), &context).await;
+    assert_eq!(result.error.as_deref(), Some("Edit plan application cancelled by user - wrong name"));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn old() {}\n");
+    assert!(!temp_dir.path().join(".apchat_edit_plan.json").exists());
+
+    let result = registry.execute_tool("plan_edits", ToolParameters::from_json(plan).unwrap(), &context).await;
+    assert!(result.success, "{:?}", result.error);
+    let result = registry.execute_tool("apply_edit_plan", ToolParameters::new(), &context).await;
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn new() {}\n");
+
+    let requests = provider.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].target, "lib.rs");
+    assert_eq!(
+        requests[0].preview.as_deref(),
+        Some("Edit #1 lib.rs - Rename\n  -fn old() {}\n  +fn new() {}\n\n")
+    );
+}
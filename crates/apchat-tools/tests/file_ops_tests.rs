@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use apchat_policy::{ActionType, Decision, PolicyManager, PolicyRule};
+use apchat_toolcore::confirmation::ScriptedConfirmation;
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{Tool, ToolParameters};
+use apchat_tools::{OpenFileTool, ReadFileTool, WriteFileTool};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_file_tools_check_read_and_write_rules() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join(".env"), "TOKEN=1\n").unwrap();
+    std::fs::write(temp_dir.path().join("README.md"), "# Notes\n").unwrap();
+
+    let policy_manager = PolicyManager::new();
+    policy_manager.add_session_rule(PolicyRule::new(ActionType::FileRead, ".env".to_string(), Decision::Deny));
+    policy_manager.add_session_rule(PolicyRule::new(ActionType::FileWrite, "docs/**".to_string(), Decision::Allow));
+    policy_manager.add_session_rule(PolicyRule::new(ActionType::FileWrite, "Cargo.toml".to_string(), Decision::Deny));
+    // Nothing may be asked: every decision here comes from a rule, or from
+    // reads being allowed without one
+    let provider = Arc::new(ScriptedConfirmation::new([]));
+    let context = ToolContext::new(temp_dir.path().to_path_buf(), "file_ops_test".to_string(), policy_manager)
+        .with_confirmation_provider(provider.clone());
+    let params = |json: &str| ToolParameters::from_json(json).unwrap();
+
+    let result = ReadFileTool.execute(params(r#"{"file_path": ".env"}"#), &context).await;
+    assert!(result.error.unwrap().contains("Denied by policy"));
+    let result = OpenFileTool.execute(params(r#"{"file_path": "./.env"}"#), &context).await;
+    assert!(result.error.unwrap().contains("Denied by policy"));
+    let result = ReadFileTool.execute(params(r#"{"file_path": "README.md"}"#), &context).await;
+    assert!(result.success, "{:?}", result.error);
+
+    let result = WriteFileTool.execute(params(r#"{"file_path": "Cargo.toml", "content": "[package]"}"#), &context).await;
+    assert!(result.error.unwrap().contains("Denied by policy"));
+    assert!(!temp_dir.path().join("Cargo.toml").exists());
+    let result = WriteFileTool.execute(params(r#"{"file_path": "docs/guide.md", "content": "hello"}"#), &context).await;
+    assert!(result.success, "{:?}", result.error);
+    assert!(provider.requests().is_empty());
+}
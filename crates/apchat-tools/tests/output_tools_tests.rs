@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use apchat_policy::PolicyManager;
+use apchat_toolcore::artifacts::ArtifactStore;
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{Tool, ToolParameters};
+use apchat_tools::ReadToolOutputTool;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_read_tool_output_pages_fit_the_spill_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = Arc::new(ArtifactStore::new(temp_dir.path().join("artifacts")));
+    let output = vec!["x".repeat(1000); 200].join("\n");
+    let handle = store.save("run_command", &output).unwrap();
+    let context = ToolContext::new(temp_dir.path().to_path_buf(), "output_test".to_string(), PolicyManager::new())
+        .with_artifact_store(store);
+
+    let tool = ReadToolOutputTool::new(4000);
+    let params = ToolParameters::from_json(&format!(r#"{{"handle": "{}", "lines": 200}}"#, handle)).unwrap();
+    let result = tool.execute(params, &context).await;
+    assert!(result.success, "{:?}", result.error);
+    assert!(result.content.len() <= 4000, "page is {} bytes", result.content.len());
+    assert!(result.content.starts_with(&format!("Output '{}': lines 1-3 of 200", handle)));
+    assert!(result.content.ends_with("[More lines follow; continue with start_line=4]"));
+
+    // A line larger than the whole budget is cut rather than skipped
+    let params = ToolParameters::from_json(&format!(r#"{{"handle": "{}", "start_line": 4}}"#, handle)).unwrap();
+    let result = ReadToolOutputTool::new(500).execute(params, &context).await;
+    assert!(result.content.len() <= 500, "page is {} bytes", result.content.len());
+    assert!(result.content.contains("     4  xxx"));
+    assert!(result.content.ends_with("[More lines follow; continue with start_line=5]"));
+}
@@ -0,0 +1,85 @@
+use apchat_policy::PolicyManager;
+use apchat_toolcore::tool_context::ToolContext;
+use apchat_toolcore::{ToolParameters, ToolProfiles, ToolRegistry, SEARCH_TOOLS};
+use apchat_tools::{ListFilesTool, PtyGetScreenTool, PtyLaunchTool, ReadFileTool, RunCommandTool, SearchToolsTool};
+use tempfile::TempDir;
+
+fn registry() -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    registry.register_with_categories(ReadFileTool, vec!["file_ops".to_string()]);
+    registry.register_with_categories(ListFilesTool, vec!["file_ops".to_string()]);
+    registry.register_with_categories(RunCommandTool, vec!["system".to_string()]);
+    registry.register_with_categories(PtyLaunchTool, vec!["terminal".to_string()]);
+    registry.register_with_categories(PtyGetScreenTool, vec!["terminal".to_string()]);
+    registry.register(SearchToolsTool);
+    registry.enable_tool_search(["read_file", "list_files"]);
+    registry
+}
+
+fn sent_tools(registry: &ToolRegistry) -> Vec<String> {
+    registry
+        .get_openai_tool_definitions()
+        .iter()
+        .map(|definition| definition["function"]["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn search(registry: &ToolRegistry, arguments: &str) -> String {
+    let temp_dir = TempDir::new().unwrap();
+    let context = ToolContext::new(temp_dir.path().to_path_buf(), "search_test".to_string(), PolicyManager::new());
+    let result = registry
+        .execute_tool(SEARCH_TOOLS, ToolParameters::from_json(arguments).unwrap(), &context)
+        .await;
+    assert!(result.success, "{:?}", result.error);
+    result.content
+}
+
+#[tokio::test]
+async fn test_found_tools_are_appended() {
+    let registry = registry();
+    assert_eq!(sent_tools(&registry), vec!["list_files", "read_file", SEARCH_TOOLS]);
+
+    let found = search(&registry, r#"{"query": "screen"}"#).await;
+    assert!(found.starts_with("Found 1 tool(s)"));
+    assert!(found.contains("\"name\": \"pty_get_screen\""));
+    assert!(found.contains("\"terminal\""));
+    assert_eq!(sent_tools(&registry), vec!["list_files", "read_file", SEARCH_TOOLS, "pty_get_screen"]);
+
+    // Later finds go after earlier ones, and finding a tool again changes nothing
+    search(&registry, r#"{"query": "", "category": "terminal"}"#).await;
+    search(&registry, r#"{"query": "command"}"#).await;
+    assert_eq!(
+        sent_tools(&registry),
+        vec!["list_files", "read_file", SEARCH_TOOLS, "pty_get_screen", "pty_launch", "run_command"]
+    );
+
+    // Clones of the registry share what was found
+    assert_eq!(sent_tools(&registry.clone()).len(), 6);
+}
+
+#[tokio::test]
+async fn test_search_skips_core_and_disabled_tools() {
+    let mut registry = registry();
+    assert!(search(&registry, r#"{"query": "read file"}"#).await.starts_with("No tools match"));
+
+    registry.set_enabled("run_command", false);
+    assert!(!search(&registry, r#"{"query": "run_command"}"#).await.contains("\"name\": \"run_command\""));
+
+    // Profiles leave the search tool enabled
+    registry.apply_profile(ToolProfiles::builtin().get("readonly").unwrap());
+    assert_eq!(sent_tools(&registry), vec!["list_files", "read_file", SEARCH_TOOLS]);
+}
+
+#[tokio::test]
+async fn test_search_needs_tool_search() {
+    let mut registry = ToolRegistry::new();
+    registry.register(SearchToolsTool);
+    let temp_dir = TempDir::new().unwrap();
+    let context = ToolContext::new(temp_dir.path().to_path_buf(), "search_test".to_string(), PolicyManager::new());
+
+    let result = registry
+        .execute_tool(SEARCH_TOOLS, ToolParameters::from_json(r#"{"query": "pty"}"#).unwrap(), &context)
+        .await;
+    assert!(!result.success);
+    assert!(result.error.unwrap().contains("not enabled"));
+}
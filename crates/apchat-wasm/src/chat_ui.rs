@@ -7,6 +7,7 @@ use std::cell::RefCell;
 use gloo_net::websocket::futures::WebSocket;
 use futures::{StreamExt, SinkExt};
 use crate::protocol::{ClientMessage, ServerMessage, Message};
+use serde_json::Value;
 use crate::dom;
 use crate::markdown;
 use crate::utils;
@@ -309,8 +310,9 @@ impl ChatApp {
                 result,
                 success,
                 formatted_result,
+                metadata,
             } => {
-                self.handle_tool_result(document, tool_call_id, result, success, formatted_result)?;
+                self.handle_tool_result(document, tool_call_id, result, success, formatted_result, metadata)?;
             }
 
             ServerMessage::TaskProgress {
@@ -694,6 +696,7 @@ impl ChatApp {
         result: String,
         success: bool,
         formatted_result: Option<String>,
+        metadata: Option<Value>,
     ) -> Result<(), JsValue> {
         if let Some(tool_element) = document.get_element_by_id(&format!("tool-{}", tool_call_id)) {
             // Remove confirmation buttons/status if present
@@ -712,10 +715,33 @@ impl ChatApp {
             result_div.set_class_name(if success { "tool-result success" } else { "tool-result error" });
 
             let display_result = formatted_result.unwrap_or(result);
+            let mut header = if success { "✓ Success" } else { "✗ Error" }.to_string();
+            if let Some(summary) = metadata.as_ref().map(summarize_metadata).filter(|s| !s.is_empty()) {
+                header.push_str(&format!(" · {}", summary));
+            }
+            // Output spilled to the artifact store can be opened in full
+            let links: String = metadata
+                .as_ref()
+                .and_then(|m| m.get("artifacts"))
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .filter_map(|path| path.rsplit(['/', '\\']).next()?.strip_suffix(".txt"))
+                .map(|handle| {
+                    format!(
+                        r#"<a class="tool-artifact" href="/api/sessions/{}/artifacts/{}" target="_blank">Full output ({})</a>"#,
+                        utils::escape_html(&self.session_id),
+                        utils::escape_html(handle),
+                        utils::escape_html(handle)
+                    )
+                })
+                .collect();
             result_div.set_inner_html(&format!(
-                r#"<div class="result-header">{}</div><pre><code>{}</code></pre>"#,
-                if success { "✓ Success" } else { "✗ Error" },
-                utils::escape_html(&display_result)
+                r#"<div class="result-header">{}</div><pre><code>{}</code></pre>{}"#,
+                utils::escape_html(&header),
+                utils::escape_html(&display_result),
+                links
             ));
 
             tool_element.append_child(&result_div)?;
@@ -932,3 +958,23 @@ async fn send_message_handler(
 
     Ok(())
 }
+
+/// One-line summary of a tool result's metadata, e.g. "exit 0 · 2 file(s) changed · 120ms"
+fn summarize_metadata(metadata: &Value) -> String {
+    let count = |key: &str| metadata.get(key).and_then(Value::as_array).map_or(0, Vec::len);
+    let mut parts = Vec::new();
+    if let Some(code) = metadata.get("exit_code").and_then(Value::as_i64) {
+        parts.push(format!("exit {}", code));
+    }
+    let changed = count("files_written") + count("files_deleted");
+    if changed > 0 {
+        parts.push(format!("{} file(s) changed", changed));
+    }
+    if metadata.get("truncated").and_then(Value::as_bool) == Some(true) {
+        parts.push("truncated".to_string());
+    }
+    if let Some(ms) = metadata.get("duration_ms").and_then(Value::as_u64) {
+        parts.push(format!("{}ms", ms));
+    }
+    parts.join(" · ")
+}